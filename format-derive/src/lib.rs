@@ -0,0 +1,197 @@
+//! `#[derive(FromFormat)]`: a line parser generated from a pattern.
+//!
+//! ```ignore
+//! #[derive(FromFormat)]
+//! #[format("{name} = ({left}, {right})")]
+//! struct Node<'a> { name: &'a str, left: &'a str, right: &'a str }
+//! ```
+//!
+//! Every field appears once as `{field}` and the text between them is
+//! matched literally, save that a space matches any run of whitespace.
+//! `{field:SEP}` splits a `Vec` field on `SEP` and parses each element.
+//! `{{` and `}}` are literal braces. The generated code calls into
+//! `crate::format`, so the derive is only for use inside the main crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, GenericParam, LitStr, Type};
+
+enum Segment {
+    Literal(String),
+    Field { name: String, sep: Option<String> },
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '}' => return Err("unmatched `}`; write `}}` for a literal brace".into()),
+            '{' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inner.push(c),
+                        None => return Err("unclosed `{` in format".into()),
+                    }
+                }
+                let (name, sep) = match inner.split_once(':') {
+                    Some((name, sep)) => (name, Some(sep.to_string())),
+                    None => (inner.as_str(), None),
+                };
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    return Err("empty placeholder `{}`; name the field it fills".into());
+                }
+                if sep.as_deref() == Some("") {
+                    return Err(format!("`{{{name}:}}` needs a separator after the `:`"));
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                } else if let Some(Segment::Field { name: prev, .. }) = segments.last() {
+                    return Err(format!("`{{{prev}}}` and `{{{name}}}` need some literal text between them"));
+                }
+                segments.push(Segment::Field { name, sep });
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+fn is_str_ref(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(r) => matches!(&*r.elem, Type::Path(p) if p.path.is_ident("str")),
+        _ => false,
+    }
+}
+
+#[proc_macro_derive(FromFormat, attributes(format))]
+pub fn derive_from_format(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(named) => &named.named,
+            _ => return Err(syn::Error::new_spanned(ident, "FromFormat needs a struct with named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(ident, "FromFormat only derives for structs")),
+    };
+
+    let attr = input.attrs.iter()
+        .find(|a| a.path().is_ident("format"))
+        .ok_or_else(|| syn::Error::new(Span::call_site(), "FromFormat needs a #[format(\"...\")] attribute"))?;
+    let lit: LitStr = attr.parse_args()?;
+    let pattern = lit.value();
+    let segments = parse_pattern(&pattern).map_err(|msg| syn::Error::new_spanned(&lit, msg))?;
+
+    // every field exactly once
+    let mut seen = Vec::new();
+    for seg in segments.iter() {
+        if let Segment::Field { name, .. } = seg {
+            if !fields.iter().any(|f| f.ident.as_ref().is_some_and(|i| i == name)) {
+                return Err(syn::Error::new_spanned(&lit, format!("`{ident}` has no field `{name}`")));
+            }
+            if seen.contains(&name) {
+                return Err(syn::Error::new_spanned(&lit, format!("`{{{name}}}` appears more than once")));
+            }
+            seen.push(name);
+        }
+    }
+    if let Some(f) = fields.iter().find(|f| !seen.iter().any(|n| f.ident.as_ref().is_some_and(|i| i == *n))) {
+        return Err(syn::Error::new_spanned(f, "field missing from the #[format] pattern"));
+    }
+
+    // scan the input, one step per segment
+    let mut steps = Vec::new();
+    let mut i = 0;
+    while i < segments.len() {
+        match &segments[i] {
+            Segment::Literal(l) => steps.push(quote! { scanner.literal(#l)?; }),
+            Segment::Field { name, .. } => {
+                let var = syn::Ident::new(&format!("field_{name}"), Span::call_site());
+                match segments.get(i + 1) {
+                    Some(Segment::Literal(l)) => {
+                        steps.push(quote! { let #var = scanner.until(#name, #l)?; });
+                        i += 1;
+                    }
+                    _ => steps.push(quote! { let #var = scanner.rest(); }),
+                }
+            }
+        }
+        i += 1;
+    }
+
+    // then turn each piece into its field
+    let inits = fields.iter().map(|f| {
+        let field = f.ident.as_ref().unwrap();
+        let name = field.to_string();
+        let var = syn::Ident::new(&format!("field_{name}"), Span::call_site());
+        let sep = segments.iter().find_map(|s| match s {
+            Segment::Field { name: n, sep } if *n == name => sep.clone(),
+            _ => None,
+        });
+        if is_str_ref(&f.ty) {
+            quote! { #field: #var }
+        } else if let Some(sep) = sep {
+            quote! { #field: scanner.list(#name, #var, #sep)? }
+        } else {
+            quote! { #field: scanner.field(#name, #var)? }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let lifetime = input.generics.params.iter().find_map(|p| match p {
+        GenericParam::Lifetime(l) => Some(&l.lifetime),
+        _ => None,
+    });
+    let input_ty = match lifetime {
+        Some(lt) => quote! { &#lt str },
+        None => quote! { &str },
+    };
+
+    let from_str = lifetime.is_none().then(|| quote! {
+        impl #impl_generics ::std::str::FromStr for #ident #ty_generics #where_clause {
+            type Err = crate::format::FormatError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                Self::from_format(s)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #[doc = concat!("Parses `", #pattern, "`.")]
+            pub fn from_format(input: #input_ty) -> ::std::result::Result<Self, crate::format::FormatError> {
+                let mut scanner = crate::format::Scanner::new(#pattern, input);
+                #(#steps)*
+                scanner.finish()?;
+                ::std::result::Result::Ok(Self { #(#inits),* })
+            }
+        }
+
+        #from_str
+    })
+}
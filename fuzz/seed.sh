@@ -0,0 +1,11 @@
+#!/bin/sh
+# Seeds each target's corpus with the bundled puzzle input.
+#
+#     fuzz/seed.sh && cargo +nightly fuzz run day07
+set -e
+cd "$(dirname "$0")"
+for input in ../src/day*/input; do
+    day=$(basename "$(dirname "$input")")
+    mkdir -p "corpus/$day"
+    cp "$input" "corpus/$day/input"
+done
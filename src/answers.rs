@@ -0,0 +1,159 @@
+//! Known-correct answers for the bundled inputs, kept in `answers.toml`.
+//!
+//! Only the bit of TOML the file needs is understood: a `[dayNN]` table per
+//! day with `part1`/`part2` keys, string or integer values, and comments.
+//!
+//! ```toml
+//! [day01]
+//! part1 = 55108
+//! part2 = "56324"
+//! ```
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+pub const PATH: &str = "answers.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    answers: BTreeMap<(u32, u8), String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong { expected: String },
+    /// Nothing recorded for this part yet.
+    Unknown,
+}
+
+impl Answers {
+    pub fn get(&self, day: u32, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|s| s.as_str())
+    }
+
+    /// Records a confirmed answer, returning the one it replaces. Only
+    /// what the file can hold is taken; see [`is_plain`].
+    pub fn record(&mut self, day: u32, part: u8, answer: &str) -> Result<Option<String>, String> {
+        if !is_plain(answer) {
+            return Err(format!("can't record {:?}: answers are printable ASCII without `\"`, `\\` or `#`", answer));
+        }
+        Ok(self.answers.insert((day, part), answer.to_string()))
+    }
+
+    pub fn check(&self, day: u32, part: u8, answer: &str) -> Verdict {
+        match self.get(day, part) {
+            None => Verdict::Unknown,
+            Some(expected) if expected == answer => Verdict::Correct,
+            Some(expected) => Verdict::Wrong { expected: expected.to_string() },
+        }
+    }
+}
+
+/// Whether `s` fits between the quotes of a value as it is: no escapes,
+/// nothing that reads as a comment, and all on one line.
+fn is_plain(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c == ' ' || c.is_ascii_graphic()) && !s.contains(['"', '\\', '#'])
+}
+
+fn parse_value(s: &str) -> Option<String> {
+    if let Some(s) = s.strip_prefix('"') {
+        let s = s.strip_suffix('"')?;
+        return is_plain(s).then(|| s.to_string());
+    }
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    (!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())).then(|| s.to_string())
+}
+
+impl FromStr for Answers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = Self::default();
+        let mut day = None;
+
+        for (i, line) in s.lines().enumerate() {
+            let err = |msg: &str| format!("{}:{}: {}", PATH, i + 1, msg);
+            let line = match line.split_once(" #") {
+                Some((line, _)) => line,
+                None => line,
+            }.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = line.strip_prefix('[') {
+                let d = table.strip_suffix(']')
+                    .and_then(|t| t.strip_prefix("day"))
+                    .and_then(|d| d.parse().ok())
+                    .filter(|d| (1..=25).contains(d))
+                    .ok_or_else(|| err("expected a table like [day01]"))?;
+                day = Some(d);
+                continue;
+            }
+
+            let day = day.ok_or_else(|| err("answer outside a [dayNN] table"))?;
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected part1 = ..."))?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                key => return Err(err(&format!("unknown key {}", key))),
+            };
+            let value = parse_value(value.trim()).ok_or_else(|| err("expected a number or a plain string"))?;
+            if answers.answers.insert((day, part), value).is_some() {
+                return Err(err(&format!("day {} part {} given twice", day, part)));
+            }
+        }
+        Ok(answers)
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Confirmed answers for the bundled inputs; see `record` in the CLI.")?;
+        let mut last_day = None;
+        for ((day, part), answer) in self.answers.iter() {
+            if last_day != Some(*day) {
+                write!(f, "\n[day{:02}]\n", day)?;
+                last_day = Some(*day);
+            }
+            writeln!(f, "part{} = \"{}\"", part, answer)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test() {
+    let answers: Answers = "
+# comment
+[day01]
+part1 = 142
+part2 = \"281\"  # trailing
+
+[day25]
+part1 = \"54\"
+".parse().unwrap();
+    assert_eq!(answers.get(1, 1), Some("142"));
+    assert_eq!(answers.get(25, 1), Some("54"));
+    assert_eq!(answers.check(1, 2, "281"), Verdict::Correct);
+    assert_eq!(answers.check(1, 2, "280"), Verdict::Wrong { expected: "281".into() });
+    assert_eq!(answers.check(2, 1, "8"), Verdict::Unknown);
+
+    // the file round-trips
+    assert_eq!(answers.to_string().parse::<Answers>().unwrap(), answers);
+
+    assert_eq!("part1 = 1".parse::<Answers>().unwrap_err(), "answers.toml:1: answer outside a [dayNN] table");
+    assert!("[day26]".parse::<Answers>().is_err());
+    assert!("[day01]\npart3 = 1".parse::<Answers>().is_err());
+    assert!("[day01]\npart1 = 1\npart1 = 2".parse::<Answers>().is_err());
+    assert!("[day01]\npart1 = 1x".parse::<Answers>().is_err());
+
+    // whatever gets recorded can be read back
+    let mut answers = Answers::default();
+    assert_eq!(answers.record(1, 1, "RGZUBJEP"), Ok(None));
+    assert_eq!(answers.record(1, 1, "1,2"), Ok(Some("RGZUBJEP".into())));
+    for bad in ["a\"b", "a\\b", "a #b", "a\nb", ""] {
+        assert!(answers.record(1, 2, bad).is_err(), "{:?}", bad);
+    }
+    assert_eq!(answers.to_string().parse::<Answers>().unwrap(), answers);
+}
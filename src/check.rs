@@ -0,0 +1,101 @@
+//! What the solvers take for granted about their input.
+//!
+//! Some days only come out fast, or at all, because the real inputs are
+//! built a particular way: a ghost's path that loops back exactly onto its
+//! first `Z`, a garden with a clear line out from the start. Each such day
+//! lists those properties as [`Assumption`]s that can be checked against an
+//! input before solving it.
+
+use std::fmt;
+
+use crate::{day06, day08, day10, day13, day17, day19, day20, day21, day22, day23, day24};
+
+/// One property of the input, and whether it holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assumption {
+    pub name: &'static str,
+    /// What breaks it, if it doesn't hold.
+    pub violation: Option<String>,
+}
+
+impl Assumption {
+    pub fn new(name: &'static str, result: Result<(), String>) -> Self {
+        Self { name, violation: result.err() }
+    }
+
+    /// Holds if `ok`, otherwise broken for the reason `why` gives.
+    pub fn check(name: &'static str, ok: bool, why: impl FnOnce() -> String) -> Self {
+        Self { name, violation: (!ok).then(why) }
+    }
+
+    /// For an input that doesn't even parse; nothing else is worth checking.
+    pub fn unparsable(why: impl fmt::Display) -> Vec<Self> {
+        vec![Self { name: "the input parses", violation: Some(why.to_string()) }]
+    }
+
+    pub fn holds(&self) -> bool {
+        self.violation.is_none()
+    }
+}
+
+impl fmt::Display for Assumption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.violation {
+            None => write!(f, "ok    {}", self.name),
+            Some(why) => write!(f, "FAIL  {}: {}", self.name, why),
+        }
+    }
+}
+
+pub const DAYS: [u32; 11] = [6, 8, 10, 13, 17, 19, 20, 21, 22, 23, 24];
+
+/// The assumptions `day`'s solvers make, checked against `input`.
+pub fn assumptions(day: u32, input: &str) -> Result<Vec<Assumption>, String> {
+    match day {
+        6 => Ok(day06::assumptions(input)),
+        8 => Ok(day08::assumptions(input)),
+        10 => Ok(day10::assumptions(input)),
+        13 => Ok(day13::assumptions(input)),
+        17 => Ok(day17::assumptions(input)),
+        19 => Ok(day19::assumptions(input)),
+        20 => Ok(day20::assumptions(input)),
+        21 => Ok(day21::assumptions(input)),
+        22 => Ok(day22::assumptions(input)),
+        23 => Ok(day23::assumptions(input)),
+        24 => Ok(day24::assumptions(input)),
+        _ => Err(format!("day {} makes no assumptions to check; try one of {:?}", day, DAYS)),
+    }
+}
+
+/// One line per assumption, prefixed with its day.
+pub fn report(results: &[(u32, Vec<Assumption>)]) -> String {
+    let mut out = String::new();
+    for (day, assumptions) in results {
+        for a in assumptions {
+            out += &format!("day {:>2}  {}\n", day, a);
+        }
+    }
+    let broken = results.iter().flat_map(|(_, v)| v).filter(|a| !a.holds()).count();
+    let total = results.iter().map(|(_, v)| v.len()).sum::<usize>();
+    out += &format!("{} of {} assumptions hold\n", total - broken, total);
+    out
+}
+
+#[test]
+fn test() {
+    let ok = Assumption::check("fine", true, || unreachable!());
+    let bad = Assumption::new("square", Err("3 by 4".to_string()));
+    assert!(ok.holds() && !bad.holds());
+    assert_eq!(bad.to_string(), "FAIL  square: 3 by 4");
+    assert_eq!(
+        report(&[(6, vec![ok]), (21, vec![bad])]),
+        "day  6  ok    fine\nday 21  FAIL  square: 3 by 4\n1 of 2 assumptions hold\n",
+    );
+    assert!(assumptions(1, "").is_err());
+
+    for day in DAYS {
+        let input = std::fs::read_to_string(format!("src/day{:02}/input", day)).unwrap();
+        let broken = assumptions(day, &input).unwrap().into_iter().filter(|a| !a.holds()).collect::<Vec<_>>();
+        assert!(broken.is_empty(), "day {}: {:?}", day, broken);
+    }
+}
@@ -57,12 +57,15 @@ What is the sum of all of the calibration values?
 
 */
 
-pub fn solve() {
-    let input = include_str!("input");
-    let input = input.split("\n").collect::<Vec<_>>();
-    // println!("input: {:#?}", input);
+use crate::input;
+
+/// The calibration lines; any text will do, as long as there is some.
+pub fn parse(input: &str) -> Option<Vec<&str>> {
+    input::lines(input).ok()
+}
 
-    let sum = input.iter()
+pub fn part_1(input: &str) -> u32 {
+    parse(input).unwrap().into_iter()
         .fold(0, |acc, s| {
             let first = if let Some(c) = s.chars().find(|c|c.is_ascii_digit()) {
                 c.to_digit(10).unwrap()
@@ -72,15 +75,24 @@ pub fn solve() {
             } else { 0 };
 
             acc + first * 10 + last
-        });
+        })
+}
+
+pub fn part_2(input: &str) -> u32 {
+    parse(input).unwrap().into_iter()
+        .fold(0, |acc, s| {
+            acc + find_first_digit(s) * 10 + find_last_digit(s)
+        })
+}
 
+pub fn solve() {
+    let input = include_str!("input");
+
+    let sum = part_1(input);
     println!("sum: {}", sum);
 
     // part two
-    let sum = input.iter()
-        .fold(0, |acc, s| {
-            acc + find_first_digit(s) * 10 + find_last_digit(s)
-        });
+    let sum = part_2(input);
     println!("sum: {}", sum);
 }
 
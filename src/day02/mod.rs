@@ -49,8 +49,10 @@ For each game, find the minimum set of cubes that must have been present. What i
 
 use std::str::FromStr;
 
+use crate::{format::FromFormat, input};
 
-#[derive(Debug)]
+#[derive(Debug, FromFormat)]
+#[format("Game {id}: {info:;}")]
 pub struct Game {
     pub id: u32,
     pub info: Vec<GameInfo>
@@ -70,56 +72,35 @@ impl FromStr for GameInfo {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut this = Self { red: 0, blue: 0, green: 0 };
         for color in s.split(",") {
-            let mut it = color.trim().split(" ");
-            let num: u32 = it.next().unwrap().parse().unwrap();
-            let c = it.next().unwrap();
+            let (num, c) = color.trim().split_once(" ").ok_or(())?;
+            // at most a `u16`, so the power of a set fits a `u64`
+            let num = u32::from(num.parse::<u16>().map_err(|_| ())?);
             match c {
                 "red" => this.red = num,
                 "green" => this.green = num,
                 "blue" => this.blue = num,
-                _ => unreachable!(),
+                _ => return Err(()),
             }
         }
         Ok(this)
     }
 }
 
-#[test]
-fn solve() {
-    let input = include_str!("input");
-    let games = input.split("\n")
-        .map(|s|{
-            let mut it = s.split(":");
-
-            let id = it.next().unwrap();
-
-            // Game 1
-            let id: u32 = id[5..].parse().unwrap();
-
-            let v = it.next().unwrap();
-            let vec: Vec<_> = v.split(";")
-                .map(|s|{
-                    let game_info: GameInfo = s.parse().unwrap();
-                    game_info
-                })
-                .collect();
+pub fn parse(input: &str) -> Option<Vec<Game>> {
+    input::lines(input).ok()?.into_iter()
+        .map(|s| s.trim().parse().ok())
+        .collect()
+}
 
-            Game {
-                id,
-                info: vec,
-            }
-        })
-        .collect::<Vec<_>>();
+pub fn part_1(input: &str) -> u64 {
+    let games = parse(input).unwrap();
 
-    println!("games: {:?}", games);
-
-    // Part One
     // 12 red, 13 green, 14 blue
     let red = 12;
     let green = 13;
     let blue = 14;
 
-    let sum: u32 = games.iter()
+    games.iter()
         .filter(|game| {
             game.info
                 .iter()
@@ -130,14 +111,15 @@ fn solve() {
                 })
         })
         .map(|game|{
-            game.id
+            u64::from(game.id)
         })
-        .sum();
+        .sum()
+}
 
-    println!("sum: {}", sum);
+pub fn part_2(input: &str) -> u64 {
+    let games = parse(input).unwrap();
 
-    // Part Two
-    let sum: u32 = games.iter()
+    games.iter()
         .map(|game|{
             let mut red = 0;
             let mut green = 0;
@@ -155,10 +137,24 @@ fn solve() {
                         blue = info.blue;
                     }
                 });
-            red * green * blue
+            u64::from(red) * u64::from(green) * u64::from(blue)
         })
-        .sum();
+        .sum()
+}
+
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let games = parse(input).unwrap();
 
+    println!("games: {:?}", games);
+
+    // Part One
+    let sum = part_1(input);
     println!("sum: {}", sum);
 
-}
+    // Part Two
+    let sum = part_2(input);
+    println!("sum: {}", sum);
+
+}
\ No newline at end of file
@@ -1,5 +1,7 @@
 use std::{ops::{Range, Index}, collections::HashSet};
 
+use crate::input;
+
 
 #[derive(Debug, Hash, PartialEq, Eq)]
 pub struct Num {
@@ -106,11 +108,18 @@ fn find_num(arr: &Vec<&str>, row: usize, col: usize) -> HashSet<Num> {
     all
 }
 
-#[test]
-fn solve() {
-    let input = include_str!("input");
-    let arr = input.split("\n")
-        .collect::<Vec<_>>();
+/// The schematic's rows; every number in it has to fit a `u16`, so a gear
+/// ratio fits a `u32` and the sums a `u64`.
+pub fn parse(input: &str) -> Option<Vec<&str>> {
+    let arr = input::grid(input).ok()?;
+    let fits = arr.iter()
+        .flat_map(|line| line.split(|c: char| !c.is_ascii_digit()))
+        .all(|n| n.is_empty() || n.parse::<u16>().is_ok());
+    fits.then_some(arr)
+}
+
+pub fn part_1(input: &str) -> u64 {
+    let arr = parse(input).unwrap();
 
     let mut all_rngs = HashSet::new();
 
@@ -129,14 +138,15 @@ fn solve() {
     let mut sum = 0;
     for rng in all_rngs {
         let line = arr[rng.row];
-        let d: u32 = line.index(rng.range).parse().unwrap();
+        let d: u64 = line.index(rng.range).parse().unwrap();
         sum += d;
     }
+    sum
+}
 
-    println!("sum: {}", sum);
-
+pub fn part_2(input: &str) -> u64 {
+    let arr = parse(input).unwrap();
 
-    // ------- Part Two -----------
     let mut sum = 0;
 
     for (row, line) in arr.iter().enumerate() {
@@ -150,12 +160,24 @@ fn solve() {
             let gear_first = it.next().unwrap();
             let gear_second = it.next().unwrap();
 
-            let first: u32 = arr[gear_first.row].index(gear_first.range.clone()).parse().unwrap();
-            let second: u32 = arr[gear_second.row].index(gear_second.range.clone()).parse().unwrap();
+            let first: u64 = arr[gear_first.row].index(gear_first.range.clone()).parse().unwrap();
+            let second: u64 = arr[gear_second.row].index(gear_second.range.clone()).parse().unwrap();
             sum += first * second;
         }
     }
+    sum
+}
+
+#[test]
+fn solve() {
+    let input = include_str!("input");
 
+    let sum = part_1(input);
+    println!("sum: {}", sum);
+
+
+    // ------- Part Two -----------
+    let sum = part_2(input);
     println!("sum: {}", sum);
 
 }
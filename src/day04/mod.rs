@@ -68,6 +68,8 @@ Your puzzle answer was [     ].
 
 use std::collections::HashSet;
 
+use crate::input;
+
 
 #[derive(Debug)]
 pub struct Card {
@@ -79,63 +81,72 @@ pub struct Card {
 }
 
 
-#[test]
-fn sovle() {
-    let input = include_str!("input");
-
-    let mut cards = input.split("\n")
+/// `Card n: winning | mine` lines; no card may win copies of cards past
+/// the end of the table, and the copies won, all told, have to fit a
+/// `usize`. Each card's `instance_count` is filled in.
+pub fn parse(input: &str) -> Option<Vec<Card>> {
+    let mut cards = input::lines(input).ok()?.into_iter()
         .map(|line| {
-            let mut it = line.split(":");
-            it.next();
-
-            let content = it.next().unwrap().trim();
-            let mut it = content.split("|");
-
-            let win_nums = it.next().unwrap().trim()
-                .split(" ")
+            let (_, content) = line.split_once(":")?;
+            let (win_nums, my_nums) = content.split_once("|")?;
+            let nums = |s: &str| s.split(" ")
                 .filter(|s|!s.is_empty())
-                .map(|s| {
-                    s.parse::<i32>().unwrap()
-                })
-                .collect::<HashSet<_>>();
+                .map(|s| s.parse::<i32>().ok())
+                .collect::<Option<HashSet<_>>>();
 
-            let my_nums = it.next().unwrap().trim()
-                .split(" ")
-                .filter(|s|!s.is_empty())
-                .map(|s| {
-                    s.parse::<i32>().unwrap()
-                })
-                .collect::<HashSet<_>>();
+            let win_nums = nums(win_nums)?;
+            let my_nums = nums(my_nums)?;
 
             let match_count = my_nums.intersection(&win_nums).count() as i32;
-            let points = if match_count == 0 {
-                0
-            } else {
-                2_i32.pow(match_count as u32 - 1)
+            // more than 31 matches would overflow the points
+            let points = match match_count {
+                0 => 0,
+                1..=31 => 2_i32.pow(match_count as u32 - 1),
+                _ => return None,
             };
 
-            Card {
+            Some(Card {
                 instance_count: 1,
                 win_nums,
                 my_nums,
                 match_count,
                 points,
-            }
+            })
         })
-        .collect::<Vec<_>>();
-
-    let sum: i32 = cards.iter().map(|c|c.points).sum();
-    println!("sum: {}", sum);
+        .collect::<Option<Vec<_>>>()?;
+    let n = cards.len();
+    if !cards.iter().enumerate().all(|(i, c)| i + (c.match_count as usize) < n) {
+        return None;
+    }
 
-    // Part Two
-    for i in 0..cards.len() {
+    for i in 0..n {
         let match_count = cards[i].match_count as usize;
         let ins_count = cards[i].instance_count;
         for j in 1..match_count + 1 {
-            cards[i+j].instance_count += ins_count;
+            cards[i+j].instance_count = cards[i+j].instance_count.checked_add(ins_count)?;
         }
     }
+    cards.iter().try_fold(0_usize, |acc, c| acc.checked_add(c.instance_count))?;
+    Some(cards)
+}
+
+pub fn part_1(input: &str) -> i64 {
+    parse(input).unwrap().iter().map(|c| i64::from(c.points)).sum()
+}
+
+pub fn part_2(input: &str) -> usize {
+    let cards = parse(input).unwrap();
+    cards.iter().map(|c|c.instance_count).sum()
+}
+
+#[test]
+fn sovle() {
+    let input = include_str!("input");
 
-    let sum: usize = cards.iter().map(|c|c.instance_count).sum();
+    let sum = part_1(input);
+    println!("sum: {}", sum);
+
+    // Part Two
+    let sum = part_2(input);
     println!("sum: {}", sum);
 }
@@ -1,4 +1,6 @@
-use std::{collections::{HashSet, HashMap}, str::FromStr, ops::Range, vec};
+use std::str::FromStr;
+
+use crate::{input, interval::{Interval, IntervalSet}};
 
 #[derive(Debug, Clone, Copy)]
 pub struct MyMap {
@@ -23,10 +25,11 @@ impl FromStr for MyMap {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut it = s.split(" ").filter(|s|!s.is_empty());
-        let dst = it.next().unwrap().parse().unwrap();
-        let src = it.next().unwrap().parse().unwrap();
-        let range_len = it.next().unwrap().parse().unwrap();
+        let nums = s.split(" ").filter(|s|!s.is_empty())
+            .map(parse_num)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(())?;
+        let [dst, src, range_len] = nums[..] else { return Err(()) };
         Ok(Self {
             src,
             dst,
@@ -36,7 +39,7 @@ impl FromStr for MyMap {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VecMyMap(pub Vec<MyMap>);
 
 impl VecMyMap {
@@ -49,50 +52,18 @@ impl VecMyMap {
         return key;
     }
 
-    pub fn get_dest_ranges(&self, range: Vec<Range<u128>>) -> Vec<Range<u128>> {
-        let mut vec = range;
-        let mut ret = Vec::new();
-
-        while !vec.is_empty() {
-            let range = vec.pop().unwrap();
-            let mut intersected = false;
-            for r in self.0.iter() {
-                // 没有交集
-                // xxxx
-                //     xxxx
-                if r.src+r.range_len <= range.start || range.end <= r.src {
-                    continue;
-                }
-                intersected = true;
-                // 有交集
-                // xxxxx
-                //   xxxxx
-                let start = r.src.max(range.start);
-                let end = (r.src+r.range_len).min(range.end);
-
-                let seg1 = range.start..start;
-                let seg2 = end..range.end;
-                if !seg1.is_empty() {
-                    vec.push(seg1);
-                }
-                if !seg2.is_empty() {
-                    vec.push(seg2);
-                }
-
-                let diff_start = start - r.src;
-                let len = end - start;
-                let dst_start = r.dst + diff_start;
-                ret.push(dst_start..dst_start+len);
-                break;
-            }
+    pub fn get_dest_ranges(&self, ranges: &IntervalSet<u128>) -> IntervalSet<u128> {
+        let mut rest = ranges.clone();
+        let mut ret = IntervalSet::new();
 
-            // 完全没有交集
-            if !intersected {
-                ret.push(range);
-            }
+        for r in self.0.iter() {
+            let src = IntervalSet::from_iter([Interval::new(r.src, r.src + r.range_len)]);
+            ret = ret.union(&rest.intersection(&src).shift(r.src, r.dst));
+            rest = rest.difference(&src);
         }
 
-        ret
+        // 完全没有交集
+        ret.union(&rest)
     }
 }
 
@@ -103,125 +74,199 @@ impl From<Vec<MyMap>> for VecMyMap {
 }
 
 
-#[test]
-fn solve() {
-    let input = include_str!("input");
+/// The seeds line and the seven maps from seed to location, in order.
+#[derive(Debug, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<u128>,
+    pub maps: Vec<VecMyMap>,
+}
 
-    let mut it = input.split("\n\n");
+/// Numbers are read as `u64`s, so sums of them don't overflow a `u128`.
+fn parse_num(s: &str) -> Option<u128> {
+    s.parse::<u64>().ok().map(u128::from)
+}
 
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
+/// `None` unless there's a seeds line with `start len` pairs in it, then
+/// maps that each have a header.
+pub fn parse(input: &str) -> Option<Almanac> {
+    let paragraphs = input::paragraphs(input).ok()?;
+    let mut it = paragraphs.into_iter();
+
+    let group = it.next()?;
+    let [line] = group[..] else { return None };
+    let nums = line.strip_prefix("seeds:")?;
     let seeds = nums.split(" ")
         .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<u128>().unwrap())
-        .collect::<Vec::<_>>();
-
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
-    let seed_to_soil: VecMyMap = nums.split("\n")
-        .filter(|s|!s.is_empty())
-        .map(|line|{
-            line.parse::<MyMap>().unwrap()
-        })
-        .collect::<Vec<_>>()
-        .into();
-
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
-    let soil_to_fertilizer: VecMyMap = nums.split("\n")
-        .filter(|s|!s.is_empty())
-        .map(|line|{
-            line.parse::<MyMap>().unwrap()
-        })
-        .collect::<Vec<_>>()
-        .into();
-
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
-    let fertilizer_to_water: VecMyMap = nums.split("\n")
-        .filter(|s|!s.is_empty())
-        .map(|line|{
-            line.parse::<MyMap>().unwrap()
-        })
-        .collect::<Vec<_>>()
-        .into();
-
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
-    let water_to_light: VecMyMap = nums.split("\n")
-        .filter(|s|!s.is_empty())
-        .map(|line|{
-            line.parse::<MyMap>().unwrap()
-        })
-        .collect::<Vec<_>>()
-        .into();
-
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
-    let light_to_temperature: VecMyMap = nums.split("\n")
-        .filter(|s|!s.is_empty())
-        .map(|line|{
-            line.parse::<MyMap>().unwrap()
-        })
-        .collect::<Vec<_>>()
-        .into();
-
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
-    let temperature_to_humidity: VecMyMap = nums.split("\n")
-        .filter(|s|!s.is_empty())
-        .map(|line|{
-            line.parse::<MyMap>().unwrap()
-        })
-        .collect::<Vec<_>>()
-        .into();
-
-    let group = it.next().unwrap();
-    let nums = group.split(":").skip(1).next().unwrap();
-    let humidity_to_location: VecMyMap = nums.split("\n")
-        .filter(|s|!s.is_empty())
-        .map(|line|{
-            line.parse::<MyMap>().unwrap()
-        })
-        .collect::<Vec<_>>()
-        .into();
-
-
-    let min = seeds.iter()
-        .map(|seed| {
-            let seed = seed_to_soil.get(*seed);
-            let seed = soil_to_fertilizer.get(seed);
-            let seed = fertilizer_to_water.get(seed);
-            let seed = water_to_light.get(seed);
-            let seed = light_to_temperature.get(seed);
-            let seed = temperature_to_humidity.get(seed);
-            let seed = humidity_to_location.get(seed);
-            seed
+        .map(parse_num)
+        .collect::<Option<Vec::<_>>>()?;
+    if seeds.is_empty() || seeds.len() % 2 != 0 {
+        return None;
+    }
+
+    // seed-to-soil, soil-to-fertilizer, ..., humidity-to-location
+    let maps = it
+        .map(|group| {
+            // the first line is `seed-to-soil map:`
+            if !group[0].trim_end().ends_with("map:") {
+                return None;
+            }
+            group[1..].iter()
+                .map(|line| line.parse::<MyMap>().ok())
+                .collect::<Option<Vec<_>>>()
+                .map(Into::into)
         })
-        .min()
-        .unwrap();
+        .collect::<Option<_>>()?;
 
-    println!("min: {}", min);
+    Some(Almanac { seeds, maps })
+}
 
-    // --------- Part Two -------------
-    let mut seed_ranges = Vec::new();
-    for i in (0..seeds.len()).step_by(2) {
-        let start =seeds[i];
-        let len = seeds[i + 1];
-        let range = start .. start + len;
-        seed_ranges.push(range);
+impl Almanac {
+    pub fn location(&self, seed: u128) -> u128 {
+        self.maps.iter().fold(seed, |seed, map| map.get(seed))
     }
 
+    /// The seeds line read as `start len` pairs.
+    pub fn seed_ranges(&self) -> IntervalSet<u128> {
+        self.seeds.chunks(2)
+            .map(|pair| Interval::new(pair[0], pair[0] + pair[1]))
+            .collect()
+    }
 
-    let ranges = seed_to_soil.get_dest_ranges(seed_ranges);
-    let ranges = soil_to_fertilizer.get_dest_ranges(ranges);
-    let ranges = fertilizer_to_water.get_dest_ranges(ranges);
-    let ranges = water_to_light.get_dest_ranges(ranges);
-    let ranges = light_to_temperature.get_dest_ranges(ranges);
-    let ranges = temperature_to_humidity.get_dest_ranges(ranges);
-    let ranges = humidity_to_location.get_dest_ranges(ranges);
+    pub fn location_ranges(&self, ranges: IntervalSet<u128>) -> IntervalSet<u128> {
+        self.maps.iter().fold(ranges, |ranges, map| map.get_dest_ranges(&ranges))
+    }
+}
+
+pub fn part_1(input: &str) -> u128 {
+    let almanac = parse(input).unwrap();
+    almanac.seeds.iter()
+        .map(|seed| almanac.location(*seed))
+        .min()
+        .unwrap()
+}
+
+pub fn part_2(input: &str) -> u128 {
+    let almanac = parse(input).unwrap();
+    let ranges = almanac.location_ranges(almanac.seed_ranges());
     // println!("ranges: {:?}", ranges);
 
-    let min = ranges.iter().min_by_key(|r|r.start).unwrap();
-    println!("min: {}", min.start);
+    ranges.min().unwrap()
+}
+
+#[test]
+fn test() {
+    let input = "
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+    assert_eq!(part_1(input), 35);
+    assert_eq!(part_2(input), 46);
+
+    // every seed in the ranges ends up somewhere in the location ranges
+    let almanac = parse(input).unwrap();
+    let locations = almanac.location_ranges(almanac.seed_ranges());
+    assert_eq!(locations.len(), 27);
+    for r in almanac.seed_ranges().intervals() {
+        assert!(r.range().all(|seed| locations.contains(almanac.location(seed))));
+    }
+}
+
+#[test]
+fn differential() {
+    use crate::prop::{self, Config, Rng};
+
+    // a few seed ranges through a few small, possibly overlapping maps
+    let generate = |rng: &mut Rng| {
+        let seeds = (0..2 * rng.size(1..=3)).map(|_| rng.range(0..=30) as u128).collect();
+        let maps = (0..rng.size(1..=3))
+            .map(|_| VecMyMap((0..rng.size(0..=3))
+                .map(|_| MyMap {
+                    src: rng.range(0..=40) as u128,
+                    dst: rng.range(0..=40) as u128,
+                    range_len: rng.range(1..=10) as u128,
+                })
+                .collect()))
+            .collect();
+        Almanac { seeds, maps }
+    };
+    let shrink_num = |n: &u128| prop::shrink_int(*n as i64, 0).into_iter().map(|n| n as u128).collect::<Vec<_>>();
+    let shrink = |a: &Almanac| {
+        let mut ret = Vec::new();
+        for i in (0..a.seeds.len()).step_by(2).filter(|_| a.seeds.len() > 2) {
+            let mut b = a.clone();
+            b.seeds.drain(i..i + 2);
+            ret.push(b);
+        }
+        for seeds in prop::shrink_vec(&a.seeds, a.seeds.len(), shrink_num) {
+            ret.push(Almanac { seeds, maps: a.maps.clone() });
+        }
+        let shrink_map = |m: &MyMap| {
+            let mut ret = Vec::new();
+            ret.extend(shrink_num(&m.src).into_iter().map(|src| MyMap { src, ..*m }));
+            ret.extend(shrink_num(&m.dst).into_iter().map(|dst| MyMap { dst, ..*m }));
+            ret.extend(shrink_num(&m.range_len).into_iter().filter(|n| *n > 0).map(|range_len| MyMap { range_len, ..*m }));
+            ret
+        };
+        for maps in prop::shrink_vec(&a.maps, 1, |m| {
+            prop::shrink_vec(&m.0, 0, shrink_map).into_iter().map(VecMyMap).collect()
+        }) {
+            ret.push(Almanac { seeds: a.seeds.clone(), maps });
+        }
+        ret
+    };
+    let oracle = |a: &Almanac| {
+        let mut v = a.seed_ranges().intervals().iter()
+            .flat_map(|r| r.range())
+            .map(|seed| a.location(seed))
+            .collect::<Vec<_>>();
+        v.sort_unstable();
+        v.dedup();
+        v
+    };
+    let fast = |a: &Almanac| {
+        a.location_ranges(a.seed_ranges()).intervals().iter().flat_map(|r| r.range()).collect::<Vec<_>>()
+    };
+    prop::assert_agree(&Config::default(), generate, shrink, oracle, fast);
+}
+
+#[test]
+fn solve() {
+    let input = include_str!("input");
+
+    let min = part_1(input);
+    println!("min: {}", min);
+
+    // --------- Part Two -------------
+    let min = part_2(input);
+    println!("min: {}", min);
 }
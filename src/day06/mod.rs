@@ -3,6 +3,7 @@
 //! The distance will be: (t-x) * x
 //! 
 
+use crate::{check::Assumption, input};
 
 pub struct Race {
     pub time: f64,
@@ -16,23 +17,136 @@ impl Race {
 }
 
 impl Race {
+    /// Ways to go strictly further than the record. The roots of the
+    /// quadratic only give a first guess at the shortest winning hold,
+    /// which is then settled exactly, so a tie at a whole number of
+    /// milliseconds doesn't count as a win.
     pub fn beat_ways(&self) -> u64 {
-        let race = self;
-        let x = (race.time.powf(2.) / 4. - race.distance).sqrt() + race.time / 2.;
-        let start = if x > race.time / 2. {
-            race.time - x.floor()
-        } else {
-            x.ceil()
-        };
-        let range = (start as u64)..=(race.time as u64 - start as u64);
-        println!("range: {:?}", range);
-        range.count() as u64
+        let (t, d) = (self.time as u64, self.distance as u128);
+        let beats = |x: u64| x as u128 * (t - x) as u128 > d;
+        let guess = t as f64 / 2. - (self.time.powf(2.) / 4. - self.distance).max(0.).sqrt();
+        let mut start = (guess.max(0.) as u64).min(t / 2);
+        while start > 0 && beats(start - 1) {
+            start -= 1;
+        }
+        while start < t / 2 && !beats(start) {
+            start += 1;
+        }
+        if beats(start) { t - 2 * start + 1 } else { 0 }
     }
 }
 
+/// `Time:` and `Distance:` lines, one race per column. Times have to be
+/// exact as an `f64`, and as a race of time `t` has at most `t + 1` ways to
+/// win, those have to multiply out to a `u64` over all the races.
+pub fn parse(input: &str) -> Option<Vec<Race>> {
+    let numbers = |line: &str, label: &str| -> Option<Vec<u64>> {
+        line.strip_prefix(label)?
+            .split_whitespace()
+            .map(|s| s.parse::<u64>().ok())
+            .collect()
+    };
+    let [times, distances] = input::lines(input).ok()?[..] else { return None };
+    let times = numbers(times, "Time:")?;
+    let distances = numbers(distances, "Distance:")?;
+    if times.is_empty() || times.len() != distances.len() {
+        return None;
+    }
+    if times.iter().any(|&t| t >= 1 << f64::MANTISSA_DIGITS) {
+        return None;
+    }
+    times.iter().try_fold(1_u64, |acc, &t| acc.checked_mul(t + 1))?;
+    Some(times.into_iter()
+        .zip(distances)
+        .map(|(t, d)| Race::new(t as f64, d as f64))
+        .collect())
+}
+
+/// Part two joins each line's digits into one race, and both parts solve
+/// the quadratic in floating point.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let lines = match input::lines(input) {
+        Ok(lines) => lines,
+        Err(e) => return Assumption::unparsable(e),
+    };
+    let numbers = |line: &str, label: &str| -> Option<Vec<u64>> {
+        line.strip_prefix(label)?
+            .split_whitespace()
+            .map(|s| s.parse().ok())
+            .collect()
+    };
+    let (times, distances) = match lines.as_slice() {
+        [t, d] => match (numbers(t, "Time:"), numbers(d, "Distance:")) {
+            (Some(t), Some(d)) => (t, d),
+            _ => return Assumption::unparsable("expected `Time:` and `Distance:` followed by numbers"),
+        },
+        _ => return Assumption::unparsable(format!("expected 2 lines, found {}", lines.len())),
+    };
+    // t² > 4d, for numbers so big that either side overflows
+    let beatable = |t: u128, d: u128| match (t.checked_mul(t), d.checked_mul(4)) {
+        (Some(tt), Some(dd)) => tt > dd,
+        (tt, _) => tt.is_none(),
+    };
+    let joined = |v: &[u64]| v.iter().map(|n| n.to_string()).collect::<String>().parse::<u128>().ok();
+    let mut races = times.iter().zip(distances.iter())
+        .map(|(t, d)| (*t as u128, *d as u128))
+        .collect::<Vec<_>>();
+    let race = joined(&times).zip(joined(&distances));
+    races.extend(race);
+
+    vec![
+        Assumption::check("a distance for every time", times.len() == distances.len() && !times.is_empty(), || {
+            format!("{} times, {} distances", times.len(), distances.len())
+        }),
+        Assumption::check("the joined race is small enough for an f64", race.is_some_and(|(t, d)| t.checked_mul(t).is_some_and(|tt| tt < 1 << 53) && d < 1 << 53), || {
+            format!("{:?} loses precision", race)
+        }),
+        Assumption::check("every race can be won", races.iter().all(|&(t, d)| beatable(t, d)), || {
+            let (t, d) = races.iter().find(|&&(t, d)| !beatable(t, d)).unwrap();
+            format!("no way to beat {} in {}", d, t)
+        }),
+    ]
+}
+
+pub fn part_1(input: &str) -> u64 {
+    // x = sqrt(pow(t)/4 - y) + t/2
+    parse(input).unwrap().iter()
+        .map(|race| {
+            race.beat_ways()
+        })
+        .product()
+}
+
+/// The same lines with the whitespace between digits ignored.
+pub fn part_2(input: &str) -> u64 {
+    let input = input.lines()
+        .map(|line| line.split_whitespace().collect::<String>() + "\n")
+        .collect::<String>();
+    let race = &parse(&input).unwrap()[0];
+    race.beat_ways()
+}
+
+#[test]
+fn test() {
+    let input = "
+Time:      7  15   30
+Distance:  9  40  200
+".trim();
+    let ways = parse(input).unwrap().iter().map(|race| race.beat_ways()).collect::<Vec<_>>();
+    // the last race ties the record at 10 and 20 milliseconds
+    assert_eq!(ways, [4, 8, 9]);
+    assert_eq!(part_1(input), 288);
+    assert_eq!(part_2(input), 71503);
+
+    assert_eq!(Race::new(4., 4.).beat_ways(), 0);
+    assert_eq!(Race::new(5., 100.).beat_ways(), 0);
+    assert_eq!(Race::new(1., 0.).beat_ways(), 0);
+    assert_eq!(Race::new(2., 0.).beat_ways(), 1);
+}
+
 #[test]
 fn solve() {
-    // let input = include_str!("input");
+    let input = include_str!("input");
     let vec = vec![
         Race::new(59., 543.),
         Race::new(68., 1020.),
@@ -48,8 +162,10 @@ fn solve() {
         .product();
 
     println!("product: {}", product);
+    assert_eq!(part_1(input), product);
 
     // Part Two
     let ways = Race::new(59688274., 543102016641022.).beat_ways();
     println!("ways: {}", ways);
-}
\ No newline at end of file
+    assert_eq!(part_2(input), ways);
+}
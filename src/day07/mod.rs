@@ -1,5 +1,7 @@
 use std::{collections::HashMap, cmp::Ordering, str::FromStr};
 
+use crate::input;
+
 
 #[derive(Debug)]
 pub struct Hand {
@@ -170,15 +172,13 @@ impl FromStr for Hand {
     type Err = ();
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let mut it = line.split(" ");
-        let s = it.next().unwrap();
-        let bid: u64 = it.next().unwrap().parse().unwrap();
-
-        let mut it = s.chars();
-        let mut cards = ['0'; 5];
-        for c in cards.iter_mut() {
-            *c = it.next().unwrap();
+        let (s, bid) = line.split_once(" ").ok_or(())?;
+        let bid: u64 = bid.parse().map_err(|_| ())?;
+
+        if !s.chars().all(|c| "23456789TJQKA".contains(c)) {
+            return Err(());
         }
+        let cards: [char; 5] = s.chars().collect::<Vec<_>>().try_into().map_err(|_| ())?;
 
         // println!("cards: {:?}, bid: {}", cards, bid);
 
@@ -209,6 +209,33 @@ fn char_to_score_with_joker(c: char) -> char {
 }
 
 
+/// In a `u128`, as a bid may be anything up to `u64::MAX`.
+fn total_winnings(mut vec: Vec<Hand>, score: fn(&Hand) -> [char; 6]) -> u128 {
+    vec.sort_by_key(score);
+    vec.iter()
+        .enumerate()
+        .fold(0, |acc, (i, v)| {
+            let rank = i + 1;
+            let rank = rank as u128;
+            acc + (rank * u128::from(v.bid))
+        })
+}
+
+pub fn parse(input: &str) -> Option<Vec<Hand>> {
+    input::lines(input).ok()?.into_iter()
+        .map(|line| line.parse::<Hand>().ok())
+        .collect()
+}
+
+pub fn part_1(input: &str) -> u128 {
+    total_winnings(parse(input).unwrap(), Hand::to_score)
+}
+
+/// J is Joker that can act like whatever card
+pub fn part_2(input: &str) -> u128 {
+    total_winnings(parse(input).unwrap(), Hand::to_score_with_joker)
+}
+
 #[test]
 fn solve() {
     let input = r"32T3K 765
@@ -216,37 +243,18 @@ T55J5 684
 KK677 28
 KTJJT 220
 QQQJA 483";
+    assert_eq!(part_1(input), 6440);
+    assert_eq!(part_2(input), 5905);
+    // Windows line endings and a trailing newline
+    assert_eq!(part_1(&format!("{}\r\n", input.replace('\n', "\r\n"))), 6440);
 
     let input = include_str!("input");
 
-    let mut vec = input.split("\n")
-        .map(|line| {
-            line.parse::<Hand>().unwrap()
-        })
-        .collect::<Vec<_>>();
-
-    vec.sort_by_key(|h| h.to_score());
-    let sum = vec.iter()
-        .enumerate()
-        .fold(0, |acc, (i, v)| {
-            let rank = i + 1;
-            let rank = rank as u64;
-            acc + (rank * v.bid)
-        });
-
+    let sum = part_1(input);
     println!("sum: {}", sum);
 
 
     // Part Two
-    // J is Joker that can act like whatever card
-    vec.sort_by_key(|h|h.to_score_with_joker());
-    let sum = vec.iter()
-        .enumerate()
-        .fold(0, |acc, (i, v)| {
-            let rank = i + 1;
-            let rank = rank as u64;
-            acc + (rank * v.bid)
-        });
-
+    let sum = part_2(input);
     println!("sum: {}", sum);
 }
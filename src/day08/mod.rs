@@ -1,77 +1,190 @@
 use std::collections::HashMap;
 
-#[test]
-fn solve() {
-    let input = include_str!("input");
-    let mut it = input.split("\n");
-    let instructions = it.next().unwrap();
+use crate::{check::Assumption, dot::{Graph, ToDot}, format::FromFormat, input, math};
 
-    let mut hash = HashMap::new();
+#[derive(Debug, FromFormat)]
+#[format("{name} = ({left}, {right})")]
+struct Node<'a> {
+    name: &'a str,
+    left: &'a str,
+    right: &'a str,
+}
+
+/// The left/right instructions and the `AAA = (BBB, CCC)` nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network<'a> {
+    pub instructions: &'a str,
+    pub nodes: HashMap<&'a str, (&'a str, &'a str)>,
+}
+
+impl<'a> Network<'a> {
+    pub fn parse(input: &'a str) -> Option<Self> {
+        let paragraphs = input::paragraphs(input).ok()?;
+        let [instructions, lines] = paragraphs.as_slice() else { return None };
+        let instructions = match instructions.as_slice() {
+            [line] => line.trim(),
+            _ => return None,
+        };
+        if !instructions.chars().all(|c| c == 'L' || c == 'R') {
+            return None;
+        }
+        let mut nodes = HashMap::new();
+        for line in lines {
+            let node = Node::from_format(line.trim()).ok()?;
+            nodes.insert(node.name, (node.left, node.right));
+        }
+        Some(Self { instructions, nodes })
+    }
 
-    let mut start_nodes = Vec::new();
+    /// Nodes visited from `start` up to the first one `is_end` accepts,
+    /// both included; `None` if it's never reached.
+    pub fn walk(&self, start: &'a str, is_end: impl Fn(&str) -> bool) -> Option<Vec<&'a str>> {
+        let mut path = vec![start];
+        let mut node = start;
+        // past this many steps some (node, instruction) state has repeated
+        let limit = self.instructions.len() * (self.nodes.len() + 1);
+        for instruct in self.instructions.chars().cycle().take(limit) {
+            let v = self.nodes.get(node)?;
+            node = if instruct == 'L' { v.0 } else { v.1 };
+            path.push(node);
+            if is_end(node) {
+                return Some(path);
+            }
+        }
+        None
+    }
+}
 
-    it.filter(|s|!s.is_empty())
-        .for_each(|node| {
-            // GXT = (MQM, CHN)
-            let mut it = node.split(" = ");
-            let key = it.next().unwrap();
-            if key.ends_with("A") {
-                start_nodes.push(key);
+impl ToDot for Network<'_> {
+    /// Every node with an `L` and an `R` edge, merged when they agree;
+    /// `..A` nodes are boxes and `..Z` nodes double circles.
+    fn to_graph(&self) -> Graph {
+        let mut graph = Graph::directed("network");
+        let mut keys = self.nodes.keys().copied().collect::<Vec<_>>();
+        keys.sort_unstable();
+        for &key in keys.iter() {
+            let node = graph.node(key);
+            if key.ends_with('A') {
+                node.set("shape", "box");
+            } else if key.ends_with('Z') {
+                node.set("shape", "doublecircle");
+            }
+        }
+        for &key in keys.iter() {
+            let (left, right) = self.nodes[key];
+            if left == right {
+                graph.edge(key, left).label("L/R");
+            } else {
+                graph.edge(key, left).label("L");
+                graph.edge(key, right).label("R");
             }
-            let s = it.next().unwrap();
-            let s = &s[1..s.len()-1];
-            let mut it = s.split(", ");
-            let left = it.next().unwrap();
-            let right = it.next().unwrap();
-            hash.insert(key, (left, right));
+        }
+        graph
+    }
+}
+
+/// Part two takes the lcm of the steps to each ghost's first `..Z`, which
+/// only works if every ghost then loops back to that same `..Z` in the same
+/// number of steps.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let Some(network) = Network::parse(input) else {
+        return Assumption::unparsable("expected `L`s and `R`s, a blank line, then `AAA = (BBB, CCC)` lines");
+    };
+    let rounds = network.instructions.len();
+    let mut starts = network.nodes.keys().copied().filter(|n| n.ends_with('A')).collect::<Vec<_>>();
+    starts.sort_unstable();
+    fn to_z<'a>(network: &Network<'a>, from: &'a str) -> Option<(usize, &'a str)> {
+        let path = network.walk(from, |n| n.ends_with('Z'))?;
+        Some((path.len() - 1, *path.last().unwrap()))
+    }
+    let firsts = starts.iter().map(|s| (*s, to_z(&network, s))).collect::<Vec<_>>();
+
+    let reaches = firsts.iter()
+        .find(|(_, first)| first.is_none())
+        .map_or(Ok(()), |(start, _)| Err(format!("{} never does", start)));
+    let whole = firsts.iter()
+        .find_map(|(start, first)| first.filter(|(steps, _)| steps % rounds != 0).map(|(steps, z)| (start, steps, z)))
+        .map_or(Ok(()), |(start, steps, z)| {
+            Err(format!("{} gets to {} in {} steps, with {} instructions", start, z, steps, rounds))
         });
+    // only meaningful once the first two hold, as `walk` starts from the
+    // first instruction again
+    let loops = firsts.iter()
+        .filter_map(|(start, first)| Some((start, (*first)?)))
+        .find_map(|(start, (steps, z))| match to_z(&network, z) {
+            Some((again, next)) if again == steps && next == z => None,
+            Some((again, next)) => Some(format!("{} gets to {} in {} steps, then {} in {} more", start, z, steps, next, again)),
+            None => Some(format!("{} never gets back to a ..Z after {}", start, z)),
+        })
+        .map_or(Ok(()), Err);
+    let lcm = math::lcm(firsts.iter().filter_map(|(_, first)| Some(first.as_ref()?.0 as u128)));
 
-    let mut steps = 0;
-    let mut node = "AAA";
-    for instruct in instructions.chars().cycle() {
-        steps += 1;
-        let v = hash.get(node).unwrap();
+    vec![
+        Assumption::check("AAA and ZZZ are nodes", ["AAA", "ZZZ"].iter().all(|n| network.nodes.contains_key(n)), || {
+            "part one has nowhere to start or finish".to_string()
+        }),
+        Assumption::check("there are ..A nodes", !starts.is_empty(), || "no ghosts for part two".to_string()),
+        Assumption::new("every ..A node reaches a ..Z node", reaches),
+        Assumption::new("the first ..Z comes after whole rounds of instructions", whole),
+        Assumption::new("each ghost loops back to its first ..Z in as many steps again", loops),
+        Assumption::check("the ghosts line up within a u128", lcm.is_some(), || {
+            "the lcm of their steps overflows".to_string()
+        }),
+    ]
+}
 
-        node = match instruct {
-            'L' => v.0,
-            'R' => v.1,
-            _ => unreachable!(),
-        };
+pub fn part_1(input: &str) -> usize {
+    let network = Network::parse(input).unwrap();
+    network.walk("AAA", |n| n == "ZZZ").unwrap().len() - 1
+}
 
-        if node == "ZZZ" {
-            break;
-        }
-    }
+/// Every `..A` node walks until a `..Z` one; they all line up at the lcm.
+pub fn part_2(input: &str) -> u128 {
+    let network = Network::parse(input).unwrap();
+    let steps = network.nodes.keys()
+        .filter(|node| node.ends_with('A'))
+        .map(|node| network.walk(node, |n| n.ends_with('Z')).unwrap().len() as u128 - 1);
+    math::lcm(steps).unwrap()
+}
+
+#[test]
+fn test() {
+    let input = {"
+LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+    ".trim()};
 
+    let network = Network::parse(input).unwrap();
+    let path = network.walk("AAA", |n| n == "ZZZ").unwrap();
+    assert_eq!(path, ["AAA", "BBB", "AAA", "BBB", "AAA", "BBB", "ZZZ"]);
+    assert!(network.walk("ZZZ", |n| n == "AAA").is_none());
+
+    // in part one's sample ZZZ loops onto itself every step
+    let broken = assumptions(input).into_iter().filter(|a| !a.holds()).collect::<Vec<_>>();
+    assert_eq!(broken.iter().map(|a| a.name).collect::<Vec<_>>(), ["each ghost loops back to its first ..Z in as many steps again"]);
+    assert_eq!(broken[0].violation.as_deref(), Some("AAA gets to ZZZ in 6 steps, then ZZZ in 1 more"));
+
+    let dot = network.to_dot();
+    assert!(dot.contains("\"AAA\" [shape=box];"));
+    assert!(dot.contains("\"BBB\" -> \"ZZZ\" [label=R];"));
+    assert!(dot.contains("\"ZZZ\" -> \"ZZZ\" [label=\"L/R\"];"));
+}
+
+#[test]
+fn solve() {
+    let input = include_str!("input");
+
+    let steps = part_1(input);
     println!("steps: {}", steps);
 
     // ------- Part Two ----------
     // Calculate the LCM(Least Common Multiple) of the steps
     // https://www.calculatorsoup.com/calculators/math/lcm.php
     // 最小公倍数
-    let steps = start_nodes.iter()
-        .map(|node| {
-            let mut steps = 0;
-            let mut node = *node;
-            for instruct in instructions.chars().cycle() {
-                steps += 1;
-                let v = hash.get(node).unwrap();
-
-                node = match instruct {
-                    'L' => v.0,
-                    'R' => v.1,
-                    _ => unreachable!(),
-                };
-
-                if node.ends_with("Z") {
-                    break;
-                }
-            }
-            steps
-        })
-        .fold(1_u128, |acc, x| {
-            num::integer::lcm(acc, x)
-        });
+    let steps = part_2(input);
 
     println!("steps: {:?}", steps);
 
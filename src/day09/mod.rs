@@ -1,16 +1,19 @@
 
-fn generate_next_number(arr: Vec<i64>) -> (i64, i64) {
+use crate::input;
+
+fn generate_next_number(arr: Vec<i64>) -> (i128, i128) {
 
     let mut cur_arr = arr;
-    let mut sum = *cur_arr.last().unwrap();
-    let mut new_first = *cur_arr.first().unwrap();
+    let mut sum = i128::from(*cur_arr.last().unwrap());
+    let mut new_first = i128::from(*cur_arr.first().unwrap());
 
     let mut sign = -1;
 
     let mut additional_sequence = vec![];
 
     loop {
-        if cur_arr.iter().all(|v|*v==0) { break; }
+        // a single number left is taken to go on forever
+        if cur_arr.len() < 2 || cur_arr.iter().all(|v|*v==0) { break; }
 
         additional_sequence.clear();
         for i in 0..cur_arr.len()-1 {
@@ -20,36 +23,59 @@ fn generate_next_number(arr: Vec<i64>) -> (i64, i64) {
 
         std::mem::swap(&mut cur_arr, &mut additional_sequence);
 
-        sum += *cur_arr.last().unwrap();
+        sum += i128::from(*cur_arr.last().unwrap());
 
-        new_first += *cur_arr.first().unwrap() * sign;
+        new_first += i128::from(*cur_arr.first().unwrap()) * sign;
         sign = -sign;
     }
 
     (new_first, sum)
 }
 
-#[test]
-fn solve() {
-    let input = "0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45";
-
-    let input = include_str!("input");
+/// Histories can't be longer than this.
+pub const MAX_LEN: usize = 32;
 
-    let sum = input.split("\n")
+/// One history of numbers per line. Each number fits an `i32` and there are
+/// at most [`MAX_LEN`] of them, so the `k`th differences stay under
+/// `2^(32 + k)` and fit an `i64`.
+pub fn parse(input: &str) -> Option<Vec<Vec<i64>>> {
+    input::lines(input).ok()?.into_iter()
         .map(|line| {
-            let nums = line.split(" ")
+            line.split(" ")
                 .filter(|s|!s.is_empty())
-                .map(|s|s.parse::<i64>().unwrap())
-                .collect::<Vec<_>>();
-            generate_next_number(nums)
+                .map(|s|s.parse::<i32>().ok().map(i64::from))
+                .collect::<Option<Vec<_>>>()
+                .filter(|history| history.len() <= MAX_LEN)
         })
+        .collect()
+}
+
+/// `(previous, next)` extrapolated values, summed over every history.
+fn extrapolate(input: &str) -> (i128, i128) {
+    parse(input).unwrap().into_iter()
+        .map(generate_next_number)
         .fold((0, 0), |acc, v| {
             (acc.0 + v.0, acc.1 + v.1)
-        });
-    println!("sum: {:?}", sum);
+        })
 }
 
+pub fn part_1(input: &str) -> i128 {
+    extrapolate(input).1
+}
+
+pub fn part_2(input: &str) -> i128 {
+    extrapolate(input).0
+}
+
+#[test]
+fn solve() {
+    let input = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+    assert_eq!(extrapolate(input), (2, 114));
 
+    let input = include_str!("input");
 
+    let sum = extrapolate(input);
+    println!("sum: {:?}", sum);
+}
@@ -1,29 +1,9 @@
-use std::{collections::{HashMap, HashSet}, sync::{Arc, Weak}};
+use crate::{check::Assumption, input, polygon::Polygon};
 
 #[derive(Debug)]
 pub struct Node {
     pub pipe: Option<Pipe>,
     pub is_border: bool,
-    pub outside_dir: Option<Direction>,
-}
-
-impl Node {
-    // 外侧是否在内角的方向
-    fn is_outside_at_inner_angle(&self) -> Result<bool, ()> {
-        let pipe = self.pipe.unwrap();
-        if pipe == Pipe::Vertical || pipe == Pipe::Horizontal {
-            return Err(());
-        }
-        let dir = self.outside_dir.unwrap();
-
-        match pipe {
-            Pipe::Start | Pipe::Vertical | Pipe::Horizontal => return Err(()),
-            Pipe::L => Ok(dir == Direction::Up || dir == Direction::Right),
-            Pipe::J => Ok(dir == Direction::Up || dir == Direction::Left),
-            Pipe::P7 => Ok(dir == Direction::Down || dir == Direction::Left),
-            Pipe::F => Ok(dir == Direction::Down || dir == Direction::Right),
-        }
-    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -128,13 +108,36 @@ impl Idx {
 }
 
 impl Maze {
-    pub fn new(maze: Vec<Vec<Node>>, start_row: usize, start_col: usize) -> Self {
+    /// `None` unless the pipes through the start close into a loop.
+    pub fn new(maze: Vec<Vec<Node>>, start_row: usize, start_col: usize) -> Option<Self> {
         let mut this = Self { maze, start_row, start_col };
         this.find_start();
+        if !this.closes_loop() {
+            return None;
+        }
         for (node, _dir) in this.iter_mut() {
             node.is_border = true;
         }
-        this
+        Some(this)
+    }
+
+    /// Whether following the pipe out of the start comes back to it.
+    fn closes_loop(&self) -> bool {
+        let (row, col) = (self.start_row, self.start_col);
+        let from_dir = match self.maze[row][col].pipe {
+            None | Some(Pipe::Start) => return false,
+            Some(pipe) => pipe.get_random_direction(),
+        };
+        let mut idx = Idx::new(row, col, Some(from_dir));
+        let cells = self.maze.len() * self.maze[0].len();
+        for _ in 0..cells {
+            match self.find_path(idx) {
+                Some(next) if (next.row, next.col) == (row, col) => return true,
+                Some(next) => idx = next,
+                None => return false,
+            }
+        }
+        false
     }
 
     pub fn is_border(&self, row: usize, col: usize) -> bool {
@@ -223,61 +226,61 @@ impl Maze {
         let down = if row < maze.len()-1 { maze[row+1][col].pipe } else { None };
         let left = if col > 0 { maze[row][col-1].pipe } else { None };
         let right = if col < maze[row].len()-1 { maze[row][col+1].pipe } else { None };
-        let pipe = maze[row][col].pipe.unwrap();
+        let pipe = maze[row][col].pipe?;
 
         match pipe {
             // '-'
             Pipe::Horizontal => {
-                if from_dir.unwrap() == Direction::Right && left.unwrap().hash_right() {
+                if from_dir == Some(Direction::Right) && left.is_some_and(|p| p.hash_right()) {
                     return Some(Idx::new(row, col-1, Some(Direction::Right)));
                 }
-                else if from_dir.unwrap() == Direction::Left && right.unwrap().hash_left() {
+                else if from_dir == Some(Direction::Left) && right.is_some_and(|p| p.hash_left()) {
                     return Some(Idx::new(row, col+1, Some(Direction::Left)));
                 }
                 None
             },
             // '|'
             Pipe::Vertical => {
-                if from_dir.unwrap() == Direction::Down && up.unwrap().hash_down() {
+                if from_dir == Some(Direction::Down) && up.is_some_and(|p| p.hash_down()) {
                     return Some(Idx::new(row-1, col, Some(Direction::Down)));
                 }
-                if from_dir.unwrap() == Direction::Up && down.unwrap().hash_up() {
+                if from_dir == Some(Direction::Up) && down.is_some_and(|p| p.hash_up()) {
                     return Some(Idx::new(row+1, col, Some(Direction::Up)));
                 }
                 None
             },
             Pipe::J => {
-                if from_dir.unwrap() == Direction::Left && up.unwrap().hash_down() {
+                if from_dir == Some(Direction::Left) && up.is_some_and(|p| p.hash_down()) {
                     return Some(Idx::new(row-1, col, Some(Direction::Down)));
                 }
-                if from_dir.unwrap() == Direction::Up && left.unwrap().hash_right() {
+                if from_dir == Some(Direction::Up) && left.is_some_and(|p| p.hash_right()) {
                     return Some(Idx::new(row, col-1, Some(Direction::Right)));
                 }
                 None
             },
             Pipe::F => {
-                if from_dir.unwrap() == Direction::Right && down.unwrap().hash_up() {
+                if from_dir == Some(Direction::Right) && down.is_some_and(|p| p.hash_up()) {
                     return Some(Idx::new(row+1, col, Some(Direction::Up)));
                 }
-                if from_dir.unwrap() == Direction::Down && right.unwrap().hash_left() {
+                if from_dir == Some(Direction::Down) && right.is_some_and(|p| p.hash_left()) {
                     return Some(Idx::new(row, col+1, Some(Direction::Left)));
                 }
                 None
             },
             Pipe::L => {
-                if from_dir.unwrap() == Direction::Right && up.unwrap().hash_down() {
+                if from_dir == Some(Direction::Right) && up.is_some_and(|p| p.hash_down()) {
                     return Some(Idx::new(row-1, col, Some(Direction::Down)));
                 }
-                if from_dir.unwrap() == Direction::Up && right.unwrap().hash_left() {
+                if from_dir == Some(Direction::Up) && right.is_some_and(|p| p.hash_left()) {
                     return Some(Idx::new(row, col+1, Some(Direction::Left)));
                 }
                 None
             },
             Pipe::P7 => {
-                if from_dir.unwrap() == Direction::Left && down.unwrap().hash_up() {
+                if from_dir == Some(Direction::Left) && down.is_some_and(|p| p.hash_up()) {
                     return Some(Idx::new(row+1, col, Some(Direction::Up)));
                 }
-                if from_dir.unwrap() == Direction::Down && left.unwrap().hash_right() {
+                if from_dir == Some(Direction::Down) && left.is_some_and(|p| p.hash_right()) {
                     return Some(Idx::new(row, col-1, Some(Direction::Right)));
                 }
                 None
@@ -286,280 +289,9 @@ impl Maze {
         }
     }
 
-    fn update_outside_direction(&mut self, row: usize, col: usize, direction: Direction) {
-        self.maze[row][col].outside_dir = Some(direction);
-
-        let mut it = self.new_iter_mut(row, col);
-        let (mut pre_node, _) = it.next().unwrap();
-        for (cur_node, idx) in it {
-            let dir = idx.from_dir.unwrap();
-            let prev_pipe = pre_node.pipe.unwrap();
-            let cur_pipe = cur_node.pipe.unwrap();
-            let pre_outside_dir = pre_node.outside_dir.unwrap();
-
-            if prev_pipe == Pipe::Vertical || prev_pipe == Pipe::Horizontal {
-                cur_node.outside_dir = Some(pre_outside_dir);
-            } else {
-                // 内角是否为外侧
-                let inner_side = pre_node.is_outside_at_inner_angle().unwrap();
-                match prev_pipe {
-                    Pipe::L => {
-                        // x
-                        // L
-                        if dir == Direction::Down {
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Right
-                                } else {
-                                    Direction::Left
-                                });
-                        } else {
-                            // L x
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Up
-                                } else {
-                                    Direction::Down
-                                });
-                        }
-                    },
-                    Pipe::J => {
-                        // x
-                        // J
-                        if dir == Direction::Down {
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Left
-                                } else {
-                                    Direction::Right
-                                });
-                        } else {
-                            // x J
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Up
-                                } else {
-                                    Direction::Down
-                                });
-                        }
-                    },
-                    Pipe::P7 => {
-                        // 7
-                        // x
-                        if dir == Direction::Up {
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Left
-                                } else {
-                                    Direction::Right
-                                });
-                        } else {
-                            // x 7
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Down
-                                } else {
-                                    Direction::Up
-                                });
-                        }
-                    },
-                    Pipe::F => {
-                        // F
-                        // x
-                        if dir == Direction::Up {
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Right
-                                } else {
-                                    Direction::Left
-                                });
-                        } else {
-                            // F x
-                            cur_node.outside_dir = Some(
-                                if inner_side {
-                                    Direction::Down
-                                } else {
-                                    Direction::Up
-                                });
-                        }
-                    },
-                    _ => unreachable!(),
-                }
-            }
-            pre_node = cur_node;
-        }
-    }
-
-    fn calc_inner_count(&self) -> usize {
-        let mut seed_nodes = HashSet::new();
-        let height = self.maze.len();
-        let width = self.maze[0].len();
-
-        for (node, idx) in self.iter() {
-            let pipe = node.pipe.unwrap();
-            if let Ok(is_inner) = node.is_outside_at_inner_angle() {
-                match pipe {
-                    Pipe::L => if is_inner {
-                            // left
-                            if idx.col > 0 {
-                                seed_nodes.insert((idx.row, idx.col - 1));
-                            }
-                            // down
-                            if idx.row < height - 1 {
-                                seed_nodes.insert((idx.row + 1, idx.col));
-                            }
-                        } else {
-                            // right
-                            if idx.col < width - 1 {
-                                seed_nodes.insert((idx.row, idx.col + 1));
-                            }
-                            // up
-                            if idx.row > 0 {
-                                seed_nodes.insert((idx.row - 1, idx.col));
-                            }
-                        },
-                    Pipe::J => if is_inner {
-                            // right
-                            if idx.col < width - 1 {
-                                seed_nodes.insert((idx.row, idx.col + 1));
-                            }
-                            // down
-                            if idx.row < height - 1 {
-                                seed_nodes.insert((idx.row + 1, idx.col));
-                            }
-                        } else {
-                            // left
-                            if idx.col > 0 {
-                                seed_nodes.insert((idx.row, idx.col - 1));
-                            }
-                            // up
-                            if idx.row > 0 {
-                                seed_nodes.insert((idx.row - 1, idx.col));
-                            }
-                        },
-                    Pipe::P7 => if is_inner {
-                            // right
-                            if idx.col < width - 1 {
-                                seed_nodes.insert((idx.row, idx.col + 1));
-                            }
-                            // up
-                            if idx.row > 0 {
-                                seed_nodes.insert((idx.row - 1, idx.col));
-                            }
-                        } else {
-                            // left
-                            if idx.col > 0 {
-                                seed_nodes.insert((idx.row, idx.col - 1));
-                            }
-                            // down
-                            if idx.row < height - 1 {
-                                seed_nodes.insert((idx.row + 1, idx.col));
-                            }
-                        },
-                    Pipe::F => if is_inner {
-                            // left
-                            if idx.col > 0 {
-                                seed_nodes.insert((idx.row, idx.col - 1));
-                            }
-                            // up
-                            if idx.row > 0 {
-                                seed_nodes.insert((idx.row - 1, idx.col));
-                            }
-                        } else {
-                            // right
-                            if idx.col < width - 1 {
-                                seed_nodes.insert((idx.row, idx.col + 1));
-                            }
-                            // down
-                            if idx.row < height - 1 {
-                                seed_nodes.insert((idx.row + 1, idx.col));
-                            }
-                        },
-                    _ => unreachable!(),
-                }
-            } else {
-                let dir = node.outside_dir.unwrap();
-                let (row, col) = match dir {
-                    Direction::Up => {
-                        if idx.row >= height - 1 { continue; }
-                        (idx.row + 1, idx.col)
-                    },
-                    Direction::Down => {
-                        if idx.row <= 0 { continue; }
-                        (idx.row - 1, idx.col)
-                    },
-                    Direction::Left => {
-                        if idx.col >= width - 1 { continue; }
-                        (idx.row, idx.col + 1)
-                    },
-                    Direction::Right => {
-                        if idx.col <= 0 { continue; }
-                        (idx.row, idx.col - 1)
-                    },
-                };
-                seed_nodes.insert((row, col));
-            };
-        }
-
-        seed_nodes = seed_nodes.into_iter()
-            .filter(|(row, col)| !self.is_border(*row, *col))
-            .collect();
-
-        self.traverse_inner(&mut seed_nodes);
-        seed_nodes.len()
-    }
-
-    fn traverse_inner(&self, inner_nodes: &mut HashSet<(usize, usize)>) {
-        let height = self.maze.len();
-        let width = self.maze[0].len();
-        let mut new_found_nodes = inner_nodes.clone();
-
-        loop {
-            if new_found_nodes.is_empty() {
-                break;
-            }
-            let mut nodes = HashSet::new();
-
-            for (row, col) in new_found_nodes.into_iter() {
-                // up
-                if row > 0 {
-                    let row = row-1;
-                    let col = col;
-                    if !self.is_border(row, col) && inner_nodes.get(&(row, col)).is_none() {
-                        inner_nodes.insert((row, col));
-                        nodes.insert((row, col));
-                    }
-                }
-                // down
-                if row < height - 1 {
-                    let row = row+1;
-                    let col = col;
-                    if !self.is_border(row, col) && inner_nodes.get(&(row, col)).is_none() {
-                        inner_nodes.insert((row, col));
-                        nodes.insert((row, col));
-                    }
-                }
-                // left
-                if col > 0 {
-                    let row = row;
-                    let col = col - 1;
-                    if !self.is_border(row, col) && inner_nodes.get(&(row, col)).is_none() {
-                        inner_nodes.insert((row, col));
-                        nodes.insert((row, col));
-                    }
-                }
-                // right
-                if col < width - 1 {
-                    let row = row;
-                    let col = col + 1;
-                    if !self.is_border(row, col) && inner_nodes.get(&(row, col)).is_none() {
-                        inner_nodes.insert((row, col));
-                        nodes.insert((row, col));
-                    }
-                }
-            }
-            new_found_nodes = nodes;
-        }
+    /// The loop as a polygon through the centre of every pipe, in `(row, col)`.
+    pub fn loop_polygon(&self) -> Polygon<i64> {
+        Polygon::new(self.iter().map(|(_, idx)| (idx.row as i64, idx.col as i64)).collect())
     }
 }
 
@@ -636,159 +368,139 @@ impl<'a> Iterator for IterMut<'a> {
 
 
 
-#[test]
-fn solve() {
-    let input = "
-7-F7-
-.FJ|7
-SJLL7
-|F--J
-LJ.LJ";
-    let input = include_str!("input");
-
+/// The sketch, with `S` replaced by the pipe it hides and the loop marked
+/// as border; `None` for an unknown tile, anything but one `S`, or an `S`
+/// that isn't on a loop.
+pub fn parse_maze(input: &str) -> Option<Maze> {
+    let mut starts = 0;
     let mut start_row = 0;
     let mut start_col = 0;
 
-    let maze = input.split("\n")
-        .filter(|s|!s.is_empty())
+    let maze = input::grid(input).ok()?.into_iter()
         .enumerate()
         .map(|(row, line)| {
-            line.chars()
+            line.trim_end()
+                .chars()
                 .enumerate()
                 .map(|(col, c)| {
                     let node = Node {
                         pipe: Pipe::try_from(c).ok(),
                         is_border: false,
-                        outside_dir: None,
                     };
+                    if node.pipe.is_none() && c != '.' {
+                        return None;
+                    }
                     if let Some(Pipe::Start) = node.pipe {
+                        starts += 1;
                         start_row = row;
                         start_col = col;
                     }
-                    node
+                    Some(node)
                 })
-                .collect::<Vec<_>>()
+                .collect::<Option<Vec<_>>>()
         })
-        .collect::<Vec<_>>();
+        .collect::<Option<Vec<_>>>()?;
 
-    let mut maze = Maze::new(maze, start_row, start_col);
+    if starts != 1 {
+        return None;
+    }
+    Maze::new(maze, start_row, start_col)
+}
 
-    let node_count = maze.iter().count();
-    println!("node_count: {}", node_count);
+/// The loop is followed out of `S`, whose own pipe is worked out from the
+/// pipes around it, so exactly two of them must lead into it.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let rows = match input::grid(input) {
+        Ok(rows) => rows.into_iter().map(|row| row.chars().collect::<Vec<_>>()).collect::<Vec<_>>(),
+        Err(e) => return Assumption::unparsable(e),
+    };
+    if let Some(c) = rows.iter().flatten().find(|c| **c != '.' && Pipe::try_from(**c).is_err()) {
+        return Assumption::unparsable(format!("unknown tile {:?}", c));
+    }
+    let starts = (0..rows.len())
+        .flat_map(|r| (0..rows[r].len()).map(move |c| (r, c)))
+        .filter(|&(r, c)| rows[r][c] == 'S')
+        .collect::<Vec<_>>();
+    let pipe = |r: Option<usize>, c: Option<usize>| {
+        let ch = *rows.get(r?)?.get(c?)?;
+        Pipe::try_from(ch).ok()
+    };
+    let connections = starts.first().map(|&(r, c)| {
+        [
+            pipe(r.checked_sub(1), Some(c)).is_some_and(|p| p.hash_down()),
+            pipe(Some(r + 1), Some(c)).is_some_and(|p| p.hash_up()),
+            pipe(Some(r), c.checked_sub(1)).is_some_and(|p| p.hash_right()),
+            pipe(Some(r), Some(c + 1)).is_some_and(|p| p.hash_left()),
+        ].into_iter().filter(|x| *x).count()
+    });
+
+    vec![
+        Assumption::check("exactly one S", starts.len() == 1, || format!("found {}", starts.len())),
+        Assumption::check("two pipes lead into S", connections.is_none_or(|n| n == 2), || {
+            format!("{} do", connections.unwrap())
+        }),
+    ]
+}
 
-    println!("steps: {}", (node_count+1)/2);
+pub fn part_1(input: &str) -> usize {
+    let maze = parse_maze(input).unwrap();
+    let node_count = maze.iter().count();
+    node_count.div_ceil(2)
+}
 
+/// Tiles enclosed by the loop: its polygon's interior lattice points, as
+/// every tile the loop runs through is on the boundary.
+pub fn part_2(input: &str) -> usize {
+    parse_maze(input).unwrap().loop_polygon().interior_points() as usize
+}
 
-    // Part Two
-    // 先找到一个一定处于外面的点
-    let len = maze.maze.len();
-
-    let mut outer_idx = None;
-    for i in 0..len {
-        if !maze.is_border(i, 0) {
-            outer_idx = Some((i, 0));
-            break;
-        }
-        if !maze.is_border(i, len-1) {
-            outer_idx = Some((i, len-1));
-            break;
-        }
-        if !maze.is_border(0, i) {
-            outer_idx = Some((0, i));
-            break;
-        }
-        if !maze.is_border(len-1, i) {
-            outer_idx = Some((len-1, i));
-            break;
-        }
-    }
+#[test]
+fn test() {
+    let input = "
+7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
+    assert_eq!(part_1(input), 8);
 
-    let idx = outer_idx.unwrap();
-    let vec = &mut maze.maze;
-    let idx = find_maze_border(vec, idx);
-    let (row, col) = (idx.row, idx.col);
+    let input = "
+...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+    assert_eq!(part_2(input), 4);
 
-    maze.update_outside_direction(row, col, idx.from_dir.unwrap());
-    let inner_count = maze.calc_inner_count();
-    println!("inner_count: {}", inner_count);
+    let input = "
+FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+    let maze = parse_maze(input).unwrap();
+    assert_eq!(maze.loop_polygon().boundary_points() as usize, maze.iter().count());
+    assert_eq!(part_2(input), 10);
 }
 
-/// 由外向内, 找到第一个 maze 的边界, 并以此确定那边为外侧
-fn find_maze_border(maze: &mut Vec<Vec<Node>>, idx: (usize, usize)) -> Idx {
-    let (row, col) = idx;
-    maze[row][col].pipe = None;
-    let len = maze.len();
+#[test]
+fn solve() {
+    let input = include_str!("input");
 
-    let mut indices = HashSet::new();
-    indices.insert((row, col));
+    let steps = part_1(input);
+    println!("steps: {}", steps);
 
-    let mut start_row = 0;
-    let mut start_col = 0;
-    let mut from_dir = Direction::Down;
 
-    'outer: loop {
-        if indices.is_empty() {
-            break;
-        }
-        let mut new_indices = HashSet::new();
-        for (row, col) in indices.iter() {
-            let (row, col) = (*row, *col);
-            // up
-            if row > 0 && maze[row-1][col].pipe.is_some() {
-                let row = row - 1;
-                if !maze[row][col].is_border {
-                    maze[row][col].pipe = None;
-                    new_indices.insert((row, col));
-                } else {
-                    start_row = row;
-                    start_col = col;
-                    from_dir = Direction::Down;
-                    break 'outer;
-                }
-            }
-            // down
-            if row < len - 1 && maze[row+1][col].pipe.is_some() {
-                let row = row + 1;
-                if !maze[row][col].is_border {
-                    maze[row][col].pipe = None;
-                    new_indices.insert((row, col));
-                } else {
-                    start_row = row;
-                    start_col = col;
-                    from_dir = Direction::Up;
-                    break 'outer;
-                }
-            }
-            // left
-            if col > 0 && maze[row][col-1].pipe.is_some() {
-                let col = col - 1;
-                if !maze[row][col].is_border {
-                    maze[row][col].pipe = None;
-                    new_indices.insert((row, col));
-                } else {
-                    start_row = row;
-                    start_col = col;
-                    from_dir = Direction::Right;
-                    break 'outer;
-                }
-            }
-            // right
-            if col < len-1 && maze[row][col+1].pipe.is_some() {
-                let col = col + 1;
-                if !maze[row][col].is_border {
-                    maze[row][col].pipe = None;
-                    new_indices.insert((row, col));
-                } else {
-                    start_row = row;
-                    start_col = col;
-                    from_dir = Direction::Left;
-                    break 'outer;
-                }
-            }
-        }
-        indices = new_indices;
-    }
-
-    Idx::new(start_row, start_col, Some(from_dir))
+    // Part Two
+    let inner_count = part_2(input);
+    println!("inner_count: {}", inner_count);
 }
-
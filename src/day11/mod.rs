@@ -1,5 +1,7 @@
 use std::{collections::HashSet, hint};
 
+use crate::input;
+
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct Pos {
@@ -11,9 +13,8 @@ impl Pos {
     pub fn new(row: usize, col: usize) -> Self {
         Self { row, col }
     }
-    pub fn distance(&self, other: &Pos, empty_rows: &HashSet<usize>, empty_cols: &HashSet<usize>) -> usize {
-        // each empty row should be replaced with 1000000 empty rows, and each empty column should be replaced with 1000000 empty columns.
-        let factor = 1000000;
+    /// Each empty row or column counts as `factor` of them.
+    pub fn distance(&self, other: &Pos, empty_rows: &HashSet<usize>, empty_cols: &HashSet<usize>, factor: usize) -> usize {
 
         let low = self.row.min(other.row);
         let high = self.row.max(other.row);
@@ -44,27 +45,15 @@ impl Pos {
 }
 
 
-#[test]
-fn test() {
-    let input = 
-"...#......
-.......#..
-#.........
-..........
-......#...
-.#........
-.........#
-..........
-.......#..
-#...#.....";
-
-    let input = include_str!("input");
-
+/// Sum of the distances between every pair of galaxies, each empty row and
+/// column being `factor` wide. Walks every pair, and every row and column
+/// between them.
+pub fn sum_of_distances_naive(input: &str, factor: usize) -> usize {
     let mut map = Vec::new();
     let mut empty_rows = HashSet::new();
     let mut empty_cols = HashSet::new();
 
-    input.split("\n")
+    input::grid(input).unwrap().into_iter()
         .enumerate()
         .for_each(|(r, line)| {
             let row = line.chars().collect::<Vec<_>>();
@@ -99,11 +88,117 @@ fn test() {
                     if *c != '#' { return; }
 
                     let pos = Pos::new(row, col);
-                    sum += hash.iter().map(|p|p.distance(&pos, &empty_rows, &empty_cols)).sum::<usize>();
+                    sum += hash.iter().map(|p|p.distance(&pos, &empty_rows, &empty_cols, factor)).sum::<usize>();
 
                     hash.insert(pos);
                 });
         });
 
+    sum
+}
+
+/// The image as rows of galaxy or not; only `#` and `.` are allowed.
+pub fn parse(input: &str) -> Option<Vec<Vec<bool>>> {
+    input::grid(input).ok()?.into_iter()
+        .map(|line| line.chars().map(|c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }).collect())
+        .collect()
+}
+
+/// [`sum_of_distances_naive`] without the pairs: the galaxies are moved to
+/// where the expansion puts them, and then rows and columns are summed
+/// separately, as `|a - b|` over all pairs of sorted values.
+pub fn sum_of_distances(input: &str, factor: usize) -> usize {
+    let map = parse(input).unwrap();
+    // where each row or column ends up
+    let expanded = |occupied: Vec<bool>| {
+        occupied.iter()
+            .scan(0, |at, &o| {
+                let here = *at;
+                *at += if o { 1 } else { factor };
+                Some(here)
+            })
+            .collect::<Vec<_>>()
+    };
+    let rows = expanded(map.iter().map(|row| row.contains(&true)).collect());
+    let cols = expanded((0..map[0].len()).map(|c| map.iter().any(|row| row[c])).collect());
+
+    let pairwise = |mut v: Vec<usize>| {
+        v.sort_unstable();
+        let mut before = 0;
+        v.iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let d = x * i - before;
+                before += x;
+                d
+            })
+            .sum::<usize>()
+    };
+    let galaxies = (0..map.len())
+        .flat_map(|r| (0..map[r].len()).map(move |c| (r, c)))
+        .filter(|&(r, c)| map[r][c])
+        .collect::<Vec<_>>();
+    pairwise(galaxies.iter().map(|&(r, _)| rows[r]).collect())
+        + pairwise(galaxies.iter().map(|&(_, c)| cols[c]).collect())
+}
+
+pub fn part_1(input: &str) -> usize {
+    sum_of_distances(input, 2)
+}
+
+/// each empty row should be replaced with 1000000 empty rows, and each empty column should be replaced with 1000000 empty columns.
+pub fn part_2(input: &str) -> usize {
+    sum_of_distances(input, 1000000)
+}
+
+#[test]
+fn test() {
+    let input = 
+"...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+    assert_eq!(part_1(input), 374);
+    assert_eq!(sum_of_distances(input, 10), 1030);
+    assert_eq!(sum_of_distances(input, 100), 8410);
+
+    let input = include_str!("input");
+
+    let sum = part_2(input);
     println!("sum: {:?}", sum);
-}
\ No newline at end of file
+}
+
+#[test]
+fn differential() {
+    use crate::prop::{self, Config, Rng};
+
+    let generate = |rng: &mut Rng| {
+        let (h, w) = (rng.size(1..=8), rng.size(1..=8));
+        let grid = (0..h)
+            .map(|_| (0..w).map(|_| if rng.chance(1, 4) { '#' } else { '.' }).collect())
+            .collect::<Vec<Vec<_>>>();
+        (grid, rng.size(1..=1000))
+    };
+    let shrink = |(grid, factor): &(Vec<Vec<char>>, usize)| {
+        let mut ret = prop::shrink_grid(grid, &'.').into_iter().map(|g| (g, *factor)).collect::<Vec<_>>();
+        ret.extend(prop::shrink_int(*factor as i64, 1).into_iter().map(|f| (grid.clone(), f as usize)));
+        ret
+    };
+    prop::assert_agree(
+        &Config::default(),
+        generate,
+        shrink,
+        |(grid, factor)| sum_of_distances_naive(&prop::render_grid(grid), *factor),
+        |(grid, factor)| sum_of_distances(&prop::render_grid(grid), *factor),
+    );
+}
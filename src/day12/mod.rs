@@ -2,28 +2,29 @@
 
 use permutator::Combination;
 
+use crate::input;
+
 fn calc(mut pre: String, record: &str, groups: &[usize]) -> usize {
 
     let mut new_record = None;
 
     for (i, c) in record.chars().enumerate() {
         if c == '?' {
-            if i < record.len() - 1 {
-                new_record = Some(&record[i+1..]);
-            }
+            // possibly empty, when the `?` is the last one
+            new_record = Some(&record[i+1..]);
             break;
         }
         pre.push(c);
     }
 
     if new_record.is_none() {
-        return if pre.chars().chain(record.chars())
-            .collect::<String>()
+        // every group, not just as many as there happen to be
+        // by now `pre` has the whole row
+        return if pre
             .split(".")
             .filter(|s|!s.is_empty())
             .map(|s|s.len())
-            .zip(groups.iter())
-            .all(|(a, b)| a == *b) {
+            .eq(groups.iter().copied()) {
                 1
             } else {
                 0
@@ -70,8 +71,9 @@ fn match_count(cond: Vec<char>, records: Vec<usize>) -> usize {
     }
 
     let sum: usize = records.iter().sum();
-    let k = sum - dama_count;
-    if k == 0 { return 1; }
+    // already too many damaged, or none left to place
+    let Some(k) = sum.checked_sub(dama_count) else { return 0; };
+    if k == 0 { return if is_match(&cond, &records) {1} else {0}; }
 
     let combs = combination(unkn_idx.len(), k);
     let sum = combs.into_iter()
@@ -83,7 +85,6 @@ fn match_count(cond: Vec<char>, records: Vec<usize>) -> usize {
             if is_match(&v, &records) {1} else {0}
         })
         .sum();
-    println!("match-count: {}", sum);
 
     sum
 
@@ -98,50 +99,45 @@ fn match_count(cond: Vec<char>, records: Vec<usize>) -> usize {
     //     .sum()
 }
 
-fn part_1(input: &str) -> usize {
-    input.split("\n")
+/// `???.### 1,1,3` rows: the springs, then the sizes of the damaged groups,
+/// none of them longer than the row.
+pub fn parse(input: &str) -> Option<Vec<(Vec<char>, Vec<usize>)>> {
+    input::lines(input).ok()?.into_iter()
         .map(|line|{
-            let mut it = line.split(" ");
-            let conditions = it.next().unwrap()
-                .chars()
-                .filter(|s| *s!=' ')
-                .collect::<Vec<_>>();
-            let records = it.next().unwrap()
+            let (conditions, records) = line.split_once(" ")?;
+            let conditions = conditions.chars().collect::<Vec<_>>();
+            if !conditions.iter().all(|c| ".#?".contains(*c)) {
+                return None;
+            }
+            let records = records.trim()
                 .split(",")
-                .map(|s|s.parse::<usize>().unwrap())
-                .collect::<Vec<_>>();
-
-            // println!("{:?}", conditions);
-            // println!("{:?}", records);
-
-            match_count(conditions, records)
+                .map(|s|s.parse::<usize>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            if records.iter().any(|&n| n > conditions.len()) {
+                return None;
+            }
+            Some((conditions, records))
         })
+        .collect()
+}
+
+pub fn part_1(input: &str) -> usize {
+    parse(input).unwrap().into_iter()
+        .map(|(conditions, records)| match_count(conditions, records))
         .sum()
 }
 
 
-fn part_2(input: &str) -> usize {
-    input.split("\n")
-        .map(|line|{
-            let mut it = line.split(" ");
-            let mut conditions = it.next().unwrap()
-                .chars()
-                .filter(|s| *s!=' ')
-                .collect::<Vec<_>>();
+pub fn part_2(input: &str) -> usize {
+    parse(input).unwrap().into_iter()
+        .map(|(mut conditions, records)|{
             conditions.push('?');
             conditions = conditions.repeat(5);
             conditions.pop();
 
-            let records = it.next().unwrap()
-                .split(",")
-                .map(|s|s.parse::<usize>().unwrap())
-                .collect::<Vec<_>>()
-                .repeat(5);
-
-            println!("{:?}", conditions);
-            println!("{:?}", records);
+            let records = records.repeat(5);
 
-            match_count(conditions, records) as usize
+            match_count(conditions, records)
         })
         .sum()
 }
@@ -207,4 +203,33 @@ fn test_combination() {
     for v in ret.iter() {
         println!("{:?}", v);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn differential() {
+    use crate::prop::{self, Config, Rng};
+
+    let generate = |rng: &mut Rng| {
+        let springs = (0..rng.size(1..=12)).map(|_| *rng.pick(&['.', '#', '?', '?'])).collect::<String>();
+        let groups = (0..rng.size(1..=4)).map(|_| rng.size(1..=3)).collect::<Vec<_>>();
+        (springs, groups)
+    };
+    let shrink = |(springs, groups): &(String, Vec<usize>)| {
+        let chars = springs.chars().collect::<Vec<_>>();
+        let mut ret = prop::shrink_vec(&chars, 1, |c| if *c == '.' { vec![] } else { vec!['.'] })
+            .into_iter()
+            .map(|v| (v.into_iter().collect(), groups.clone()))
+            .collect::<Vec<_>>();
+        ret.extend(prop::shrink_vec(groups, 1, |n| prop::shrink_int(*n as i64, 1).into_iter().map(|n| n as usize).collect())
+            .into_iter()
+            .map(|g| (springs.clone(), g)));
+        ret
+    };
+    prop::assert_agree(
+        &Config::default(),
+        generate,
+        shrink,
+        |(springs, groups)| calc(String::new(), springs, groups),
+        |(springs, groups)| match_count(springs.chars().collect(), groups.clone()),
+    );
+}
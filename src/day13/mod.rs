@@ -1,3 +1,5 @@
+use crate::{check::Assumption, input};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum MirrorType{
     LeftRight,
@@ -109,19 +111,43 @@ fn fuck_2(group: &Vec<Vec<char>>) -> (MirrorType, usize) {
     (MirrorType::LeftRight, 0)
 }
 
-fn part_1(input: &str) -> usize {
-    input.split("\n\n")
+/// The patterns, each a rectangle of `#` and `.`.
+pub fn parse(input: &str) -> Option<Vec<Vec<Vec<char>>>> {
+    input::paragraphs(input).ok()?.into_iter()
         .map(|group| {
-            let vec = group.split("\n")
-                .filter(|line|!line.is_empty())
+            let vec = group.iter()
                 .map(|line| line.chars().collect::<Vec<_>>())
                 .collect::<Vec<_>>();
+            let width = vec[0].len();
+            let ok = vec.iter().all(|row| row.len() == width && row.iter().all(|c| *c == '#' || *c == '.'));
+            ok.then_some(vec)
+        })
+        .collect()
+}
+
+/// Both parts panic on a pattern without the line they're looking for.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let Some(patterns) = parse(input) else {
+        return Assumption::unparsable("expected blank-line separated rectangles of `#` and `.`");
+    };
+    let without = |find: fn(&Vec<Vec<char>>) -> (MirrorType, usize)| {
+        patterns.iter()
+            .position(|p| find(p).1 == 0)
+            .map_or(Ok(()), |i| Err(format!("pattern {} has none", i + 1)))
+    };
+    vec![
+        Assumption::new("every pattern has a line of reflection", without(fuck_1)),
+        Assumption::new("every pattern has a line of reflection with one smudge", without(fuck_2)),
+    ]
+}
 
+pub fn part_1(input: &str) -> usize {
+    parse(input).unwrap().into_iter()
+        .map(|group| {
             // up * 100 + left
-            let (t, n) = fuck_1(&vec);
+            let (t, n) = fuck_1(&group);
             if n == 0 {
-                println!("error: {}", group);
-                panic!("error: {}", group);
+                panic!("error: {:?}", group);
             }
 
             if t == MirrorType::UpDown {
@@ -133,18 +159,12 @@ fn part_1(input: &str) -> usize {
         .sum::<usize>()
 }
 
-fn part_2(input: &str) -> usize {
-    input.split("\n\n")
+pub fn part_2(input: &str) -> usize {
+    parse(input).unwrap().into_iter()
         .map(|group| {
-            let vec = group.split("\n")
-                .filter(|line|!line.is_empty())
-                .map(|line| line.chars().collect::<Vec<_>>())
-                .collect::<Vec<_>>();
-
-            let (t, n) = fuck_2(&vec);
+            let (t, n) = fuck_2(&group);
             if n == 0 {
-                println!("error: {}", group);
-                panic!("error: {}", group);
+                panic!("error: {:?}", group);
             }
 
             if t == MirrorType::UpDown {
@@ -1,7 +1,16 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::cmp::Ordering;
 
+use crate::{input, math};
 
-fn tilt_north(v: Vec<Vec<char>>) -> Vec<Vec<char>> {
+/// The platform as rows of `O`, `#` and `.`.
+pub fn parse(input: &str) -> Option<Vec<Vec<char>>> {
+    let v = input::grid(input).ok()?.into_iter()
+        .map(|line| line.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    v.iter().flatten().all(|c| "O#.".contains(*c)).then_some(v)
+}
+
+pub fn tilt_north(v: Vec<Vec<char>>) -> Vec<Vec<char>> {
     let mut v = rotate(v);
 
     for v in v.iter_mut() {
@@ -11,14 +20,14 @@ fn tilt_north(v: Vec<Vec<char>>) -> Vec<Vec<char>> {
     rotate(v)
 }
 
-fn tilt_west(mut v: Vec<Vec<char>>) -> Vec<Vec<char>> {
+pub fn tilt_west(mut v: Vec<Vec<char>>) -> Vec<Vec<char>> {
     for v in v.iter_mut() {
         sort(v, |a, b| b.cmp(a));
     }
     v
 }
 
-fn tilt_south(v: Vec<Vec<char>>) -> Vec<Vec<char>> {
+pub fn tilt_south(v: Vec<Vec<char>>) -> Vec<Vec<char>> {
     let mut v = rotate(v);
 
     for v in v.iter_mut() {
@@ -28,13 +37,18 @@ fn tilt_south(v: Vec<Vec<char>>) -> Vec<Vec<char>> {
     rotate(v)
 }
 
-fn tilt_east(mut v: Vec<Vec<char>>) -> Vec<Vec<char>> {
+pub fn tilt_east(mut v: Vec<Vec<char>>) -> Vec<Vec<char>> {
     for v in v.iter_mut() {
         sort(v, |a, b| a.cmp(b));
     }
     v
 }
 
+/// North, west, south, then east.
+pub fn spin_cycle(v: Vec<Vec<char>>) -> Vec<Vec<char>> {
+    tilt_east(tilt_south(tilt_west(tilt_north(v))))
+}
+
 fn rotate<T>(vec2d: Vec<Vec<T>>) -> Vec<Vec<T>> {
     let mut ret = Vec::with_capacity(vec2d[0].len());
 
@@ -73,6 +87,28 @@ where
 }
 
 
+/// Load on the north support beams.
+pub fn north_load(v: &[Vec<char>]) -> usize {
+    let mut load = v.len();
+    let mut sum = 0;
+
+    for v in v.iter() {
+        sum += v.iter().filter(|c|**c=='O').count() * load;
+        load -= 1;
+    }
+    sum
+}
+
+pub fn part_1(input: &str) -> usize {
+    north_load(&tilt_north(parse(input).unwrap()))
+}
+
+/// Spin cycles repeat long before a billion, so only the first loop is run.
+pub fn part_2(input: &str) -> usize {
+    let cycle = math::find_cycle(parse(input).unwrap(), |v| spin_cycle(v.clone()));
+    north_load(cycle.nth(1000000000))
+}
+
 #[test]
 fn test() {
     let input = "
@@ -87,72 +123,15 @@ O.#..O.#.#
 #....###..
 #OO..#...."
     .trim();
+    assert_eq!(part_1(input), 136);
+    assert_eq!(part_2(input), 64);
 
     let input = include_str!("input");
 
-    let v = input.split("\n")
-        .map(|line| line.chars()
-            .collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let origin = v.clone();
-
-    let v = tilt_north(v);
-
-    let mut load = v.len();
-    let mut sum = 0;
-
-    for v in v.iter() {
-        // println!("{:?}", v);
-        sum += v.iter().filter(|c|**c=='O').count() * load;
-        load -= 1;
-    }
-
+    let sum = part_1(input);
     println!("sum: {:?}", sum);
 
     // Part Two
-    let mut v = origin;
-    let cycles = 1000000000;
-
-    let mut states = Vec::new();
-    let mut hash = HashMap::new();
-
-    let mut idx_state = None;
-    let mut idx_i = 0;
-
-    for i in 0..cycles {
-        v = tilt_north(v);
-        v = tilt_west(v);
-        v = tilt_south(v);
-        v = tilt_east(v);
-
-        if let Some(j) = hash.get(&v) {
-            idx_state = Some(*j);
-            idx_i = i;
-            break;
-        }
-        states.push(v.clone());
-        hash.insert(v.clone(), states.len()-1);
-    }
-
-    if let Some(idx) = idx_state {
-        let len = states.len() - idx;
-        let diff = cycles - idx_i - 1;
-
-        v = states[idx + diff % len].clone();
-    }
-
-
-    let mut load = v.len();
-    let mut sum = 0;
-
-    for v in v.iter() {
-        println!("{:?}", v);
-        sum += v.iter().filter(|c|**c=='O').count() * load;
-        load -= 1;
-    }
-
+    let sum = part_2(input);
     println!("sum: {:?}", sum);
-
-
-}
\ No newline at end of file
+}
@@ -1,5 +1,9 @@
+use std::{fmt, str::FromStr};
 
-fn hash(s: &str) -> usize {
+use crate::input;
+
+/// The HASH algorithm: a value in `0..256` for any string.
+pub fn hash(s: &str) -> usize {
     let mut value = 0;
     for c in s.chars() {
         value += c as usize;
@@ -8,64 +12,247 @@ fn hash(s: &str) -> usize {
     value
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Lens {
-    label: &'static str,
-    focal_length: usize,
+/// One step of the initialization sequence, e.g. `rn=1` or `cm-`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// `label=focal_length`
+    Insert(String, usize),
+    /// `label-`
+    Remove(String),
+}
+
+impl FromStr for Step {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(label) = s.strip_suffix('-') {
+            return Ok(Self::Remove(label.to_string()));
+        }
+        let (label, focal_length) = s.split_once('=').ok_or(())?;
+        // up to a `u16`, so the focusing power can't overflow
+        let focal_length = focal_length.parse::<u16>().map_err(|_| ())?;
+        Ok(Self::Insert(label.to_string(), focal_length.into()))
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Insert(label, focal_length) => write!(f, "{}={}", label, focal_length),
+            Step::Remove(label) => write!(f, "{}-", label),
+        }
+    }
+}
+
+/// Parses a comma separated initialization sequence.
+pub fn parse_sequence(input: &str) -> Result<Vec<Step>, String> {
+    // newlines inside the sequence don't count
+    input::lines(input).map_err(|e| e.to_string())?
+        .concat()
+        .split(',')
+        .map(|s| s.trim().parse().map_err(|_| format!("invalid step: {:?}", s)))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Lens<V> {
+    label: String,
+    value: V,
 }
 
-#[derive(Debug)]
-struct HashMap {
-    boxes: Vec<Vec<Lens>>,
+/// Box contents after one step of the initialization sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot<V> {
+    pub step: Step,
+    /// Non-empty boxes only, in box order.
+    pub boxes: Vec<(usize, Vec<(String, V)>)>,
 }
 
-impl HashMap {
+impl<V: fmt::Display> fmt::Display for Snapshot<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "After \"{}\":", self.step)?;
+        for (box_id, lenses) in self.boxes.iter() {
+            write!(f, "Box {}:", box_id)?;
+            for (label, value) in lenses.iter() {
+                write!(f, " [{} {}]", label, value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The Holiday ASCII String Helper Manual Arrangement Procedure:
+/// 256 boxes of labelled lenses, the box picked by `hash(label)`.
+#[derive(Debug, Clone)]
+pub struct LensMap<V = usize> {
+    boxes: Vec<Vec<Lens<V>>>,
+    len: usize,
+    trace: Option<Vec<Snapshot<V>>>,
+}
+
+impl<V> Default for LensMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> LensMap<V> {
     pub fn new() -> Self {
         let mut boxes = Vec::with_capacity(256);
-        boxes.resize(256, Vec::new());
+        boxes.resize_with(256, Vec::new);
         Self {
             boxes,
+            len: 0,
+            trace: None,
         }
     }
 
-    pub fn update(&mut self, s: &'static str) {
-        if s.ends_with('-') {
-            let label = &s[..s.len()-1];
-            let idx = hash(label);
+    /// Like `new`, but every `apply` records a `Snapshot`.
+    pub fn with_trace() -> Self {
+        Self {
+            trace: Some(Vec::new()),
+            ..Self::new()
+        }
+    }
 
-            let v = &mut self.boxes[idx];
-            for i in 0..v.len() {
-                if v[i].label == label {
-                    v.remove(i);
-                    break;
-                }
-            }
-        }else{
-            let mut it = s.split('=');
-            let label = it.next().unwrap();
-            let focal_length = it.next().unwrap().parse().unwrap();
-
-            let idx = hash(label);
-            let v = &mut self.boxes[idx];
-            for i in 0..v.len() {
-                if v[i].label == label {
-                    v[i].focal_length = focal_length;
-                    return;
-                }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Replaces the value in place if `label` is already present, otherwise
+    /// appends it to the back of its box.
+    pub fn insert(&mut self, label: impl Into<String>, value: V) -> Option<V> {
+        let label = label.into();
+        let v = &mut self.boxes[hash(&label)];
+        if let Some(lens) = v.iter_mut().find(|lens| lens.label == label) {
+            return Some(std::mem::replace(&mut lens.value, value));
+        }
+        v.push(Lens { label, value });
+        self.len += 1;
+        None
+    }
+
+    /// Removes `label`, shifting the remaining lenses of its box forward.
+    pub fn remove(&mut self, label: &str) -> Option<V> {
+        let v = &mut self.boxes[hash(label)];
+        let i = v.iter().position(|lens| lens.label == label)?;
+        self.len -= 1;
+        Some(v.remove(i).value)
+    }
+
+    pub fn get(&self, label: &str) -> Option<&V> {
+        self.boxes[hash(label)].iter()
+            .find(|lens| lens.label == label)
+            .map(|lens| &lens.value)
+    }
+
+    pub fn get_mut(&mut self, label: &str) -> Option<&mut V> {
+        self.boxes[hash(label)].iter_mut()
+            .find(|lens| lens.label == label)
+            .map(|lens| &mut lens.value)
+    }
+
+    pub fn contains_key(&self, label: &str) -> bool {
+        self.get(label).is_some()
+    }
+
+    /// `(box, slot, label, value)` in box order, then slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &str, &V)> {
+        self.boxes.iter()
+            .enumerate()
+            .flat_map(|(box_id, v)| {
+                v.iter()
+                    .enumerate()
+                    .map(move |(slot, lens)| (box_id, slot, lens.label.as_str(), &lens.value))
+            })
+    }
+
+    /// Lenses of one box, front to back.
+    pub fn box_contents(&self, box_id: usize) -> impl Iterator<Item = (&str, &V)> {
+        self.boxes[box_id].iter().map(|lens| (lens.label.as_str(), &lens.value))
+    }
+
+    pub fn clear(&mut self) {
+        self.boxes.iter_mut().for_each(|v| v.clear());
+        self.len = 0;
+    }
+
+    /// Snapshots recorded so far, `None` unless built `with_trace`.
+    pub fn trace(&self) -> Option<&[Snapshot<V>]> {
+        self.trace.as_deref()
+    }
+}
+
+impl<V: Clone> LensMap<V> {
+    fn snapshot(&self, step: Step) -> Snapshot<V> {
+        let boxes = self.boxes.iter()
+            .enumerate()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(box_id, v)| {
+                (box_id, v.iter().map(|lens| (lens.label.clone(), lens.value.clone())).collect())
+            })
+            .collect();
+        Snapshot { step, boxes }
+    }
+}
+
+impl LensMap<usize> {
+    pub fn apply(&mut self, step: &Step) {
+        match step {
+            Step::Insert(label, focal_length) => { self.insert(label.as_str(), *focal_length); },
+            Step::Remove(label) => { self.remove(label); },
+        }
+        if self.trace.is_some() {
+            let snapshot = self.snapshot(step.clone());
+            if let Some(trace) = &mut self.trace {
+                trace.push(snapshot);
             }
-            v.push(Lens { label, focal_length })
         }
     }
+
+    /// Runs a whole initialization sequence such as `rn=1,cm-,qp=3`.
+    pub fn run(&mut self, input: &str) -> Result<(), String> {
+        for step in parse_sequence(input)? {
+            self.apply(&step);
+        }
+        Ok(())
+    }
+
+    /// Sum of `(box + 1) * (slot + 1) * focal_length` over every lens.
+    pub fn focusing_power(&self) -> usize {
+        self.iter()
+            .map(|(box_id, slot, _, focal_length)| (box_id + 1) * (slot + 1) * focal_length)
+            .sum()
+    }
+}
+
+pub fn part_1(input: &str) -> usize {
+    input::lines(input).unwrap()
+        .concat()
+        .split(',')
+        .map(hash)
+        .sum()
+}
+
+pub fn part_2(input: &str) -> usize {
+    let mut map = LensMap::new();
+    map.run(input).unwrap();
+    map.focusing_power()
 }
 
 #[test]
 fn solve() {
     let input = include_str!("input");
-    let sum = input.trim()
-        .split(",")
-        .map(|s|hash(s))
-        .sum::<usize>();
+    let sum = part_1(input);
     println!("{}", sum);
+
+    let power = part_2(input);
+    println!("focusing power: {}", power);
 }
 
 #[test]
@@ -76,30 +263,28 @@ fn test() {
     assert_eq!(ret, 52);
 
     let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
-    let sum = input.trim()
-        .split(",")
-        .map(|s|hash(s))
-        .sum::<usize>();
+    let sum = part_1(input);
     println!("{}", sum);
     assert_eq!(sum, 1320);
 
     // Part Two
-    let input = include_str!("input");
-    let mut hashmap = HashMap::new();
-    input.trim()
-        .split(",")
-        .for_each(|s|hashmap.update(s));
+    let mut map = LensMap::with_trace();
+    map.run(input).unwrap();
+    assert_eq!(map.focusing_power(), 145);
+    assert_eq!(map.len(), 5);
+    assert_eq!(map.get("ot"), Some(&7));
+    assert_eq!(map.get("cm"), Some(&2));
+    assert_eq!(map.get("qp"), None);
 
-    let mut sum = 0;
-    
-    for (box_id, b) in hashmap.boxes.iter().enumerate() {
-        if b.is_empty() {continue;}
-        sum += b.iter().enumerate().map(|(slot, lens)| {
-            (box_id + 1) * (slot + 1) * lens.focal_length
-        })
-        .sum::<usize>();
-    }
+    let labels = map.iter().map(|(box_id, _, label, _)| (box_id, label)).collect::<Vec<_>>();
+    assert_eq!(labels, vec![(0, "rn"), (0, "cm"), (3, "ot"), (3, "ab"), (3, "pc")]);
 
-    println!("sum: {:?}", sum);
-}
+    let trace = map.trace().unwrap();
+    assert_eq!(trace.len(), 11);
+    assert_eq!(trace[2].to_string(), "After \"qp=3\":\nBox 0: [rn 1]\nBox 1: [qp 3]\n");
+    assert_eq!(trace[10].to_string(), "After \"ot=7\":\nBox 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n");
 
+    assert_eq!(map.remove("ab"), Some(5));
+    assert_eq!(map.insert("ot".to_string(), 1), Some(7));
+    assert_eq!(map.box_contents(3).collect::<Vec<_>>(), vec![("ot", &1), ("pc", &6)]);
+}
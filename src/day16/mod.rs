@@ -1,17 +1,78 @@
-use std::collections::HashSet;
+use std::{collections::{HashMap, HashSet}, str::FromStr};
 
+use crate::input;
 
-enum Tile {
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tile {
     /// `.`
     Empty,
-    /// `/` or `\`
-    Mirror,
-    /// `|` or `-`
-    Splitter,
+    /// `/`
+    ForwardMirror,
+    /// `\`
+    BackMirror,
+    /// `|`
+    VerticalSplitter,
+    /// `-`
+    HorizontalSplitter,
+}
+
+impl TryFrom<char> for Tile {
+    type Error = ();
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '.' => Ok(Self::Empty),
+            '/' => Ok(Self::ForwardMirror),
+            '\\' => Ok(Self::BackMirror),
+            '|' => Ok(Self::VerticalSplitter),
+            '-' => Ok(Self::HorizontalSplitter),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Tile {
+    pub fn as_char(&self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::ForwardMirror => '/',
+            Tile::BackMirror => '\\',
+            Tile::VerticalSplitter => '|',
+            Tile::HorizontalSplitter => '-',
+        }
+    }
+
+    /// Whether a beam heading `dir` into this tile gets split in two.
+    pub fn splits(&self, dir: Direction) -> bool {
+        match self {
+            Tile::VerticalSplitter => matches!(dir, Direction::Left | Direction::Right),
+            Tile::HorizontalSplitter => matches!(dir, Direction::Up | Direction::Down),
+            _ => false,
+        }
+    }
+
+    /// Directions a beam heading `dir` leaves this tile in.
+    pub fn outputs(&self, dir: Direction) -> Vec<Direction> {
+        use Direction::*;
+        match (self, dir) {
+            (Tile::ForwardMirror, Up) => vec![Right],
+            (Tile::ForwardMirror, Down) => vec![Left],
+            (Tile::ForwardMirror, Left) => vec![Down],
+            (Tile::ForwardMirror, Right) => vec![Up],
+            (Tile::BackMirror, Up) => vec![Left],
+            (Tile::BackMirror, Down) => vec![Right],
+            (Tile::BackMirror, Left) => vec![Up],
+            (Tile::BackMirror, Right) => vec![Down],
+            (Tile::VerticalSplitter, Left | Right) => vec![Up, Down],
+            (Tile::HorizontalSplitter, Up | Down) => vec![Left, Right],
+            _ => vec![dir],
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -19,7 +80,7 @@ enum Direction {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Beam {
+pub struct Beam {
     pub row: usize,
     pub col: usize,
     pub direction: Direction,
@@ -30,131 +91,413 @@ impl Beam {
         Self { row, col, direction }
     }
 
+    /// The beam one cell further along `dir`, `None` if it leaves the contraption.
+    fn advance(&self, dir: Direction, contraption: &Contraption) -> Option<Self> {
+        let (row, col) = (self.row, self.col);
+        match dir {
+            Direction::Up if row > 0 => Some(Self::new(row-1, col, dir)),
+            Direction::Down if row < contraption.height() - 1 => Some(Self::new(row+1, col, dir)),
+            Direction::Left if col > 0 => Some(Self::new(row, col-1, dir)),
+            Direction::Right if col < contraption.width() - 1 => Some(Self::new(row, col+1, dir)),
+            _ => None,
+        }
+    }
+
     pub fn heading_next(&self, contraption: &Contraption) -> Vec<Self> {
-        let height = contraption.tiles.len();
-        let width = contraption.tiles[0].len();
-
-        let (row, col, dir) = (self.row, self.col, self.direction);
-        let mut ret = Vec::with_capacity(2);
-
-        let tile = contraption.tiles[row][col];
-        match tile {
-            '.' => {
-                match dir {
-                    Direction::Up => {
-                        if row > 0 {
-                            ret.push(Self::new(row-1, col, Direction::Up));
-                        }
-                    },
-                    Direction::Down => {
-                        if row < height - 1 {
-                            ret.push(Self::new(row+1, col, Direction::Down));
-                        }
-                    },
-                    Direction::Left => {
-                        if col > 0 {
-                            ret.push(Self::new(row, col-1, Direction::Left));
-                        }
-                    },
-                    Direction::Right => {
-                        if col < width - 1 {
-                            ret.push(Self::new(row, col+1, Direction::Right));
-                        }
-                    },
+        contraption.tile(self.row, self.col)
+            .outputs(self.direction)
+            .into_iter()
+            .filter_map(|dir| self.advance(dir, contraption))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Contraption {
+    tiles: Vec<Vec<Tile>>,
+}
+
+impl FromStr for Contraption {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tiles = input::grid(s).map_err(|_| ())?
+            .into_iter()
+            .map(|line| line.chars().map(Tile::try_from).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(tiles))
+    }
+}
+
+impl Contraption {
+    pub fn new(tiles: Vec<Vec<Tile>>) -> Self {
+        Self { tiles }
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles[0].len()
+    }
+
+    pub fn tile(&self, row: usize, col: usize) -> Tile {
+        self.tiles[row][col]
+    }
+
+    /// Every beam entering from the edge, heading inwards.
+    pub fn edge_entries(&self) -> Vec<Beam> {
+        let (height, width) = (self.height(), self.width());
+        let mut ret = Vec::with_capacity(2 * (height + width));
+        for row in 0..height {
+            ret.push(Beam::new(row, 0, Direction::Right));
+            ret.push(Beam::new(row, width-1, Direction::Left));
+        }
+        for col in 0..width {
+            ret.push(Beam::new(0, col, Direction::Down));
+            ret.push(Beam::new(height-1, col, Direction::Up));
+        }
+        ret
+    }
+
+    /// The beams of a single beam's path, grouped by how many cells they
+    /// are from the start; every beam state appears once.
+    pub fn wavefronts(&self, beam: Beam) -> Vec<Vec<Beam>> {
+        let mut hash = HashSet::new();
+        hash.insert(beam);
+
+        let mut ret = Vec::new();
+        let mut v = vec![beam];
+        while !v.is_empty() {
+            let mut new_beams = Vec::new();
+            for beam in v.iter() {
+                for next in beam.heading_next(self) {
+                    if hash.insert(next) {
+                        new_beams.push(next);
+                    }
                 }
-            },
-            '|' => {
-                if dir != Direction::Down && row > 0 {
-                    ret.push(Self::new(row-1, col, Direction::Up));
+            }
+            ret.push(v);
+            v = new_beams;
+        }
+        ret
+    }
+
+    /// Energized map of a single beam, simulated cell by cell.
+    pub fn energized(&self, beam: Beam) -> Vec<Vec<bool>> {
+        let mut ret = vec![vec![false; self.width()]; self.height()];
+        for beam in self.wavefronts(beam).iter().flatten() {
+            ret[beam.row][beam.col] = true;
+        }
+        ret
+    }
+
+    /// ASCII map of the tiles energized by `beam`, `#` for energized.
+    pub fn render(&self, beam: Beam) -> String {
+        let energized = self.energized(beam);
+        let mut s = String::with_capacity(self.height() * (self.width() + 1));
+        for row in energized.iter() {
+            s.extend(row.iter().map(|e| if *e {'#'} else {'.'}));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+pub fn calc_energized_tiles(beam: Beam, contraption: &Contraption) -> usize {
+    contraption.energized(beam)
+        .iter()
+        .map(|row| row.iter().filter(|e| **e).count())
+        .sum()
+}
+
+/// A straight run of beam between two splits.
+#[derive(Debug, Clone)]
+struct Segment {
+    /// Cell indices, `row * width + col`; a cell crossed twice appears twice.
+    cells: Vec<usize>,
+    /// The splitter node the segment ends in, `None` if it leaves the contraption or loops.
+    target: Option<usize>,
+}
+
+/// Splitters that actually split, linked by the segments between them.
+///
+/// The graph is condensed into strongly connected components, each keeping
+/// only the cells its own splitters and segments energize. Entries are then
+/// grouped by the component they first reach, and each group's reachable
+/// cells are marked once, so memory stays linear in the size of the grid.
+/// What the biggest component, the hub, reaches is marked up front: most
+/// beams end up there, and their walks stop where the hub's begins.
+pub struct BeamGraph<'a> {
+    contraption: &'a Contraption,
+    node_of: HashMap<(usize, usize), usize>,
+    /// Component of every node.
+    component: Vec<usize>,
+    /// Cells energized by the component's own nodes, not its successors'.
+    cells: Vec<Vec<usize>>,
+    /// Components a beam leaving the component goes on to.
+    successors: Vec<Vec<usize>>,
+    /// Whether each component reaches the hub.
+    leads_to_hub: Vec<bool>,
+    /// Whether the hub reaches each component.
+    below_hub: Vec<bool>,
+    /// Whether the hub reaches each cell.
+    hub_cells: Vec<bool>,
+    hub_count: usize,
+}
+
+impl<'a> BeamGraph<'a> {
+    pub fn new(contraption: &'a Contraption) -> Self {
+        let mut node_of = HashMap::new();
+        for row in 0..contraption.height() {
+            for col in 0..contraption.width() {
+                if matches!(contraption.tile(row, col), Tile::VerticalSplitter | Tile::HorizontalSplitter) {
+                    let id = node_of.len();
+                    node_of.insert((row, col), id);
                 }
-                if dir != Direction::Up && row < height - 1 {
-                    ret.push(Self::new(row+1, col, Direction::Down));
+            }
+        }
+
+        let mut this = Self {
+            contraption,
+            node_of,
+            component: Vec::new(),
+            cells: Vec::new(),
+            successors: Vec::new(),
+            leads_to_hub: Vec::new(),
+            below_hub: Vec::new(),
+            hub_cells: Vec::new(),
+            hub_count: 0,
+        };
+
+        let mut nodes = vec![(Vec::new(), Vec::new()); this.node_of.len()];
+        for (&(row, col), &id) in this.node_of.iter() {
+            let mut cells = vec![row * contraption.width() + col];
+            let mut next = Vec::with_capacity(2);
+
+            let split = match contraption.tile(row, col) {
+                Tile::VerticalSplitter => [Direction::Up, Direction::Down],
+                _ => [Direction::Left, Direction::Right],
+            };
+            for dir in split {
+                if let Some(start) = Beam::new(row, col, dir).advance(dir, contraption) {
+                    let segment = this.trace(start);
+                    cells.extend(segment.cells);
+                    next.extend(segment.target);
                 }
-            },
-            '-' => {
-                if dir != Direction::Left && col < width - 1{
-                    ret.push(Self::new(row, col+1, Direction::Right));
+            }
+            nodes[id] = (cells, next);
+        }
+
+        this.condense(nodes);
+        this.mark_hub();
+        this
+    }
+
+    fn cell_count(&self) -> usize {
+        self.contraption.height() * self.contraption.width()
+    }
+
+    /// Follows `beam` until it hits a splitting splitter, leaves, or loops.
+    fn trace(&self, mut beam: Beam) -> Segment {
+        let width = self.contraption.width();
+        let mut cells = Vec::new();
+        let mut seen = HashSet::new();
+
+        loop {
+            cells.push(beam.row * width + beam.col);
+            if !seen.insert(beam) {
+                return Segment { cells, target: None };
+            }
+            let tile = self.contraption.tile(beam.row, beam.col);
+            if tile.splits(beam.direction) {
+                return Segment { cells, target: Some(self.node_of[&(beam.row, beam.col)]) };
+            }
+            let dir = tile.outputs(beam.direction)[0];
+            match beam.advance(dir, self.contraption) {
+                Some(next) => beam = next,
+                None => return Segment { cells, target: None },
+            }
+        }
+    }
+
+    /// Tarjan's SCC, keeping each component's own cells and the components
+    /// it leads to.
+    fn condense(&mut self, mut nodes: Vec<(Vec<usize>, Vec<usize>)>) {
+        let n = nodes.len();
+        let mut index = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut counter = 0;
+        self.component = vec![usize::MAX; n];
+
+        for root in 0..n {
+            if index[root] != usize::MAX { continue; }
+
+            // (node, next edge to visit)
+            let mut call = vec![(root, 0)];
+            index[root] = counter;
+            low[root] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&mut (v, ref mut edge)) = call.last_mut() {
+                if let Some(&w) = nodes[v].1.get(*edge) {
+                    *edge += 1;
+                    if index[w] == usize::MAX {
+                        index[w] = counter;
+                        low[w] = counter;
+                        counter += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        call.push((w, 0));
+                    } else if on_stack[w] {
+                        low[v] = low[v].min(index[w]);
+                    }
+                    continue;
                 }
-                if dir != Direction::Right && col > 0  {
-                    ret.push(Self::new(row, col-1, Direction::Left));
+
+                call.pop();
+                if let Some(&(parent, _)) = call.last() {
+                    low[parent] = low[parent].min(low[v]);
                 }
-            },
-            '/' => {
-                match dir {
-                    Direction::Up => if col < width - 1 {
-                        ret.push(Self::new(row, col+1, Direction::Right));
-                    },
-                    Direction::Down => if col > 0 {
-                        ret.push(Self::new(row, col-1, Direction::Left));
-                    },
-                    Direction::Left => if row < height - 1 {
-                        ret.push(Self::new(row+1, col, Direction::Down));
-                    },
-                    Direction::Right => if row > 0 {
-                        ret.push(Self::new(row-1, col, Direction::Up));
-                    },
+                if low[v] != index[v] { continue; }
+
+                let id = self.cells.len();
+                let mut members = Vec::new();
+                loop {
+                    let w = stack.pop().unwrap();
+                    on_stack[w] = false;
+                    self.component[w] = id;
+                    members.push(w);
+                    if w == v { break; }
                 }
-            },
-            '\\' => {
-                match dir {
-                    Direction::Up => if col > 0 {
-                        ret.push(Self::new(row, col-1, Direction::Left));
-                    },
-                    Direction::Down => if col < width - 1 {
-                        ret.push(Self::new(row, col+1, Direction::Right));
-                    },
-                    Direction::Left => if row > 0 {
-                        ret.push(Self::new(row-1, col, Direction::Up));
-                    },
-                    Direction::Right => if row < height - 1 {
-                        ret.push(Self::new(row+1, col, Direction::Down));
-                    },
+
+                let mut cells = Vec::new();
+                let mut successors = Vec::new();
+                for &w in members.iter() {
+                    cells.append(&mut nodes[w].0);
+                    for &x in nodes[w].1.iter() {
+                        if self.component[x] != id && !successors.contains(&self.component[x]) {
+                            successors.push(self.component[x]);
+                        }
+                    }
                 }
-            },
-            _ => unreachable!(),
+                self.cells.push(cells);
+                self.successors.push(successors);
+            }
         }
-        ret
     }
-}
 
-struct Contraption {
-    tiles: Vec<Vec<char>>,
-}
+    fn mark_hub(&mut self) {
+        let n = self.component_count();
+        self.leads_to_hub = vec![false; n];
+        self.below_hub = vec![false; n];
+        self.hub_cells = vec![false; self.cell_count()];
+        let Some(hub) = (0..n).max_by_key(|&c| self.cells[c].len()) else { return };
 
-impl Contraption {
-    pub fn new(tiles: Vec<Vec<char>>) -> Self {
-        Self { tiles }
+        let mut stack = vec![hub];
+        while let Some(c) = stack.pop() {
+            if self.below_hub[c] { continue; }
+            self.below_hub[c] = true;
+            for &cell in self.cells[c].iter() {
+                if !self.hub_cells[cell] {
+                    self.hub_cells[cell] = true;
+                    self.hub_count += 1;
+                }
+            }
+            stack.extend(self.successors[c].iter().copied());
+        }
+        // successors always come first
+        for c in 0..n {
+            self.leads_to_hub[c] = c == hub || self.successors[c].iter().any(|&x| self.leads_to_hub[x]);
+        }
     }
-}
 
-fn calc_energized_tiles(beam: Beam, contraption: &Contraption) -> usize {
-    let mut hash = HashSet::new();
-    hash.insert(beam);
+    /// Number of splitter nodes in the graph.
+    pub fn node_count(&self) -> usize {
+        self.node_of.len()
+    }
 
-    let mut v = beam.heading_next(&contraption);
+    /// Number of strongly connected components of the graph.
+    pub fn component_count(&self) -> usize {
+        self.cells.len()
+    }
 
-    loop {
-        if v.is_empty() { break; }
+    pub fn energized_count(&self, beam: Beam) -> usize {
+        self.energized_counts(&[beam])[0]
+    }
+
+    /// How many tiles each of `beams` energizes. Beams reaching the same
+    /// component first share one walk over everything it leads to.
+    pub fn energized_counts(&self, beams: &[Beam]) -> Vec<usize> {
+        let segments = beams.iter().map(|&beam| self.trace(beam)).collect::<Vec<_>>();
+        let mut order = (0..beams.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| segments[i].target.map(|node| self.component[node]));
 
-        let mut new_beams = Vec::new();
-        for beam in v {
-            if hash.get(&beam).is_none() {
-                hash.insert(beam);
-                new_beams.extend(beam.heading_next(&contraption).into_iter());
+        // the last group, and the last beam, to have marked each cell
+        let mut reached = vec![usize::MAX; self.cell_count()];
+        let mut own = vec![usize::MAX; self.cell_count()];
+        let mut visited = vec![usize::MAX; self.component_count()];
+        let mut ret = vec![0; beams.len()];
+
+        let mut group = 0;
+        while group < order.len() {
+            let target = segments[order[group]].target.map(|node| self.component[node]);
+            let via_hub = target.is_some_and(|c| self.leads_to_hub[c]);
+            let mut reach = if via_hub { self.hub_count } else { 0 };
+            let mut stack = target.into_iter().collect::<Vec<_>>();
+            while let Some(c) = stack.pop() {
+                if visited[c] == group || via_hub && self.below_hub[c] { continue; }
+                visited[c] = group;
+                for &cell in self.cells[c].iter() {
+                    if reached[cell] != group && !(via_hub && self.hub_cells[cell]) {
+                        reached[cell] = group;
+                        reach += 1;
+                    }
+                }
+                stack.extend(self.successors[c].iter().copied());
             }
+
+            let len = order[group..].iter()
+                .take_while(|&&i| segments[i].target.map(|node| self.component[node]) == target)
+                .count();
+            for &i in order[group..group + len].iter() {
+                ret[i] = reach;
+                for &cell in segments[i].cells.iter() {
+                    if reached[cell] != group && !(via_hub && self.hub_cells[cell]) && own[cell] != i {
+                        own[cell] = i;
+                        ret[i] += 1;
+                    }
+                }
+            }
+            group += len;
         }
-        v = new_beams;
+        ret
     }
 
-    let mut energized_nodes = HashSet::new();
+    /// The best edge entry and how many tiles it energizes.
+    pub fn max_energized(&self) -> (Beam, usize) {
+        let beams = self.contraption.edge_entries();
+        let counts = self.energized_counts(&beams);
+        beams.into_iter()
+            .zip(counts)
+            .max_by_key(|(_, count)| *count)
+            .unwrap()
+    }
+}
 
-    hash.iter()
-        .map(|beam| {
-            (beam.row, beam.col)
-        })
-        .for_each(|n| {energized_nodes.insert(n);});
+pub fn part_1(input: &str) -> usize {
+    let contraption: Contraption = input.parse().unwrap();
+    calc_energized_tiles(Beam::new(0, 0, Direction::Right), &contraption)
+}
 
-    energized_nodes.len()
+pub fn part_2(input: &str) -> usize {
+    let contraption: Contraption = input.parse().unwrap();
+    BeamGraph::new(&contraption).max_energized().1
 }
 
 #[test]
@@ -172,43 +515,71 @@ fn test() {
 ..//.|....
 ".trim();
 
-    let input = include_str!("input");
-    println!("{}", input);
+    let contraption: Contraption = input.parse().unwrap();
 
-    let tiles = input.split("\n")
-        .map(|line| {
-            line.chars().collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
+    let beam = Beam::new(0, 0, Direction::Right);
+    let count = calc_energized_tiles(beam, &contraption);
+    assert_eq!(count, 46);
+
+    let graph = BeamGraph::new(&contraption);
+    assert_eq!(graph.energized_count(beam), 46);
+    for beam in contraption.edge_entries() {
+        assert_eq!(graph.energized_count(beam), calc_energized_tiles(beam, &contraption));
+    }
+
+    let (beam, max) = graph.max_energized();
+    assert_eq!(max, 51);
+    assert_eq!(beam, Beam::new(0, 3, Direction::Down));
+
+    let map = contraption.render(Beam::new(0, 0, Direction::Right));
+    println!("{}", map);
+    assert_eq!(map.lines().next(), Some("######...."));
+    assert_eq!(map.matches('#').count(), 46);
+}
+
+#[test]
+fn differential() {
+    use crate::prop::{self, Config, Rng};
+
+    let generate = |rng: &mut Rng| {
+        let (h, w) = (rng.size(1..=7), rng.size(1..=7));
+        (0..h)
+            .map(|_| (0..w).map(|_| *rng.pick(&['.', '.', '.', '|', '-', '/', '\\'])).collect())
+            .collect::<Vec<Vec<_>>>()
+    };
+    // every entry from the edge, simulated against the condensed graph
+    let counts = |grid: &Vec<Vec<char>>, fast: bool| {
+        let contraption: Contraption = prop::render_grid(grid).parse().unwrap();
+        let entries = contraption.edge_entries();
+        if fast {
+            return BeamGraph::new(&contraption).energized_counts(&entries);
+        }
+        entries.into_iter()
+            .map(|beam| calc_energized_tiles(beam, &contraption))
+            .collect::<Vec<_>>()
+    };
+    prop::assert_agree(
+        &Config::default(),
+        generate,
+        |grid| prop::shrink_grid(grid, &'.'),
+        |grid| counts(grid, false),
+        |grid| counts(grid, true),
+    );
+}
 
-    let contraption = Contraption::new(tiles);
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let contraption: Contraption = input.parse().unwrap();
 
     let beam = Beam::new(0, 0, Direction::Right);
     let count = calc_energized_tiles(beam, &contraption);
     println!("energized nodes: {}", count);
 
     // Part Two
-    let mut max = 0;
-    let height = contraption.tiles.len();
-    let width = contraption.tiles[0].len();
-
-    for row in 0..height {
-        let beam = Beam::new(row, 0, Direction::Right);
-        let count = calc_energized_tiles(beam, &contraption);
-        max = max.max(count);
-        let beam = Beam::new(row, width-1, Direction::Left);
-        let count = calc_energized_tiles(beam, &contraption);
-        max = max.max(count);
-    }
-    for col in 0..width {
-        let beam = Beam::new(0, col, Direction::Down);
-        let count = calc_energized_tiles(beam, &contraption);
-        max = max.max(count);
-        let beam = Beam::new(height-1, col, Direction::Up);
-        let count = calc_energized_tiles(beam, &contraption);
-        max = max.max(count);
-    }
-
+    let graph = BeamGraph::new(&contraption);
+    assert_eq!(graph.energized_count(beam), count);
+    let (_, max) = graph.max_energized();
     println!("max: {:?}", max);
 }
 
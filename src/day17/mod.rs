@@ -1,280 +1,308 @@
-use std::collections::BTreeSet;
+use std::{cmp::Reverse, collections::BinaryHeap, str::FromStr};
 
-fn calc_no_dir_len_limit(map: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-    let heigth = map.len();
-    let width = map[0].len();
+use crate::{check::Assumption, input};
 
-    let (dst_row, dst_col) = (heigth-1, width-1);
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
 
-    let mut ret = Vec::with_capacity(heigth);
-    ret.resize_with(heigth, || {
-        let mut v = Vec::<usize>::with_capacity(width);
-        v.resize(width, 0);
-        v
-    });
+impl Pos {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+}
 
-    ret[dst_row][dst_col] = 0;
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
 
-    let mut starts = BTreeSet::new();
+impl Dir {
+    fn as_char(&self) -> char {
+        match self {
+            Dir::Up => '^',
+            Dir::Down => 'v',
+            Dir::Left => '<',
+            Dir::Right => '>',
+        }
+    }
+}
+
+/// City blocks with the heat each one loses when entered.
+#[derive(Debug, Clone)]
+pub struct HeatMap {
+    blocks: Vec<Vec<usize>>,
+}
+
+impl FromStr for HeatMap {
+    type Err = ();
 
-    // up
-    if dst_row > 0 {
-        let row = dst_row - 1;
-        starts.insert((row, dst_col));
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let blocks = input::grid(s).map_err(|_| ())?
+            .into_iter()
+            .map(|line| {
+                line.chars()
+                    .map(|c| c.to_digit(10).map(|d| d as usize).ok_or(()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { blocks })
     }
-    // left
-    if dst_col > 0 {
-        let col = dst_col - 1;
-        starts.insert((dst_row, col));
+}
+
+impl HeatMap {
+    pub fn height(&self) -> usize {
+        self.blocks.len()
     }
 
-    loop {
-        if starts.is_empty() { break; }
+    pub fn width(&self) -> usize {
+        self.blocks[0].len()
+    }
 
-        let mut new_round = BTreeSet::new();
+    pub fn heat(&self, pos: Pos) -> usize {
+        self.blocks[pos.row][pos.col]
+    }
 
-        for (row, col) in starts.iter() {
-            let (row, col) = (*row, *col);
-            if row > 0 { new_round.insert((row-1, col)); }
-            if col > 0 { new_round.insert((row, col-1)); }
+    pub fn top_left(&self) -> Pos {
+        Pos::new(0, 0)
+    }
 
-            ret[row][col] = 
-                if (row + 1 == dst_row && col == dst_col) || (row == dst_row && col + 1 == dst_col) {
-                    map[dst_row][dst_col]
-                } else {
-                    let mut min = usize::MAX;
+    pub fn bottom_right(&self) -> Pos {
+        Pos::new(self.height() - 1, self.width() - 1)
+    }
 
-                    if row < heigth - 1 {
-                        min = min.min(ret[row + 1][col] + map[row + 1][col]);
-                    }
-                    if col < width - 1 {
-                        min = min.min(ret[row][col + 1] + map[row][col + 1]);
-                    }
-                    min
-                };
+    /// `pos` moved `n` blocks along `dir`, `None` if that leaves the city.
+    fn walk(&self, pos: Pos, dir: Dir, n: usize) -> Option<Pos> {
+        let Pos { row, col } = pos;
+        match dir {
+            Dir::Up => row.checked_sub(n).map(|row| Pos::new(row, col)),
+            Dir::Down => (row + n < self.height()).then(|| Pos::new(row + n, col)),
+            Dir::Left => col.checked_sub(n).map(|col| Pos::new(row, col)),
+            Dir::Right => (col + n < self.width()).then(|| Pos::new(row, col + n)),
         }
-
-        starts = new_round;
     }
-
-    ret
 }
 
-#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
-struct Pos {
-    row: usize,
-    col: usize,
+/// An optimal route, `path` running from the start to the goal inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub heat_loss: usize,
+    pub path: Vec<Pos>,
 }
 
-impl Pos {
-    pub fn new(row: usize, col: usize) -> Self {
-        Self { row, col }
+impl Route {
+    /// The map with the path drawn in `>v<^`, like the puzzle text.
+    pub fn render(&self, map: &HeatMap) -> String {
+        let mut v = map.blocks.iter()
+            .map(|row| row.iter().map(|h| char::from_digit(*h as u32, 10).unwrap()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        for w in self.path.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let dir = if b.row < a.row {
+                Dir::Up
+            } else if b.row > a.row {
+                Dir::Down
+            } else if b.col < a.col {
+                Dir::Left
+            } else {
+                Dir::Right
+            };
+            v[b.row][b.col] = dir.as_char();
+        }
+
+        v.into_iter()
+            .map(|row| row.into_iter().collect::<String>() + "\n")
+            .collect()
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
+/// A crucible that must move at least `min_run` and at most `max_run` blocks
+/// in a straight line before turning or stopping.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Crucible {
+    pub min_run: usize,
+    pub max_run: usize,
 }
 
-fn calc(map: &Vec<Vec<usize>>) -> usize {
-    let mut path_right = BTreeSet::new();
-    path_right.insert(Pos::new(0, 1));
-    let right = do_calc(map, path_right, Pos::new(0, 1), Dir::Right, 1).unwrap() + map[0][1];
+impl Crucible {
+    pub const NORMAL: Self = Self { min_run: 1, max_run: 3 };
+    pub const ULTRA: Self = Self { min_run: 4, max_run: 10 };
 
-    let mut path_down = BTreeSet::new();
-    path_down.insert(Pos::new(1, 0));
-    let down  = do_calc(map, path_down, Pos::new(1, 0), Dir::Down, 1).unwrap() + map[1][0];
-    right.min(down)
-}
+    pub fn new(min_run: usize, max_run: usize) -> Self {
+        assert!(min_run >= 1 && min_run <= max_run);
+        Self { min_run, max_run }
+    }
 
+    /// Dijkstra over `(block, axis of the last run)`; every edge is a whole
+    /// straight run, so the run length never has to be part of the state.
+    fn search(&self, map: &HeatMap, start: Pos) -> Search {
+        let (height, width) = (map.height(), map.width());
+        let index = |pos: Pos, vertical: bool| (pos.row * width + pos.col) * 2 + vertical as usize;
 
-fn do_calc(map: &Vec<Vec<usize>>, path: BTreeSet<Pos>, pos: Pos, dir: Dir, count: i32) -> Option<usize> {
-    let heigth = map.len();
-    let width = map[0].len();
+        let mut dist = vec![usize::MAX; height * width * 2];
+        let mut prev = vec![usize::MAX; height * width * 2];
+        let mut heap = BinaryHeap::new();
 
-    let (dst_row, dst_col) = (heigth-1, width-1);
-    let Pos { row, col } = pos;
+        for vertical in [false, true] {
+            dist[index(start, vertical)] = 0;
+            heap.push(Reverse((0, start, vertical)));
+        }
 
-    if row == dst_row && col == dst_col {
-        return Some(0);
-    }
+        while let Some(Reverse((heat_loss, pos, vertical))) = heap.pop() {
+            let from = index(pos, vertical);
+            if heat_loss > dist[from] { continue; }
 
-    let mut min = usize::MAX;
-
-    match dir {
-        Dir::Up => {
-            // up
-            if count < 3 && row > 0 {
-                let count = count + 1;
-                let row = row - 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-            // left
-            if col > 0 {
-                let count = 1;
-                let dir = Dir::Left;
-                let col = col - 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-            // right
-            if col < width - 1 {
-                let count = 1;
-                let dir = Dir::Right;
-                let col = col + 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-        },
-        Dir::Down => {
-            // down
-            if count < 3 && row < heigth - 1 {
-                let count = count + 1;
-                let row = row + 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-            // left
-            if col > 0 {
-                let count = 1;
-                let dir = Dir::Left;
-                let col = col - 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-            // right
-            if col < width - 1 {
-                let count = 1;
-                let dir = Dir::Right;
-                let col = col + 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-        },
-        Dir::Left => {
-            // left
-            if count < 3 && col > 0 {
-                let count = count + 1;
-                let col = col - 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-            // up
-            if row > 0 {
-                let row = row - 1;
-                let dir = Dir::Up;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-            // down
-            if row < heigth - 1 {
-                let row = row + 1;
-                let dir = Dir::Down;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
+            // the next run turns onto the other axis
+            let dirs = if vertical { [Dir::Left, Dir::Right] } else { [Dir::Up, Dir::Down] };
+            for dir in dirs {
+                let mut acc = heat_loss;
+                for n in 1..=self.max_run {
+                    let Some(next) = map.walk(pos, dir, n) else { break; };
+                    acc += map.heat(next);
+                    if n < self.min_run { continue; }
 
-        },
-        Dir::Right => {
-            // right
-            if count < 3 && col < width - 1 {
-                let count = count + 1;
-                let col = col + 1;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
+                    let to = index(next, !vertical);
+                    if acc < dist[to] {
+                        dist[to] = acc;
+                        prev[to] = from;
+                        heap.push(Reverse((acc, next, !vertical)));
                     }
                 }
             }
-            // up
-            if row > 0 {
-                let row = row - 1;
-                let dir = Dir::Up;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
-            }
-            // down
-            if row < heigth - 1 {
-                let row = row + 1;
-                let dir = Dir::Down;
-                let pos = Pos::new(row, col);
-                let mut path = path.clone();
-                if path.insert(pos) {
-                    if let Some(x) = do_calc(map, path, pos, dir, count) {
-                        min = min.min(x + map[row][col]);
-                    }
-                }
+        }
+
+        Search { width, dist, prev }
+    }
+
+    /// The least heat lost getting from `start` to `goal`, with its path.
+    pub fn route(&self, map: &HeatMap, start: Pos, goal: Pos) -> Option<Route> {
+        if start == goal {
+            return Some(Route { heat_loss: 0, path: vec![start] });
+        }
+        let search = self.search(map, start);
+        let state = [false, true].into_iter()
+            .map(|vertical| search.state(goal, vertical))
+            .min_by_key(|state| search.dist[*state])?;
+        let heat_loss = search.dist[state];
+        if heat_loss == usize::MAX {
+            return None;
+        }
+
+        // walk the predecessors back, filling in the blocks of every run
+        let mut path = vec![goal];
+        let mut state = state;
+        while search.prev[state] != usize::MAX {
+            let from = search.pos(search.prev[state]);
+            let to = search.pos(state);
+            let mut cur = to;
+            while cur != from {
+                cur = Pos::new(
+                    if cur.row < from.row { cur.row + 1 } else if cur.row > from.row { cur.row - 1 } else { cur.row },
+                    if cur.col < from.col { cur.col + 1 } else if cur.col > from.col { cur.col - 1 } else { cur.col },
+                );
+                path.push(cur);
             }
-        },
+            state = search.prev[state];
+        }
+        path.reverse();
+
+        Some(Route { heat_loss, path })
     }
 
-    if min == usize::MAX {None} else {Some(min)}
+    /// Least heat lost reaching every block from `start`, `None` for blocks
+    /// the crucible cannot stop on.
+    pub fn heat_loss_map(&self, map: &HeatMap, start: Pos) -> Vec<Vec<Option<usize>>> {
+        let search = self.search(map, start);
+        (0..map.height())
+            .map(|row| {
+                (0..map.width())
+                    .map(|col| {
+                        let pos = Pos::new(row, col);
+                        let min = search.dist[search.state(pos, false)].min(search.dist[search.state(pos, true)]);
+                        (min != usize::MAX).then_some(min)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// `heat_loss_map` as right-aligned columns, `.` for unreachable blocks.
+    pub fn render_heat_loss_map(&self, map: &HeatMap, start: Pos) -> String {
+        let v = self.heat_loss_map(map, start);
+        let cell_width = v.iter()
+            .flatten()
+            .flatten()
+            .max()
+            .map_or(1, |max| max.to_string().len());
+
+        let mut s = String::new();
+        for row in v.iter() {
+            let line = row.iter()
+                .map(|h| match h {
+                    Some(h) => format!("{:>w$}", h, w = cell_width),
+                    None => format!("{:>w$}", '.', w = cell_width),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            s.push_str(&line);
+            s.push('\n');
+        }
+        s
+    }
+}
+
+struct Search {
+    width: usize,
+    dist: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl Search {
+    fn state(&self, pos: Pos, vertical: bool) -> usize {
+        (pos.row * self.width + pos.col) * 2 + vertical as usize
+    }
+
+    fn pos(&self, state: usize) -> Pos {
+        let cell = state / 2;
+        Pos::new(cell / self.width, cell % self.width)
+    }
+}
+
+/// An ultra crucible can't stop short of four blocks, so on a small or
+/// narrow map it may never get to the factory at all.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let Ok(map) = input.parse::<HeatMap>() else {
+        return Assumption::unparsable("expected a rectangle of digits");
+    };
+    let reaches = |crucible: Crucible| crucible.route(&map, map.top_left(), map.bottom_right()).is_some();
+    vec![
+        Assumption::check("a crucible can get to the factory", reaches(Crucible::NORMAL), || "no route".to_string()),
+        Assumption::check("an ultra crucible can get to the factory", reaches(Crucible::ULTRA), || {
+            format!("no route through {} by {} blocks", map.height(), map.width())
+        }),
+    ]
 }
 
-fn solve(input: &str) -> usize {
-    let map = input.trim()
-        .split("\n")
-        .map(|line| line.chars().map(|c|c as usize - '0' as usize).collect::<Vec<_>>())
-        .collect::<Vec<_>>();
+pub fn solve(input: &str, crucible: Crucible) -> usize {
+    let map: HeatMap = input.parse().unwrap();
+    crucible.route(&map, map.top_left(), map.bottom_right())
+        .unwrap()
+        .heat_loss
+}
+
+
+pub fn part_1(input: &str) -> usize {
+    solve(input, Crucible::NORMAL)
+}
 
-    calc(&map)
+pub fn part_2(input: &str) -> usize {
+    solve(input, Crucible::ULTRA)
 }
 
 #[test]
@@ -297,6 +325,47 @@ fn test() {
 
     println!("{}", input);
 
-    let min = solve(input);
+    let min = solve(input, Crucible::NORMAL);
     println!("min: {}", min);
+    assert_eq!(min, 102);
+
+    let map: HeatMap = input.parse().unwrap();
+    let route = Crucible::NORMAL.route(&map, map.top_left(), map.bottom_right()).unwrap();
+    let rendered = route.render(&map);
+    println!("{}", rendered);
+    assert_eq!(rendered.lines().next(), Some("2>>34^>>>1323"));
+    assert_eq!(route.path.first(), Some(&map.top_left()));
+    assert_eq!(route.path.last(), Some(&map.bottom_right()));
+    assert_eq!(route.path.iter().skip(1).map(|pos| map.heat(*pos)).sum::<usize>(), 102);
+
+    let heat = Crucible::NORMAL.heat_loss_map(&map, map.top_left());
+    assert_eq!(heat[0][0], Some(0));
+    assert_eq!(heat[12][12], Some(102));
+    println!("{}", Crucible::NORMAL.render_heat_loss_map(&map, map.top_left()));
+
+    // Part Two
+    let min = solve(input, Crucible::ULTRA);
+    assert_eq!(min, 94);
+
+    let input = "
+111111111111
+999999999991
+999999999991
+999999999991
+999999999991
+    ".trim();
+    let min = solve(input, Crucible::ULTRA);
+    assert_eq!(min, 71);
+
+    let map: HeatMap = input.parse().unwrap();
+    let route = Crucible::new(1, 12).route(&map, Pos::new(0, 11), Pos::new(0, 0)).unwrap();
+    assert_eq!(route.heat_loss, 11);
+    assert_eq!(route.path.len(), 12);
+}
+
+#[test]
+fn solve_input() {
+    let input = include_str!("input");
+    println!("min: {}", solve(input, Crucible::NORMAL));
+    println!("ultra min: {}", solve(input, Crucible::ULTRA));
 }
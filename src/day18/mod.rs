@@ -1,9 +1,9 @@
-use std::{str::FromStr, collections::{HashSet, HashMap}};
+use std::{collections::HashMap, str::FromStr};
 
-mod part2;
+use crate::{input, polygon::Polygon};
 
-#[derive(Debug, Copy, Clone)]
-enum Dir {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Dir {
     Up,
     Down,
     Left,
@@ -24,16 +24,235 @@ impl FromStr for Dir {
     }
 }
 
+impl Dir {
+    /// `(row, col)` offset of one step.
+    fn delta(&self) -> (i64, i64) {
+        match self {
+            Dir::Up => (-1, 0),
+            Dir::Down => (1, 0),
+            Dir::Left => (0, -1),
+            Dir::Right => (0, 1),
+        }
+    }
+}
 
-struct DigCmd {
-    dir: Dir,
-    steps: i32,
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DigCmd {
+    pub dir: Dir,
+    pub steps: i64,
 }
 
 impl DigCmd {
-    fn new(dir: Dir, steps: i32) -> Self {
+    pub fn new(dir: Dir, steps: i64) -> Self {
         Self { dir, steps }
     }
+
+    /// Decodes a colour like `#70c710`: five hex digits of steps, then the
+    /// direction as `0..=3` for `R D L U`.
+    fn from_color(color: &str) -> Result<Self, ()> {
+        let hex = color.strip_prefix('#').ok_or(())?;
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(());
+        }
+        let steps = i64::from_str_radix(&hex[..5], 16).map_err(|_| ())?;
+        let dir = match &hex[5..] {
+            "0" => Dir::Right,
+            "1" => Dir::Down,
+            "2" => Dir::Left,
+            "3" => Dir::Up,
+            _ => return Err(()),
+        };
+        Ok(Self::new(dir, steps))
+    }
+}
+
+/// One line of the dig plan, read both ways.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DigStep {
+    /// `R 6`
+    pub plain: DigCmd,
+    /// `(#70c710)`
+    pub encoded: DigCmd,
+}
+
+impl FromStr for DigStep {
+    type Err = ();
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut it = line.split_whitespace();
+        let dir: Dir = it.next().ok_or(())?.parse()?;
+        let steps = it.next().ok_or(())?.parse::<u32>().map_err(|_| ())?;
+        // no further than the colour can say, which keeps the trench well
+        // within `polygon::LIMIT`
+        if steps >= 1 << 20 {
+            return Err(());
+        }
+        let color = it.next().ok_or(())?
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or(())?;
+        if it.next().is_some() {
+            return Err(());
+        }
+
+        Ok(Self {
+            plain: DigCmd::new(dir, steps.into()),
+            encoded: DigCmd::from_color(color)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigPlan {
+    pub steps: Vec<DigStep>,
+}
+
+impl FromStr for DigPlan {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = input::lines(s).map_err(|_| ())?
+            .into_iter()
+            .map(|line| line.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { steps })
+    }
+}
+
+impl DigPlan {
+    /// Part one's reading, `R 6`.
+    pub fn plain(&self) -> Vec<DigCmd> {
+        self.steps.iter().map(|step| step.plain).collect()
+    }
+
+    /// Part two's reading, the hex colour.
+    pub fn encoded(&self) -> Vec<DigCmd> {
+        self.steps.iter().map(|step| step.encoded).collect()
+    }
+}
+
+/// Corners of the trench, starting and ending at the origin.
+fn vertices(cmds: &[DigCmd]) -> Vec<(i64, i64)> {
+    let mut ret = Vec::with_capacity(cmds.len() + 1);
+    let (mut row, mut col) = (0, 0);
+    ret.push((row, col));
+    for cmd in cmds.iter() {
+        let (dr, dc) = cmd.dir.delta();
+        row += dr * cmd.steps;
+        col += dc * cmd.steps;
+        ret.push((row, col));
+    }
+    ret
+}
+
+/// Stretches of trench dug once each way: for every two steps running
+/// opposite ways along the same line, a square beside where they overlap,
+/// in doubled coordinates so its centre is a lattice point, and how long
+/// the overlap is.
+fn retraced(corners: &[(i64, i64)]) -> Vec<((i64, i64), i64)> {
+    // by the line they're on: `(from, to)` along it
+    let mut lines = HashMap::<(bool, i64), Vec<(i64, i64)>>::new();
+    for p in corners.windows(2) {
+        let ((r0, c0), (r1, c1)) = (p[0], p[1]);
+        if r0 == r1 && c0 != c1 {
+            lines.entry((true, r0)).or_default().push((c0, c1));
+        } else if c0 == c1 && r0 != r1 {
+            lines.entry((false, c0)).or_default().push((r0, r1));
+        }
+    }
+
+    let mut ret = Vec::new();
+    for ((across, at), steps) in lines {
+        for (i, &(a0, a1)) in steps.iter().enumerate() {
+            for &(b0, b1) in &steps[i + 1..] {
+                if (a1 > a0) == (b1 > b0) {
+                    continue;
+                }
+                let start = a0.min(a1).max(b0.min(b1));
+                let len = a0.max(a1).min(b0.max(b1)) - start;
+                if len > 0 {
+                    let square = if across { (2 * at + 1, 2 * start + 1) } else { (2 * start + 1, 2 * at + 1) };
+                    ret.push((square, len));
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// Cubic metres of lava the lagoon holds: trench plus interior.
+///
+/// The centre line of the trench is a lattice polygon whose boundary points
+/// are the trench cells and whose interior points are the cells inside, so
+/// Pick's theorem counts both. Trench that touches itself at a corner, or
+/// runs out and back along a spur into the ground outside, keeps the same
+/// count, since every cell walked twice also adds its half twice. A spur
+/// dug back into the lagoon, or a wall shared by two loops, is inside
+/// already, so its cells come off again.
+///
+/// That needs every cell inside to be gone round once, and no stretch of
+/// trench to be dug more than twice.
+pub fn lagoon_area(cmds: &[DigCmd]) -> i128 {
+    let corners = vertices(cmds);
+    let doubled = Polygon::new(corners.iter().map(|&(r, c)| (2 * r, 2 * c)).collect());
+    let inside = retraced(&corners).into_iter()
+        .filter(|&(square, _)| doubled.contains(square))
+        .map(|(_, len)| i128::from(len))
+        .sum::<i128>();
+    Polygon::new(corners).lattice_points() - inside
+}
+
+/// Digs the trench cell by cell and flood fills the outside; only for small plans.
+pub fn flood_fill_area(cmds: &[DigCmd]) -> usize {
+    let mut cells = vec![(0_i64, 0_i64)];
+    for cmd in cmds.iter() {
+        let (dr, dc) = cmd.dir.delta();
+        let &(row, col) = cells.last().unwrap();
+        cells.extend((1..=cmd.steps).map(|i| (row + dr * i, col + dc * i)));
+    }
+
+    // one cell of margin so the outside is connected
+    let up_most = cells.iter().map(|c| c.0).min().unwrap() - 1;
+    let left_most = cells.iter().map(|c| c.1).min().unwrap() - 1;
+    let height = (cells.iter().map(|c| c.0).max().unwrap() - up_most + 2) as usize;
+    let width = (cells.iter().map(|c| c.1).max().unwrap() - left_most + 2) as usize;
+
+    let mut trench = vec![vec![false; width]; height];
+    for (row, col) in cells {
+        trench[(row - up_most) as usize][(col - left_most) as usize] = true;
+    }
+
+    let mut outside = vec![vec![false; width]; height];
+    outside[0][0] = true;
+    let mut stack = vec![(0_usize, 0_usize)];
+    while let Some((row, col)) = stack.pop() {
+        let neighbours = [
+            (row.wrapping_sub(1), col),
+            (row + 1, col),
+            (row, col.wrapping_sub(1)),
+            (row, col + 1),
+        ];
+        for (r, c) in neighbours {
+            if r < height && c < width && !trench[r][c] && !outside[r][c] {
+                outside[r][c] = true;
+                stack.push((r, c));
+            }
+        }
+    }
+
+    width * height - outside.iter().flatten().filter(|o| **o).count()
+}
+
+
+pub fn part_1(input: &str) -> i128 {
+    let plan: DigPlan = input.parse().unwrap();
+    lagoon_area(&plan.plain())
+}
+
+/// The real instructions, hidden in the colours.
+pub fn part_2(input: &str) -> i128 {
+    let plan: DigPlan = input.parse().unwrap();
+    lagoon_area(&plan.encoded())
 }
 
 #[test]
@@ -55,158 +274,137 @@ L 2 (#015232)
 U 2 (#7a21e3)
     ".trim();
 
-    // let input = include_str!("input");
-
-    let cmds = input.split("\n")
-        .map(|line| {
-            let mut it = line.split(" ");
-            let dir: Dir = it.next().unwrap().parse().unwrap();
-            let steps: i32 = it.next().unwrap().parse().unwrap();
-            let color = it.next().unwrap();
-            let color = &color[2..8];
-
-            let p2_steps = i32::from_str_radix(&color[..5], 16).unwrap();
-            let p2_dir = match &color[5..] {
-                "0" => Dir::Right,
-                "1" => Dir::Down,
-                "2" => Dir::Left,
-                "3" => Dir::Up,
-                _ => unreachable!(),
-            };
-
-            // DigCmd::new(dir, steps)
-            DigCmd::new(p2_dir, p2_steps)
-        })
-        .collect::<Vec<_>>();
-
-    let mut row = 0_i32;
-    let mut col = 0_i32;
-    let mut hash = HashSet::new();
-    hash.insert((row, col));
-
-    let mut left_most = 0;
-    let mut right_most = 0;
-    let mut up_most = 0;
-    let mut down_most = 0;
-
-    cmds.iter().for_each(|cmd| {
-        let mut diff_row = 0;
-        let mut diff_col = 0;
-        match cmd.dir {
-            Dir::Up => diff_row = -1,
-            Dir::Down => diff_row = 1,
-            Dir::Left => diff_col = -1,
-            Dir::Right => diff_col = 1,
-        }
+    let plan: DigPlan = input.parse().unwrap();
+    assert_eq!(plan.steps[0].encoded, DigCmd::new(Dir::Right, 461937));
 
-        for _ in 0..cmd.steps {
-            row += diff_row;
-            col += diff_col;
-            hash.insert((row, col));
+    assert_eq!(flood_fill_area(&plan.plain()), 62);
+    assert_eq!(lagoon_area(&plan.plain()), 62);
+    assert_eq!(lagoon_area(&plan.encoded()), 952408144115);
 
-            left_most = left_most.min(col);
-            right_most = right_most.max(col);
-            up_most = up_most.min(row);
-            down_most = down_most.max(row);
-        }
-    });
-
-    let mut borders = HashSet::new();
+    // two loops touching at a corner, and a spur dug out and back
+    let touching = "
+R 2 (#000000)
+D 2 (#000000)
+R 2 (#000000)
+D 2 (#000000)
+L 2 (#000000)
+U 2 (#000000)
+L 2 (#000000)
+U 2 (#000000)
+    ";
+    let plan: DigPlan = touching.parse().unwrap();
+    assert_eq!(lagoon_area(&plan.plain()), flood_fill_area(&plan.plain()) as i128);
 
-    {
-        let left_most = left_most - 1;
-        let right_most = right_most + 1;
-        let up_most = up_most - 1;
-        let down_most = down_most + 1;
+    let spur = "
+R 3 (#000000)
+R 2 (#000000)
+L 2 (#000000)
+D 3 (#000000)
+L 3 (#000000)
+U 3 (#000000)
+    ";
+    let plan: DigPlan = spur.parse().unwrap();
+    assert_eq!(lagoon_area(&plan.plain()), 18);
+    assert_eq!(flood_fill_area(&plan.plain()), 18);
 
-        for row in up_most..=down_most {
-            borders.insert((row, left_most));
-            borders.insert((row, right_most));
-        }
-
-        for col in left_most..=right_most {
-            borders.insert((up_most, col));
-            borders.insert((down_most, col));
-        }
+    // a spur dug into the lagoon and back, one dug out of it, two loops
+    // meeting at a corner and two sharing a wall, as `(direction, steps)`
+    let plans: [&[(Dir, i64)]; 4] = [
+        &[(Dir::Right, 4), (Dir::Down, 2), (Dir::Left, 2), (Dir::Right, 2), (Dir::Down, 2), (Dir::Left, 4), (Dir::Up, 4)],
+        &[(Dir::Right, 4), (Dir::Right, 2), (Dir::Left, 2), (Dir::Down, 4), (Dir::Left, 4), (Dir::Up, 4)],
+        &[(Dir::Right, 2), (Dir::Down, 2), (Dir::Right, 2), (Dir::Down, 2), (Dir::Left, 2), (Dir::Up, 2), (Dir::Left, 2), (Dir::Up, 2)],
+        &[(Dir::Right, 4), (Dir::Down, 2), (Dir::Left, 2), (Dir::Up, 2), (Dir::Down, 2), (Dir::Left, 2), (Dir::Up, 2)],
+    ];
+    for plan in plans {
+        let cmds = plan.iter().map(|&(dir, steps)| DigCmd::new(dir, steps)).collect::<Vec<_>>();
+        assert_eq!(lagoon_area(&cmds), flood_fill_area(&cmds) as i128, "{:?}", plan);
     }
 
-    {
-        let mut outers = HashSet::new();
+    assert!("R 6 (70c710)".parse::<DigStep>().is_err());
+    assert!("X 6 (#70c710)".parse::<DigStep>().is_err());
+}
 
-        for row in up_most..=down_most {
-            if hash.get(&(row, left_most)).is_none() {
-                borders.insert((row, left_most));
-                outers.insert((row, left_most));
-            }
+#[test]
+fn differential() {
+    use crate::prop::{self, Config, Rng};
 
-            if hash.get(&(row, right_most)).is_none() {
-                borders.insert((row, right_most));
-                outers.insert((row, right_most));
-            }
+    // An x-monotone lagoon: columns of `(width, bottom, top)` side by side,
+    // each overlapping the next so the outline stays simple.
+    type Columns = Vec<(i64, i64, i64)>;
+    let valid = |cols: &Columns| {
+        cols.iter().all(|&(w, b, t)| w >= 1 && t > b)
+            && cols.windows(2).all(|p| p[1].2 > p[0].1 && p[0].2 > p[1].1)
+    };
+    let plan = |(cols, reversed): &(Columns, bool)| {
+        let mut points = vec![(0, cols[0].1), (0, cols[0].2)];
+        let mut x = 0;
+        for (i, &(w, _, t)) in cols.iter().enumerate() {
+            x += w;
+            points.push((x, t));
+            points.push((x, cols.get(i + 1).map_or(cols[i].1, |c| c.2)));
         }
-
-        for col in left_most..=right_most {
-            if hash.get(&(up_most, col)).is_none() {
-                borders.insert((up_most, col));
-                outers.insert((up_most, col));
-            }
-            if hash.get(&(down_most, col)).is_none() {
-                borders.insert((down_most, col));
-                outers.insert((down_most, col));
+        for (i, &(w, b, _)) in cols.iter().enumerate().rev() {
+            x -= w;
+            points.push((x, b));
+            if i > 0 {
+                points.push((x, cols[i - 1].1));
             }
         }
-
-        loop {
-            if outers.is_empty() { break; }
-
-            let mut new_outers = HashSet::new();
-
-            for (row, col) in outers.into_iter() {
-                // up
-                {
-                    let row =row-1;
-                    let pos = (row, col);
-                    if hash.get(&pos).is_none() && borders.get(&pos).is_none() {
-                        borders.insert(pos);
-                        new_outers.insert(pos);
-                    }
-                }
-                // down
-                {
-                    let row = row+1;
-                    let pos = (row, col);
-                    if hash.get(&pos).is_none() && borders.get(&pos).is_none() {
-                        borders.insert(pos);
-                        new_outers.insert(pos);
-                    }
-                }
-                // left
-                {
-                    let col = col - 1;
-                    let pos = (row, col);
-                    if hash.get(&pos).is_none() && borders.get(&pos).is_none() {
-                        borders.insert(pos);
-                        new_outers.insert(pos);
-                    }
-                }
-                // right
-                {
-                    let col = col + 1;
-                    let pos = (row, col);
-                    if hash.get(&pos).is_none() && borders.get(&pos).is_none() {
-                        borders.insert(pos);
-                        new_outers.insert(pos);
-                    }
-                }
-            }
-
-            outers = new_outers;
+        if *reversed {
+            points.reverse();
         }
-    }
+        points.windows(2)
+            .filter(|p| p[0] != p[1])
+            .map(|p| {
+                let ((x0, y0), (x1, y1)) = (p[0], p[1]);
+                let dir = if x1 > x0 { Dir::Right } else if x1 < x0 { Dir::Left } else if y1 > y0 { Dir::Up } else { Dir::Down };
+                DigCmd::new(dir, (x1 - x0).abs() + (y1 - y0).abs())
+            })
+            .collect::<Vec<_>>()
+    };
+    let generate = |rng: &mut Rng| loop {
+        let cols = (0..rng.size(1..=5))
+            .map(|_| {
+                let b = rng.range(0..=4);
+                (rng.range(1..=3), b, rng.range(b + 1..=6))
+            })
+            .collect::<Columns>();
+        if valid(&cols) {
+            return (cols, rng.chance(1, 2));
+        }
+    };
+    let shrink = |(cols, reversed): &(Columns, bool)| {
+        prop::shrink_vec(cols, 1, |&(w, b, t)| {
+            let mut ret = Vec::new();
+            ret.extend(prop::shrink_int(w, 1).into_iter().map(|w| (w, b, t)));
+            ret.extend(prop::shrink_int(b, 0).into_iter().map(|b| (w, b, t)));
+            ret.extend(prop::shrink_int(t, b + 1).into_iter().map(|t| (w, b, t)));
+            ret
+        })
+        .into_iter()
+        .filter(valid)
+        .map(|cols| (cols, *reversed))
+        .collect::<Vec<_>>()
+    };
+    prop::assert_agree(
+        &Config::default(),
+        generate,
+        shrink,
+        |input| flood_fill_area(&plan(input)) as i128,
+        |input| lagoon_area(&plan(input)),
+    );
+}
 
-    let len = borders.len() as i32;
-    let width = right_most + 1 - (left_most - 1) + 1;
-    let height = down_most + 1 - (up_most - 1) + 1;
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let plan: DigPlan = input.parse().unwrap();
 
-    println!("sum: {}", width * height - len);
-}
\ No newline at end of file
+    let sum = lagoon_area(&plan.plain());
+    assert_eq!(sum, flood_fill_area(&plan.plain()) as i128);
+    println!("sum: {}", sum);
+
+    // Part Two
+    let sum = lagoon_area(&plan.encoded());
+    println!("sum: {}", sum);
+}
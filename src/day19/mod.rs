@@ -1,248 +1,460 @@
-use std::{cmp::Ordering, collections::HashMap, str::FromStr, ops::Range};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{check::Assumption, dot::{Graph, ToDot}, input, interval::{Hyperbox, Interval}};
 
 type Label = String;
 
-#[derive(Debug)]
-struct Condition {
-    /// could be one of [x, m, a, s]
-    var: Label,
-    num: i32,
-    /// var.cmp(num) 的期望结果
-    order: Ordering,
-    /// If var.cmp(num) == order, jump to this label.
-    /// This label may be a normal label, or an Accept/Reject
-    jmp_to: Label,
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// `<`
+    Less,
+    /// `>`
+    Greater,
+}
+
+/// Where a rule sends a part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// `A`
+    Accept,
+    /// `R`
+    Reject,
+    Workflow(Label),
+}
+
+impl FromStr for Target {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "A" => Ok(Self::Accept),
+            "R" => Ok(Self::Reject),
+            "" => Err(()),
+            s => Ok(Self::Workflow(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    /// Rating name, `x`, `m`, `a` or `s` in the puzzle.
+    pub var: Label,
+    pub op: Op,
+    pub num: i64,
+    /// If the comparison holds, jump to this target.
+    pub jmp_to: Target,
 }
 
 impl FromStr for Condition {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut it = s.split(":");
-        let cond = it.next().unwrap();
-        let jmp_to = it.next().unwrap().to_string();
-
-        let var = cond[0..1].to_string();
-        let order = match &cond[1..2] {
-            ">" => Ordering::Greater,
-            "<" => Ordering::Less,
-            // "=" => Ordering::Equal,
-            _ => unreachable!(),
-        };
-        let num = cond[2..].parse::<i32>().unwrap();
-        Ok(Self { var, num, order, jmp_to })
+        let (cond, jmp_to) = s.split_once(':').ok_or(())?;
+        let i = cond.find(['<', '>']).ok_or(())?;
+        let var = cond[..i].to_string();
+        let op = if cond[i..].starts_with('<') { Op::Less } else { Op::Greater };
+        let num = cond[i+1..].parse().map_err(|_| ())?;
+        if var.is_empty() {
+            return Err(());
+        }
+        Ok(Self { var, op, num, jmp_to: jmp_to.parse()? })
     }
 }
 
-#[derive(Debug)]
-enum Rule {
-    Accept,
-    Reject,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
     Condition(Condition),
     /// 无条件跳转
-    Jmp(Label),
+    Jmp(Target),
 }
 
 impl FromStr for Rule {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.contains(":") {
-            Ok(Self::Condition(s.parse::<Condition>().unwrap()))
+        if s.contains(':') {
+            Ok(Self::Condition(s.parse()?))
         } else {
-            match s {
-                "A" => Ok(Self::Accept),
-                "R" => Ok(Self::Reject),
-                s => Ok(Self::Jmp(s.into())),
-            }
+            Ok(Self::Jmp(s.parse()?))
         }
     }
 }
 
-#[derive(Debug)]
-struct Workflow {
-    rules: Vec<Rule>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workflow {
+    pub label: Label,
+    pub rules: Vec<Rule>,
 }
 
-type Rating = HashMap<Label, i32>;
-
-fn calc(workflows: &HashMap<Label, Workflow>, ratings: Vec<Rating>) -> i32 {
-    ratings.into_iter()
-        .map(|rating| {
-            let mut work_label = "in";
-
-            loop {
-                let flow = workflows.get(work_label).unwrap();
-                for rule in flow.rules.iter() {
-                    match rule {
-                        Rule::Accept => {
-                            return rating.values().sum();
-                        },
-                        Rule::Reject => return 0,
-                        Rule::Condition(c) => {
-                            if rating.get(&c.var).unwrap().cmp(&c.num) == c.order {
-                                let to = &c.jmp_to;
-                                if to == "A" {
-                                    return rating.values().sum();
-                                }
-                                if to == "R" {
-                                    return 0;
-                                }
-                                work_label = to;
-                                break;
-                            }
-                        },
-                        Rule::Jmp(to) => {
-                            work_label = to;
-                            break;
-                        },
-                    }
-                }
-            }
-            0
-        })
-        .sum::<i32>()
+impl FromStr for Workflow {
+    type Err = ();
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (label, rules) = line.trim().split_once('{').ok_or(())?;
+        let rules = rules.strip_suffix('}').ok_or(())?
+            .split(',')
+            .map(|rule| rule.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        if label.is_empty() {
+            return Err(());
+        }
+        Ok(Self { label: label.to_string(), rules })
+    }
 }
 
-fn calc_combinations(workflows: &HashMap<Label, Workflow>) {
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    struct Comb {
-        x: Range<i32>,
-        m: Range<i32>,
-        a: Range<i32>,
-        s: Range<i32>,
+/// A part such as `{x=787,m=2655,a=1222,s=2876}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub ratings: Vec<(Label, i64)>,
+}
+
+impl FromStr for Part {
+    type Err = ();
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or(())?;
+        let ratings = line.split(',')
+            .map(|rating| {
+                let (name, value) = rating.split_once('=').ok_or(())?;
+                // a `u32`, so the totals fit an `i64`
+                let value = value.parse::<u32>().map_err(|_| ())?;
+                Ok((name.to_string(), value.into()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { ratings })
+    }
+}
+
+impl Part {
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.ratings.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
     }
-    impl Comb {
-        fn new() -> Self {
-            Comb {
-                x: 1..4001,
-                m: 1..4001,
-                a: 1..4001,
-                s: 1..4001,
+
+    pub fn total(&self) -> i64 {
+        self.ratings.iter().map(|(_, v)| v).sum()
+    }
+}
+
+/// The whole puzzle input: workflows, a blank line, then parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct System {
+    pub workflows: Vec<Workflow>,
+    pub parts: Vec<Part>,
+}
+
+impl FromStr for System {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let paragraphs = input::paragraphs(s).map_err(|_| ())?;
+        let (workflows, parts) = match paragraphs.as_slice() {
+            [workflows] => (workflows.as_slice(), &[][..]),
+            [workflows, parts] => (workflows.as_slice(), parts.as_slice()),
+            _ => return Err(()),
+        };
+        let workflows = workflows.iter()
+            .map(|line| line.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        let parts = parts.iter()
+            .map(|line| line.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { workflows, parts })
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Accept => write!(f, "A"),
+            Self::Reject => write!(f, "R"),
+            Self::Workflow(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+impl ToDot for System {
+    /// Workflows as boxes with an edge per rule, labelled with its
+    /// condition; the rule order is lost, so the fallback says `else`.
+    fn to_graph(&self) -> Graph {
+        let mut graph = Graph::directed("workflows");
+        graph.set("rankdir", "LR");
+        for w in self.workflows.iter() {
+            graph.node(&w.label).set("shape", "box");
+        }
+        graph.node("A").set("shape", "doublecircle").set("color", "darkgreen");
+        graph.node("R").set("shape", "doublecircle").set("color", "red");
+        for w in self.workflows.iter() {
+            for rule in w.rules.iter() {
+                let (label, target) = match rule {
+                    Rule::Condition(c) => {
+                        let op = if c.op == Op::Less { '<' } else { '>' };
+                        (format!("{}{}{}", c.var, op, c.num), &c.jmp_to)
+                    },
+                    Rule::Jmp(target) => ("else".to_string(), target),
+                };
+                graph.edge(&w.label, &target.to_string()).label(&label);
             }
         }
-        fn get_mut(&mut self, s: &str) -> &mut Range<i32> {
-            match s {
-                "x" => &mut self.x,
-                "m" => &mut self.m,
-                "a" => &mut self.a,
-                "s" => &mut self.s,
-                _ => unreachable!(),
+        graph
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileError {
+    /// No workflow with the entry label.
+    MissingEntry(Label),
+    DuplicateWorkflow(Label),
+    /// A rule jumps to a workflow that doesn't exist.
+    UnknownWorkflow(Label),
+    /// A rule tests a rating outside the given rating names.
+    UnknownRating(Label),
+    /// The last rule of the workflow is conditional, so a part can fall off the end.
+    MissingFallback(Label),
+    /// Workflows jumping back to themselves, the first one repeated at the end.
+    Cycle(Vec<Label>),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::MissingEntry(label) => write!(f, "no entry workflow `{}`", label),
+            CompileError::DuplicateWorkflow(label) => write!(f, "workflow `{}` defined twice", label),
+            CompileError::UnknownWorkflow(label) => write!(f, "jump to unknown workflow `{}`", label),
+            CompileError::UnknownRating(name) => write!(f, "unknown rating `{}`", name),
+            CompileError::MissingFallback(label) => write!(f, "workflow `{}` has no fallback rule", label),
+            CompileError::Cycle(labels) => write!(f, "workflow cycle {}", labels.join(" -> ")),
+        }
+    }
+}
+
+type NodeId = usize;
+
+const ACCEPT: NodeId = 0;
+const REJECT: NodeId = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Accept,
+    Reject,
+    Branch {
+        /// index into `Engine::ratings`
+        var: usize,
+        op: Op,
+        num: i64,
+        then: NodeId,
+        otherwise: NodeId,
+    },
+}
+
+/// Workflows compiled into a decision DAG over rating indices, so nothing is
+/// looked up by label once compiled.
+#[derive(Debug, Clone)]
+pub struct Engine {
+    ratings: Vec<Label>,
+    nodes: Vec<Node>,
+    root: NodeId,
+    unreachable: Vec<Label>,
+}
+
+impl Engine {
+    /// Compiles with the puzzle's entry workflow `in` and ratings `x m a s`.
+    pub fn xmas(workflows: &[Workflow]) -> Result<Self, CompileError> {
+        Self::compile(workflows, "in", &["x", "m", "a", "s"])
+    }
+
+    pub fn compile(workflows: &[Workflow], entry: &str, ratings: &[&str]) -> Result<Self, CompileError> {
+        let mut index = HashMap::new();
+        for (i, w) in workflows.iter().enumerate() {
+            if index.insert(w.label.as_str(), i).is_some() {
+                return Err(CompileError::DuplicateWorkflow(w.label.clone()));
             }
         }
+        let entry = *index.get(entry).ok_or_else(|| CompileError::MissingEntry(entry.to_string()))?;
+
+        let mut this = Self {
+            ratings: ratings.iter().map(|s| s.to_string()).collect(),
+            nodes: vec![Node::Accept, Node::Reject],
+            root: REJECT,
+            unreachable: Vec::new(),
+        };
+
+        // 0: unvisited, 1: on the current path, 2: compiled
+        let mut state = vec![0_u8; workflows.len()];
+        let mut compiled = vec![REJECT; workflows.len()];
+        let mut path = Vec::new();
+        this.root = this.compile_workflow(workflows, &index, entry, &mut state, &mut compiled, &mut path)?;
+
+        this.unreachable = workflows.iter()
+            .zip(state.iter())
+            .filter(|(_, s)| **s == 0)
+            .map(|(w, _)| w.label.clone())
+            .collect();
+        Ok(this)
     }
 
-    let label = "in";
-    let comb = Comb::new();
-    let mut all_comb = Vec::new();
+    fn compile_workflow(
+        &mut self,
+        workflows: &[Workflow],
+        index: &HashMap<&str, usize>,
+        i: usize,
+        state: &mut [u8],
+        compiled: &mut [NodeId],
+        path: &mut Vec<usize>,
+    ) -> Result<NodeId, CompileError> {
+        match state[i] {
+            2 => return Ok(compiled[i]),
+            1 => {
+                let start = path.iter().position(|j| *j == i).unwrap();
+                let mut cycle = path[start..].iter().map(|j| workflows[*j].label.clone()).collect::<Vec<_>>();
+                cycle.push(workflows[i].label.clone());
+                return Err(CompileError::Cycle(cycle));
+            },
+            _ => {},
+        }
+        state[i] = 1;
+        path.push(i);
 
-    fn try_comb(mut comb: Comb, label: &str, all_comb: &mut Vec<Comb>, workflows: &HashMap<Label, Workflow>) {
-        if label == "A" {
-            all_comb.push(comb);
-            return;
+        let w = &workflows[i];
+        let mut targets = Vec::with_capacity(w.rules.len());
+        for rule in w.rules.iter() {
+            let target = match rule {
+                Rule::Condition(c) => &c.jmp_to,
+                Rule::Jmp(t) => t,
+            };
+            let node = match target {
+                Target::Accept => ACCEPT,
+                Target::Reject => REJECT,
+                Target::Workflow(label) => {
+                    let j = *index.get(label.as_str()).ok_or_else(|| CompileError::UnknownWorkflow(label.clone()))?;
+                    self.compile_workflow(workflows, index, j, state, compiled, path)?
+                },
+            };
+            targets.push(node);
+            // nothing after an unconditional jump is ever looked at
+            if matches!(rule, Rule::Jmp(_)) { break; }
         }
-        if label == "R" { return; }
-
-        let flow = workflows.get(label).unwrap();
-        for rule in flow.rules.iter() {
-            match rule {
-                Rule::Accept => {
-                    all_comb.push(comb.clone());
-                    return;
+
+        // fold the rules back to front, every condition falling through to the rest
+        let mut rules = w.rules.iter().zip(targets).rev();
+        let mut node = match rules.next() {
+            Some((Rule::Jmp(_), target)) => target,
+            _ => return Err(CompileError::MissingFallback(w.label.clone())),
+        };
+        for (rule, target) in rules {
+            let Rule::Condition(c) = rule else { unreachable!() };
+            let var = self.ratings.iter()
+                .position(|name| *name == c.var)
+                .ok_or_else(|| CompileError::UnknownRating(c.var.clone()))?;
+            self.nodes.push(Node::Branch { var, op: c.op, num: c.num, then: target, otherwise: node });
+            node = self.nodes.len() - 1;
+        }
+
+        path.pop();
+        state[i] = 2;
+        compiled[i] = node;
+        Ok(node)
+    }
+
+    pub fn ratings(&self) -> &[Label] {
+        &self.ratings
+    }
+
+    /// Workflows the entry can never reach.
+    pub fn unreachable_workflows(&self) -> &[Label] {
+        &self.unreachable
+    }
+
+    /// `None` if the part lacks a rating the decision path tests.
+    pub fn accepts(&self, part: &Part) -> Option<bool> {
+        let mut node = self.root;
+        loop {
+            match self.nodes[node] {
+                Node::Accept => return Some(true),
+                Node::Reject => return Some(false),
+                Node::Branch { var, op, num, then, otherwise } => {
+                    let value = part.get(&self.ratings[var])?;
+                    let hit = match op {
+                        Op::Less => value < num,
+                        Op::Greater => value > num,
+                    };
+                    node = if hit { then } else { otherwise };
                 },
-                Rule::Reject => return,
-                Rule::Condition(c) => {
-                    let to = &c.jmp_to;
-                    let num = c.num;
-                    match c.order {
-                        Ordering::Less => {
-                            let mut comb_1 = comb.clone();
-                            let var = comb_1.get_mut(&c.var);
-                            var.end = num;
-                            if var.len() > 0 {
-                                try_comb(comb_1, to, all_comb, workflows);
-                            }
-
-                            let var = comb.get_mut(&c.var);
-                            var.start = num;
-                            if var.len() != 0 {
-                                continue;
-                            } else {
-                                return;
-                            }
-                        },
-                        Ordering::Greater => {
-                            let mut comb_1 = comb.clone();
-                            let var = comb_1.get_mut(&c.var);
-                            var.start = num + 1;
-                            if var.len() > 0 {
-                                try_comb(comb_1, to, all_comb, workflows)
-                            }
-                            let var = comb.get_mut(&c.var);
-                            var.end = num + 1;
-                            if var.len() > 0 {
-                                continue;
-                            } else {
-                                return;
-                            }
-                        },
-                        _ => unreachable!(),
+            }
+        }
+    }
+
+    /// Disjoint boxes, one side per rating, covering every accepted
+    /// combination inside `bounds`.
+    pub fn accepted_boxes(&self, bounds: &Hyperbox<i64>) -> Vec<Hyperbox<i64>> {
+        assert_eq!(bounds.dims(), self.ratings.len());
+        let mut ret = Vec::new();
+        let mut stack = vec![(self.root, bounds.clone())];
+
+        while let Some((node, hyperbox)) = stack.pop() {
+            match self.nodes[node] {
+                Node::Accept => ret.push(hyperbox),
+                Node::Reject => {},
+                Node::Branch { var, op, num, then, otherwise } => {
+                    // values below `split` go one way, the rest the other
+                    let (split, low, high) = match op {
+                        Op::Less => (num, then, otherwise),
+                        Op::Greater => (num.saturating_add(1), otherwise, then),
+                    };
+                    let (below, above) = hyperbox.split_at(var, split);
+                    if !below.is_empty() {
+                        stack.push((low, below));
+                    }
+                    if !above.is_empty() {
+                        stack.push((high, above));
                     }
-                },
-                Rule::Jmp(to) => {
-                    try_comb(comb.clone(), to, all_comb, workflows);
                 },
             }
         }
+        ret
     }
 
-    try_comb(comb, label, &mut all_comb, workflows);
-
-    let mut sum = 0;
-    for comb in all_comb {
-        println!("comb: {:?}", comb);
-        sum += comb.x.len() * comb.m.len() * comb.a.len() * comb.s.len();
+    /// How many distinct rating combinations inside `bounds` get accepted.
+    pub fn accepted_volume(&self, bounds: &Hyperbox<i64>) -> u128 {
+        self.accepted_boxes(bounds)
+            .iter()
+            .map(|b| b.volume())
+            .sum()
     }
-    println!("combinations: {}", sum);
 }
 
+/// Both parts run the workflows compiled from `in` over `x`, `m`, `a` and
+/// `s`, and part one needs all four ratings on every part.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let Ok(system) = input.parse::<System>() else {
+        return Assumption::unparsable("expected `label{rule,...}` workflows, a blank line, then `{x=1,...}` parts");
+    };
+    let engine = Engine::xmas(&system.workflows);
+    let unrated = engine.as_ref().ok()
+        .and_then(|engine| system.parts.iter().position(|part| engine.accepts(part).is_none()));
+    vec![
+        Assumption::new("the workflows compile", engine.as_ref().map(|_| ()).map_err(|e| e.to_string())),
+        Assumption::check("every part has the ratings the workflows test", unrated.is_none(), || {
+            format!("part {} doesn't", unrated.unwrap() + 1)
+        }),
+    ]
+}
 
-fn parse(input: &str) {
-    let mut it = input.split("\n\n");
-    let workflows = it.next().unwrap();
-    let ratings = it.next().unwrap();
-
-    let workflows = workflows.split("\n")
-        .map(|line| {
-            let mut it = line.split("{");
-            let label = it.next().unwrap().to_string();
-            let rules = it.next().unwrap().trim_matches('}');
-            let rules = rules.split(",")
-                .map(|rule| {
-                    rule.parse::<Rule>().unwrap()
-                })
-                .collect::<Vec<_>>();
-            let workflow = Workflow { rules };
-            (label, workflow)
-        })
-        .collect::<HashMap<_, _>>();
-    let ratings = ratings.split("\n")
-        .map(|line| {
-            let line = &line[1..line.len()-1];
-            line.split(",")
-                .map(|rating| {
-                    let mut it = rating.split("=");
-                    (it.next().unwrap().to_string(), it.next().unwrap().parse::<i32>().unwrap())
-                })
-                .collect::<Rating>()
-        })
-        .collect::<Vec<_>>();
-
-    let sum = calc(&workflows, ratings);
-    println!("sum: {:?}", sum);
+pub fn part_1(input: &str) -> i64 {
+    let system: System = input.parse().unwrap();
+    let engine = Engine::xmas(&system.workflows).unwrap();
+    system.parts.iter()
+        .filter(|part| engine.accepts(part).unwrap())
+        .map(|part| part.total())
+        .sum()
+}
 
-    // Part Two
-    calc_combinations(&workflows);
+pub fn part_2(input: &str) -> u128 {
+    let system: System = input.parse().unwrap();
+    let engine = Engine::xmas(&system.workflows).unwrap();
+    engine.accepted_volume(&Hyperbox::new(vec![Interval::new(1, 4001); 4]))
 }
 
 #[test]
@@ -268,7 +480,56 @@ hdj{m>838:A,pv}
 ".trim()
     };
 
+    assert_eq!(part_1(input), 19114);
+    assert_eq!(part_2(input), 167409079868000);
+    assert_eq!(part_1(&input.replace('\n', "\r\n")), 19114);
+    assert_eq!("a{x>1:A,R}\n\n\n{x=2}".parse::<System>(), Err(()));
+
+    let system: System = input.parse().unwrap();
+    let engine = Engine::xmas(&system.workflows).unwrap();
+    assert!(engine.unreachable_workflows().is_empty());
+
+    let dot = system.to_dot();
+    assert!(dot.contains("\"in\" -> \"px\" [label=\"s<1351\"];"));
+    assert!(dot.contains("\"crn\" -> \"R\" [label=else];"));
+    let boxes = engine.accepted_boxes(&Hyperbox::new(vec![Interval::new(1, 4001); 4]));
+    assert_eq!(boxes.iter().map(|b| b.volume()).sum::<u128>(), 167409079868000);
+    assert!(boxes.iter().enumerate().all(|(i, a)| boxes[i+1..].iter().all(|b| !a.overlaps(b))));
+
+    // arbitrary rating names and bounds
+    let system: System = "
+in{speed>5:fast,R}
+fast{weight<3:A,lost}
+lost{speed<0:A,R}
+gone{speed<0:A,R}
+    ".parse().unwrap();
+    let engine = Engine::compile(&system.workflows, "in", &["speed", "weight"]).unwrap();
+    assert_eq!(engine.unreachable_workflows(), ["gone".to_string()]);
+    assert_eq!(engine.accepted_volume(&Hyperbox::new(vec![Interval::new(0, 10); 2])), 4 * 3);
+    assert_eq!(engine.accepts(&"{speed=9,weight=1}".parse().unwrap()), Some(true));
+    assert_eq!(engine.accepts(&"{speed=1}".parse().unwrap()), Some(false));
+    assert_eq!(engine.accepts(&"{speed=9}".parse().unwrap()), None);
+
+    let system: System = "
+in{x>5:a,R}
+a{m<3:b,A}
+b{a>1:in,R}
+    ".parse().unwrap();
+    let err = Engine::xmas(&system.workflows).unwrap_err();
+    assert_eq!(err, CompileError::Cycle(vec!["in".into(), "a".into(), "b".into(), "in".into()]));
+
+    let system: System = "in{x>5:nowhere,R}".parse().unwrap();
+    assert_eq!(Engine::xmas(&system.workflows).unwrap_err(), CompileError::UnknownWorkflow("nowhere".into()));
+}
+
+#[test]
+fn solve() {
     let input = include_str!("input");
 
-    parse(input);
+    let sum = part_1(input);
+    println!("sum: {:?}", sum);
+
+    // Part Two
+    let combinations = part_2(input);
+    println!("combinations: {}", combinations);
 }
@@ -1,180 +1,410 @@
-use std::{collections::{HashMap, VecDeque, hash_map::DefaultHasher}, default, hash::{Hash, Hasher}};
+use std::{collections::{HashMap, VecDeque}, str::FromStr};
 
+use crate::{check::Assumption, dot::{Graph, ToDot}, input, math};
 
-trait OnRecvPulse {
-    fn on_recv(&mut self, pulse: Pulse, from: &str) -> Option<Pulse>;
-}
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-enum Pulse {
+pub enum Pulse {
     #[default]
     Low,
     High,
 }
 
-/// prefix %
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum FlipFlopState {
-    #[default]
-    Off,
-    On,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// named broadcaster
+    /// When it receives a pulse, it sends the same pulse to all of its destination modules.
+    Broadcast,
+    /// prefix %, on or off
+    FlipFlop(bool),
+    /// prefix &, the last pulse from each input, in `inputs` order
+    Conjunction(Vec<Pulse>),
+    /// only ever named as a destination, like `rx`
+    Untyped,
 }
 
-#[derive(Debug, Default)]
-struct Mail {
-    pulse: Pulse,
-    from: String,
-    to: String,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub name: String,
+    pub kind: Kind,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
 }
 
-impl Mail {
-    fn new(pulse: Pulse, from: String, to: String) -> Self {
-        Self { pulse, from, to }
+impl Module {
+    fn on_recv(&mut self, pulse: Pulse, from: usize) -> Option<Pulse> {
+        match &mut self.kind {
+            Kind::Broadcast => Some(pulse),
+            Kind::FlipFlop(on) => {
+                if pulse == Pulse::High { return None; }
+                *on = !*on;
+                Some(if *on { Pulse::High } else { Pulse::Low })
+            },
+            Kind::Conjunction(memory) => {
+                let i = self.inputs.iter().position(|x| *x == from).unwrap();
+                memory[i] = pulse;
+                if memory.iter().all(|p| *p == Pulse::High) {
+                    Some(Pulse::Low)
+                } else {
+                    Some(Pulse::High)
+                }
+            },
+            Kind::Untyped => None,
+        }
     }
 }
 
-#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct FlipFlop {
-    inputs: Vec<String>,
-    outputs: Vec<String>,
-    state: FlipFlopState,
+/// One pulse on its way from a module to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signal {
+    pub from: usize,
+    pub to: usize,
+    pub pulse: Pulse,
+}
+
+/// Modules upstream of one input of the conjunction feeding a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subgraph {
+    /// The module whose output reaches the conjunction.
+    pub output: String,
+    pub modules: Vec<String>,
+}
+
+/// When one feeder first sends a high pulse, and how often it repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub first: u64,
+    pub period: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    modules: Vec<Module>,
+    index: HashMap<String, usize>,
+    presses: u64,
+    /// Pulses sent by every module as `(press, pulse)`, if recording.
+    history: Option<Vec<Vec<(u64, Pulse)>>>,
 }
 
-impl OnRecvPulse for FlipFlop {
-    fn on_recv(&mut self, pulse: Pulse, _from: &str) -> Option<Pulse> {
-        let state = &mut self.state;
-        match pulse {
-            Pulse::Low => {
-                match state {
-                    FlipFlopState::Off => {
-                        *state = FlipFlopState::On;
-                        Some(Pulse::High)
-                    },
-                    FlipFlopState::On => {
-                        *state = FlipFlopState::Off;
-                        Some(Pulse::Low)
-                    },
+impl FromStr for Circuit {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut this = Self {
+            modules: Vec::new(),
+            index: HashMap::new(),
+            presses: 0,
+            history: None,
+        };
+
+        let mut edges = Vec::new();
+        for line in input::lines(s).map_err(|_| ())? {
+            let (label, outputs) = line.split_once("->").ok_or(())?;
+            let label = label.trim();
+            let (name, kind) = if let Some(name) = label.strip_prefix('%') {
+                (name, Kind::FlipFlop(false))
+            } else if let Some(name) = label.strip_prefix('&') {
+                (name, Kind::Conjunction(Vec::new()))
+            } else if label == "broadcaster" {
+                (label, Kind::Broadcast)
+            } else {
+                return Err(());
+            };
+            // the button only ever talks to a broadcaster
+            if name.is_empty() || (name == "broadcaster" && kind != Kind::Broadcast) {
+                return Err(());
+            }
+
+            let id = this.intern(name);
+            if this.modules[id].kind != Kind::Untyped {
+                return Err(());
+            }
+            this.modules[id].kind = kind;
+            for out in outputs.split(',').map(|o| o.trim()) {
+                if out.is_empty() {
+                    return Err(());
                 }
-            },
-            Pulse::High => None,
+                let to = this.intern(out);
+                edges.push((id, to));
+            }
         }
+
+        for (from, to) in edges {
+            this.modules[from].outputs.push(to);
+            this.modules[to].inputs.push(from);
+        }
+        for m in this.modules.iter_mut() {
+            if let Kind::Conjunction(memory) = &mut m.kind {
+                *memory = vec![Pulse::Low; m.inputs.len()];
+            }
+        }
+        Ok(this)
     }
 }
 
-/// prefix &
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
-struct Conjunction {
-    /// default: low pulse
-    inputs: HashMap<String, Pulse>,
-    outputs: Vec<String>,
-}
+impl Circuit {
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.index.get(name) {
+            return *id;
+        }
+        let id = self.modules.len();
+        self.modules.push(Module {
+            name: name.to_string(),
+            kind: Kind::Untyped,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        });
+        self.index.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.modules[id].name
+    }
 
-impl OnRecvPulse for Conjunction {
-    fn on_recv(&mut self, pulse: Pulse, from: &str) -> Option<Pulse> {
-        *self.inputs.get_mut(from).unwrap() = pulse;
-        if self.inputs.iter().all(|(_, p)| *p == Pulse::High) {
-            Some(Pulse::Low)
-        } else {
-            Some(Pulse::High)
+    /// Button presses so far.
+    pub fn presses(&self) -> u64 {
+        self.presses
+    }
+
+    /// Start recording every module's outgoing pulses.
+    pub fn record_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(vec![Vec::new(); self.modules.len()]);
         }
     }
-}
 
+    /// `(press, pulse)` for every pulse `name` sent since recording started.
+    pub fn history(&self, name: &str) -> Option<&[(u64, Pulse)]> {
+        let id = self.id(name)?;
+        self.history.as_ref().map(|h| h[id].as_slice())
+    }
 
-// impl Hash for Conjunction {
-//     fn hash<H: Hasher>(&self, state: &mut H) {
-//         let mut v = self.inputs.iter().collect::<Vec<_>>();
-//         v.sort_by_key(|x|x.0);
-//         v.hash(state);
-//         self.outputs.hash(state);
-//     }
-// }
-
-/// named broadcaster
-/// When it receives a pulse, it sends the same pulse to all of its destination modules.
-/// When you push the button, a single low pulse is sent directly to the broadcaster module.
-#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct Broadcast {
-    inputs: Vec<String>,
-    outputs: Vec<String>,
-}
+    /// Flip-flops off and conjunctions remembering low, as after parsing.
+    pub fn reset(&mut self) {
+        for m in self.modules.iter_mut() {
+            match &mut m.kind {
+                Kind::FlipFlop(on) => *on = false,
+                Kind::Conjunction(memory) => memory.iter_mut().for_each(|p| *p = Pulse::Low),
+                _ => {},
+            }
+        }
+        self.presses = 0;
+        if let Some(h) = &mut self.history {
+            h.iter_mut().for_each(|v| v.clear());
+        }
+    }
 
-impl OnRecvPulse for Broadcast {
-    fn on_recv(&mut self, pulse: Pulse, from: &str) -> Option<Pulse> {
-        Some(pulse)
+    /// Pushes the button once, handing every delivered signal to `on_signal`
+    /// in order; the button's own pulse to the broadcaster comes first.
+    pub fn press_with<F: FnMut(Signal)>(&mut self, mut on_signal: F) {
+        self.presses += 1;
+        let Some(broadcaster) = self.id("broadcaster") else { return; };
+
+        // `usize::MAX` stands for the button
+        let mut queue = VecDeque::new();
+        queue.push_back(Signal { from: usize::MAX, to: broadcaster, pulse: Pulse::Low });
+
+        while let Some(signal) = queue.pop_front() {
+            on_signal(signal);
+            let module = &mut self.modules[signal.to];
+            let Some(pulse) = module.on_recv(signal.pulse, signal.from) else { continue; };
+            if let Some(h) = &mut self.history {
+                h[signal.to].push((self.presses, pulse));
+            }
+            for &to in module.outputs.iter() {
+                queue.push_back(Signal { from: signal.to, to, pulse });
+            }
+        }
     }
-}
 
-#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct UntypedModule {
-    inputs: Vec<String>,
-    outputs: Vec<String>,
-}
+    /// Pushes the button once, returning `(low, high)` pulses sent.
+    pub fn press(&mut self) -> (usize, usize) {
+        let (mut low, mut high) = (0, 0);
+        self.press_with(|signal| match signal.pulse {
+            Pulse::Low => low += 1,
+            Pulse::High => high += 1,
+        });
+        (low, high)
+    }
 
-impl OnRecvPulse for UntypedModule {
-    fn on_recv(&mut self, pulse: Pulse, from: &str) -> Option<Pulse> {
-        None
+    /// Modules sending directly to `target`.
+    fn feeders(&self, target: usize) -> &[usize] {
+        &self.modules[target].inputs
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Module {
-    UntypedModule(UntypedModule),
-    Broadcast(Broadcast),
-    FlipFlop(FlipFlop),
-    Conjunction(Conjunction),
-}
+    /// The conjunction that alone feeds `target`, and the subgraph behind
+    /// each of its inputs.
+    pub fn feeding_subgraphs(&self, target: &str) -> Option<(String, Vec<Subgraph>)> {
+        let target = self.id(target)?;
+        let &[conjunction] = self.feeders(target) else { return None; };
+        if !matches!(self.modules[conjunction].kind, Kind::Conjunction(_)) {
+            return None;
+        }
 
-impl Module {
-    fn get_outputs(&self) -> &Vec<String> {
-        match self {
-            Module::UntypedModule(m) => &m.outputs,
-            Module::Broadcast(m) => &m.outputs,
-            Module::FlipFlop(m) => &m.outputs,
-            Module::Conjunction(m) => &m.outputs,
+        let subgraphs = self.feeders(conjunction)
+            .iter()
+            .map(|&output| {
+                let mut seen = vec![false; self.modules.len()];
+                seen[output] = true;
+                let mut stack = vec![output];
+                while let Some(id) = stack.pop() {
+                    for &from in self.feeders(id) {
+                        if !seen[from] && self.modules[from].kind != Kind::Broadcast {
+                            seen[from] = true;
+                            stack.push(from);
+                        }
+                    }
+                }
+                let modules = (0..self.modules.len())
+                    .filter(|id| seen[*id])
+                    .map(|id| self.modules[id].name.clone())
+                    .collect();
+                Subgraph { output: self.modules[output].name.clone(), modules }
+            })
+            .collect();
+
+        Some((self.modules[conjunction].name.clone(), subgraphs))
+    }
+
+    /// Presses from a fresh state until every input of the conjunction
+    /// feeding `target` has sent it high twice, giving up after `limit` presses.
+    pub fn feeder_periods(&mut self, target: &str, limit: u64) -> Option<Vec<(String, Period)>> {
+        let (conjunction, _) = self.feeding_subgraphs(target)?;
+        let conjunction = self.id(&conjunction).unwrap();
+        let inputs = self.feeders(conjunction).to_vec();
+
+        self.reset();
+        let mut hits = vec![Vec::<u64>::new(); inputs.len()];
+        while self.presses < limit && hits.iter().any(|v| v.len() < 2) {
+            let press = self.presses + 1;
+            self.press_with(|signal| {
+                if signal.to == conjunction && signal.pulse == Pulse::High {
+                    let i = inputs.iter().position(|x| *x == signal.from).unwrap();
+                    if hits[i].last() != Some(&press) {
+                        hits[i].push(press);
+                    }
+                }
+            });
+        }
+        if hits.iter().any(|v| v.len() < 2) {
+            return None;
         }
+
+        Some(inputs.iter()
+            .zip(hits)
+            .map(|(id, v)| (self.modules[*id].name.clone(), Period { first: v[0], period: v[1] - v[0] }))
+            .collect())
     }
-    fn get_outputs_mut(&mut self) -> &mut Vec<String> {
-        match self {
-            Module::UntypedModule(m) => &mut m.outputs,
-            Module::Broadcast(m) => &mut m.outputs,
-            Module::FlipFlop(m) => &mut m.outputs,
-            Module::Conjunction(m) => &mut m.outputs,
+
+    /// The first press on which `target` receives a low pulse.
+    ///
+    /// Assumes the puzzle's shape: `target` fed by a single conjunction whose
+    /// inputs each go high periodically, all within the same press.
+    pub fn first_low_press(&mut self, target: &str) -> Option<u64> {
+        let periods = self.feeder_periods(target, 1 << 20)?;
+        let min = periods.iter().map(|(_, p)| p.first).max()?;
+
+        let (rem, modulus) = math::crt_all(
+            periods.iter().map(|(_, p)| ((p.first % p.period) as i128, p.period as i128))
+        )?;
+
+        // smallest solution no earlier than every feeder's first high
+        let mut n = rem;
+        if n < min as i128 {
+            n += (min as i128 - n + modulus - 1) / modulus * modulus;
         }
+        u64::try_from(n).ok()
     }
 }
 
-impl OnRecvPulse for Module {
-    fn on_recv(&mut self, pulse: Pulse, from: &str) -> Option<Pulse> {
-        match self {
-            Module::UntypedModule(m) => m.on_recv(pulse, from),
-            Module::Broadcast(m) => m.on_recv(pulse, from),
-            Module::FlipFlop(m) => m.on_recv(pulse, from),
-            Module::Conjunction(m) => m.on_recv(pulse, from),
+/// Modules by kind, `%` as boxes and `&` as diamonds.
+impl ToDot for Circuit {
+    fn to_graph(&self) -> Graph {
+        let mut graph = Graph::directed("circuit");
+        for m in self.modules.iter() {
+            let (label, shape) = match m.kind {
+                Kind::Broadcast => (m.name.clone(), "doublecircle"),
+                Kind::FlipFlop(_) => (format!("%{}", m.name), "box"),
+                Kind::Conjunction(_) => (format!("&{}", m.name), "diamond"),
+                Kind::Untyped => (m.name.clone(), "ellipse"),
+            };
+            graph.node(&m.name).label(&label).set("shape", shape);
+        }
+        for m in self.modules.iter() {
+            for &to in m.outputs.iter() {
+                graph.edge(&m.name, &self.modules[to].name);
+            }
         }
+        graph
     }
 }
 
-#[derive(Debug)]
-struct MailBuilder {
-    count_low: usize,
-    count_high: usize,
-}
-impl MailBuilder {
-    fn new() -> Self {
-        Self { count_low: 0, count_high: 0 }
+pub fn part_1(input: &str) -> usize {
+    let mut circuit: Circuit = input.parse().unwrap();
+    let (mut low, mut high) = (0, 0);
+    for _ in 0..1000 {
+        let (l, h) = circuit.press();
+        low += l;
+        high += h;
     }
-    fn build(&mut self, pulse: Pulse, from: String, to: String) -> Mail {
-        match pulse {
-            Pulse::Low => self.count_low += 1,
-            Pulse::High => self.count_high += 1,
-        }
-        // println!("{from}  -{:?}->  {to}", pulse);
-        if to == "rx" && pulse == Pulse::Low {
-            println!("low pulse delivered");
+    low * high
+}
+
+/// Part two never simulates as far as `rx` going low; it times the
+/// counters behind the one conjunction feeding it and lines them up.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let Ok(mut circuit) = input.parse::<Circuit>() else {
+        return Assumption::unparsable("expected `%name -> a, b`, `&name -> ...` and `broadcaster -> ...` lines");
+    };
+    let broadcaster = circuit.modules.iter().any(|m| m.kind == Kind::Broadcast);
+    let feeding = circuit.feeding_subgraphs("rx");
+    let disjoint = feeding.as_ref().map_or(Ok(()), |(_, subgraphs)| {
+        for (i, a) in subgraphs.iter().enumerate() {
+            for b in subgraphs[i + 1..].iter() {
+                if let Some(m) = a.modules.iter().find(|m| b.modules.contains(m)) {
+                    return Err(format!("{} is behind both {} and {}", m, a.output, b.output));
+                }
+            }
         }
-        Mail::new(pulse, from, to)
-    }
+        Ok(())
+    });
+    let periodic = match &feeding {
+        Some((conjunction, _)) => match circuit.feeder_periods("rx", 1 << 20) {
+            None => Err(format!("some input of {} doesn't go high twice in {} presses", conjunction, 1 << 20)),
+            Some(periods) if periods.is_empty() => Err(format!("nothing feeds {}", conjunction)),
+            Some(_) => Ok(()),
+        },
+        None => Ok(()),
+    };
+    // only worth asking once the periods are known
+    let lines_up = feeding.is_none() || periodic.is_err() || circuit.first_low_press("rx").is_some();
+
+    vec![
+        Assumption::check("there is a broadcaster", broadcaster, || "nothing to press".to_string()),
+        Assumption::check("rx is fed by a single conjunction", feeding.is_some(), || {
+            match circuit.id("rx") {
+                None => "there's no rx".to_string(),
+                Some(rx) => format!("rx is fed by {:?}", circuit.feeders(rx).iter().map(|id| circuit.name(*id)).collect::<Vec<_>>()),
+            }
+        }),
+        Assumption::new("the counters behind it share no modules", disjoint),
+        Assumption::new("each of them sends it high on a fixed period", periodic),
+        Assumption::check("the periods line up", lines_up, || "they're never all high on the same press".to_string()),
+    ]
+}
+
+pub fn part_2(input: &str) -> u64 {
+    let mut circuit: Circuit = input.parse().unwrap();
+    circuit.first_low_press("rx").unwrap()
 }
 
 #[test]
@@ -187,6 +417,7 @@ broadcaster -> a, b, c
 &inv -> a"
 .trim()
     };
+    assert_eq!(part_1(input), 32000000);
 
     let input = {"
 broadcaster -> a
@@ -196,112 +427,64 @@ broadcaster -> a
 &con -> output"
 .trim()
     };
-
-    let input = include_str!("input");
-
-    let mut map = HashMap::new();
-
-    let mut inputs = HashMap::new();
-
-    input.split("\n")
-        .for_each(|line| {
-            let mut it = line.split(" -> ");
-            let label = it.next().unwrap().trim();
-            let outputs = it.next().unwrap().trim();
-
-            let (name, module) = if label == "broadcaster" {
-                (label, Module::Broadcast(Default::default()))
-            } else {
-                match &label[0..1] {
-                    "%" => {
-                        (&label[1..], Module::FlipFlop(Default::default()))
-                    },
-                    "&" => {
-                        (&label[1..], Module::Conjunction(Default::default()))
-                    },
-                    s => {
-                        (s, Module::UntypedModule(Default::default()))
-                    },
-                }
-            };
-
-            if map.get(name).is_none() {
-                map.insert(name, module);
-            }
-
-            let outs = map.get_mut(name).unwrap().get_outputs_mut();
-            outputs.split(",")
-                .for_each(|o| {
-                    let o = o.trim();
-                    outs.push(o.into());
-
-                    if inputs.get(o).is_none() {
-                        inputs.insert(o, Vec::new());
-                    }
-                    inputs.get_mut(o).unwrap().push(name);
-                });
-        });
-
-    // Init the inputs for conjunction modules
-    for (k, v) in inputs.into_iter() {
-        // some modules don't have outputs
-        if map.get(k).is_none() {continue;}
-
-        let m = map.get_mut(k).unwrap();
-        if let Module::Conjunction(con) = m {
-            con.inputs = v.into_iter()
-                .map(|s| (s.to_string(), Pulse::Low))
-                .collect();
-        }
+    assert_eq!(part_1(input), 11687500);
+
+    assert!("&broadcaster -> a\n%a -> broadcaster".parse::<Circuit>().is_err());
+    assert!("%broadcaster -> a".parse::<Circuit>().is_err());
+
+    let mut circuit: Circuit = input.parse().unwrap();
+    circuit.record_history();
+    circuit.press();
+    circuit.press();
+    assert_eq!(circuit.history("a"), Some(&[(1, Pulse::High), (2, Pulse::Low)][..]));
+    assert_eq!(circuit.history("output"), Some(&[][..]));
+
+    let dot = circuit.to_dot();
+    assert!(dot.contains("\"inv\" [label=\"&inv\", shape=diamond];"));
+    assert!(dot.contains("\"a\" -> \"con\";"));
+
+    // counters of period 3 and 5 gating `rx`, wired like the real input
+    let input = "
+broadcaster -> a1, b1
+%a1 -> a2, ca
+%a2 -> ca
+&ca -> a1, ia
+&ia -> gate
+%b1 -> b2, cb
+%b2 -> b3
+%b3 -> cb
+&cb -> b1, b2, ib
+&ib -> gate
+&gate -> rx
+    ";
+    let mut circuit: Circuit = input.parse().unwrap();
+    let (conjunction, subgraphs) = circuit.feeding_subgraphs("rx").unwrap();
+    assert_eq!(conjunction, "gate");
+    assert_eq!(subgraphs.len(), 2);
+    assert_eq!(subgraphs[0].output, "ia");
+    assert_eq!(subgraphs[0].modules, ["a1", "a2", "ca", "ia"]);
+
+    let first = circuit.first_low_press("rx").unwrap();
+    assert_eq!(first, 15);
+
+    let mut brute: Circuit = input.parse().unwrap();
+    let rx = brute.id("rx").unwrap();
+    let mut hit = None;
+    while hit.is_none() {
+        let press = brute.presses() + 1;
+        brute.press_with(|s| if s.to == rx && s.pulse == Pulse::Low { hit = Some(press); });
     }
+    assert_eq!(Some(first), hit);
+}
 
-    println!("inputs: {:#?}", map);
-
-    // let mut init_vec = map.iter().map(|(k,m)| (k.to_owned(), m.clone())).collect::<Vec<_>>();
-    // init_vec.sort_by_key(|x|x.0);
-
-    let mut builder = MailBuilder::new();
-
-    'outer: for i in 1.. {
-        // println!("i: {}", i);
-        let pulse = Pulse::Low;
-        let mail = builder.build(pulse, "button".into(), "broadcaster".into());
-
-        let mut mail_queue = VecDeque::new();
-        mail_queue.push_back(mail);
-
-        loop {
-            if mail_queue.is_empty() {break;}
-            let mail = mail_queue.pop_front().unwrap();
-            if map.get(mail.to.as_str()).is_none() { continue; }
-
-            let module = map.get_mut(mail.to.as_str()).unwrap();
-            if let Some(pulse) = module.on_recv(mail.pulse, mail.from.as_str()) {
-                for out in module.get_outputs() {
-                    let new_mail = builder.build(pulse, mail.to.clone(), out.clone());
-                    mail_queue.push_back(new_mail);
-
-                    if pulse == Pulse::Low {
-                        match mail.to.as_str() {
-                            "mh" => println!("mh: {}", i), // 4051, 8102
-                            "jt" => println!("jt: {}", i), // 3919, 7838
-                            "pz" => println!("pz: {}", i), // 3761, 7522
-                            "rn" => println!("rn: {}", i), // 3907, 7814
-                            _ => (),
-                        }
-                        if out == "rx" {
-                            break 'outer;
-                        }
-                    }
-                }
-            }
-        }
-
-        // println!("builder: {:?}", builder);
-        // println!("cur map: {:#?}", map);
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    println!("product: {}", part_1(input));
 
-        // let mut v = map.iter().map(|(k,m)| (k.to_owned(), m.clone())).collect::<Vec<_>>();
-        // v.sort_by_key(|x|x.0);
-        // if v == init_vec { break; }
-    }
+    // Part Two
+    let mut circuit: Circuit = input.parse().unwrap();
+    let periods = circuit.feeder_periods("rx", 1 << 20).unwrap();
+    println!("periods: {:?}", periods);
+    println!("presses: {:?}", circuit.first_low_press("rx"));
 }
@@ -1,7 +1,284 @@
-use std::collections::HashSet;
+use std::{collections::{HashSet, VecDeque}, str::FromStr};
 
+use crate::{check::Assumption, input, math};
 
 
+/// One tile of the garden; part two repeats it forever in every direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Garden {
+    rocks: Vec<Vec<bool>>,
+    start: (usize, usize),
+}
+
+impl FromStr for Garden {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut start = None;
+        let mut rocks = Vec::new();
+        for (row, line) in input::grid(s).map_err(|_| ())?.into_iter().enumerate() {
+            let v = line.chars()
+                .enumerate()
+                .map(|(col, ch)| match ch {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    'S' if start.is_none() => {
+                        start = Some((row, col));
+                        Ok(false)
+                    },
+                    _ => Err(()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            rocks.push(v);
+        }
+        Ok(Self { rocks, start: start.ok_or(())? })
+    }
+}
+
+impl Garden {
+    pub fn height(&self) -> usize {
+        self.rocks.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.rocks[0].len()
+    }
+
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    fn is_rock(&self, row: i64, col: i64) -> bool {
+        let (h, w) = (self.height() as i64, self.width() as i64);
+        self.rocks[row.rem_euclid(h) as usize][col.rem_euclid(w) as usize]
+    }
+
+    /// Steps from the start to every plot of the single tile, `None` for
+    /// rocks and plots walled off.
+    pub fn tile_distances(&self) -> Vec<Vec<Option<usize>>> {
+        self.distances(self.height(), self.width(), 0)
+    }
+
+    /// Steps from the start to every plot of a `rows` by `cols` window on
+    /// the infinite map, never leaving it, with the tile the start is in
+    /// `offset` rows and columns from the window's top left.
+    fn distances(&self, rows: usize, cols: usize, offset: usize) -> Vec<Vec<Option<usize>>> {
+        let mut dist = vec![vec![None; cols]; rows];
+        let mut queue = VecDeque::new();
+        let start = (self.start.0 + offset, self.start.1 + offset);
+        dist[start.0][start.1] = Some(0);
+        queue.push_back(start);
+
+        while let Some((row, col)) = queue.pop_front() {
+            let d = dist[row][col].unwrap();
+            let neighbours = [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ];
+            for (r, c) in neighbours {
+                if r < rows && c < cols && dist[r][c].is_none() && !self.is_rock(r as i64 - offset as i64, c as i64 - offset as i64) {
+                    dist[r][c] = Some(d + 1);
+                    queue.push_back((r, c));
+                }
+            }
+        }
+        dist
+    }
+
+    /// Plots of the single tile the elf can stop on after exactly `steps`;
+    /// those are the ones reached in no more steps and of the same parity.
+    pub fn reachable_in_tile(&self, steps: usize) -> usize {
+        self.tile_distances()
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|d| **d <= steps && **d % 2 == steps % 2)
+            .count()
+    }
+
+    /// Step by step on the infinite map, keeping every plot of the frontier.
+    pub fn reachable_brute_force(&self, steps: usize) -> usize {
+        let (row, col) = self.start;
+        let mut plots = HashSet::new();
+        plots.insert((row as i64, col as i64));
+
+        for _ in 0..steps {
+            let mut new_plots = HashSet::with_capacity(plots.len() * 2);
+            for &(row, col) in plots.iter() {
+                for (r, c) in [(row - 1, col), (row + 1, col), (row, col - 1), (row, col + 1)] {
+                    if !self.is_rock(r, c) {
+                        new_plots.insert((r, c));
+                    }
+                }
+            }
+            plots = new_plots;
+        }
+        plots.len()
+    }
+
+    /// Distances from the start to every plot out to a few copies of the
+    /// tile, found once, from which [`Reach::count`] answers for any number
+    /// of steps; `None` if they don't settle within [`MAX_RADIUS`] copies.
+    ///
+    /// The tile repeats every `period = lcm(height, width)` both ways, so
+    /// take that square as the unit. Far enough out, a plot in the next
+    /// square along is `period` steps further than in this one. So once
+    /// the squares on the ring `radius` out are each `period` further than
+    /// their neighbour towards the start, every square past them is the
+    /// one on the ring plus some number of periods. The window searched
+    /// reaches one square beyond the ring, so paths that swing out past it
+    /// still count.
+    pub fn reach(&self) -> Option<Reach> {
+        let period = math::lcm([self.height(), self.width()]).unwrap();
+        (2..=MAX_RADIUS).find_map(|radius| {
+            let extent = radius + 1;
+            let side = (2 * extent + 1) * period;
+            let reach = Reach { period, radius, extent, dist: self.distances(side, side, extent * period) };
+            reach.settled().then_some(reach)
+        })
+    }
+
+    /// Plots reachable in exactly `steps` on the infinitely tiled map, or
+    /// `None` if the distances never settle; see [`Garden::reach`].
+    pub fn reachable(&self, steps: u64) -> Option<u128> {
+        Some(self.reach()?.count(steps))
+    }
+}
+
+/// How many squares out [`Garden::reach`] looks before giving up.
+pub const MAX_RADIUS: usize = 8;
+
+/// The distances [`Garden::reach`] finds.
+#[derive(Debug, Clone)]
+pub struct Reach {
+    /// The side of the square the map repeats in.
+    period: usize,
+    /// The ring of squares the ones further out are taken from.
+    radius: usize,
+    /// How many squares out `dist` goes, one past `radius`.
+    extent: usize,
+    dist: Vec<Vec<Option<usize>>>,
+}
+
+impl Reach {
+    /// The distances across the square `(x, y)` squares from the start's,
+    /// row by row.
+    fn square(&self, x: i64, y: i64) -> impl Iterator<Item = &[Option<usize>]> + '_ {
+        let (p, e) = (self.period, self.extent as i64);
+        let (top, left) = ((y + e) as usize * p, (x + e) as usize * p);
+        self.dist[top..top + p].iter().map(move |row| &row[left..left + p])
+    }
+
+    /// Whether every square on the ring is `period` further than the one
+    /// in from it, along each way it's on the edge of the ring.
+    fn settled(&self) -> bool {
+        let k = self.radius as i64;
+        let ring = (-k..=k).flat_map(|y| (-k..=k).map(move |x| (x, y))).filter(|&(x, y)| x.abs() == k || y.abs() == k);
+        ring.flat_map(|(x, y)| {
+            let inwards = [(x.abs() == k).then(|| (x - x.signum(), y)), (y.abs() == k).then(|| (x, y - y.signum()))];
+            inwards.into_iter().flatten().map(move |inner| ((x, y), inner))
+        })
+        .all(|((x, y), (ix, iy))| {
+            self.square(x, y).zip(self.square(ix, iy))
+                .all(|(outer, inner)| outer.iter().zip(inner).all(|(o, i)| match (o, i) {
+                    (Some(o), Some(i)) => *o == i + self.period,
+                    (None, None) => true,
+                    _ => false,
+                }))
+        })
+    }
+
+    /// Plots reachable in exactly `steps`: those no further away and of
+    /// the same parity. Squares inside the ring count as they are; past
+    /// each side of it they carry on in a line, and past each corner they
+    /// fill a quadrant, `m + 1` of them `m` periods beyond the corner.
+    pub fn count(&self, steps: u64) -> u128 {
+        let (k, p) = (self.radius as i64, self.period as u64);
+        let mut total = 0;
+        for y in -k..=k {
+            for x in -k..=k {
+                let on_ring = (x.abs() == k) as u8 + (y.abs() == k) as u8;
+                for d in self.square(x, y).flatten().flatten() {
+                    let d = *d as u64;
+                    total += match on_ring {
+                        0 => u128::from(d <= steps && d % 2 == steps % 2),
+                        1 => in_line(d, steps, p),
+                        _ => in_quadrant(d, steps, p),
+                    };
+                }
+            }
+        }
+        total
+    }
+}
+
+/// How many of `d`, `d + p`, `d + 2p`, ... are within `steps` and of its
+/// parity.
+fn in_line(d: u64, steps: u64, p: u64) -> u128 {
+    if d > steps {
+        return 0;
+    }
+    let (m, odd) = (u128::from((steps - d) / p), (steps - d) % 2 == 1);
+    match (p.is_multiple_of(2), odd) {
+        (true, false) => m + 1,
+        (true, true) => 0,
+        // every other one has the right parity
+        (false, false) => m / 2 + 1,
+        (false, true) => m.div_ceil(2),
+    }
+}
+
+/// The same for a quadrant, with `m + 1` squares at `d + m * p`.
+fn in_quadrant(d: u64, steps: u64, p: u64) -> u128 {
+    if d > steps {
+        return 0;
+    }
+    let (m, odd) = (u128::from((steps - d) / p), (steps - d) % 2 == 1);
+    match (p.is_multiple_of(2), odd) {
+        (true, false) => (m + 1) * (m + 2) / 2,
+        (true, true) => 0,
+        // 1 + 3 + 5 + ... and 2 + 4 + 6 + ...
+        (false, false) => (m / 2 + 1).pow(2),
+        (false, true) => m.div_ceil(2) * (m.div_ceil(2) + 1),
+    }
+}
+
+/// Part two carries the distances a few tiles out on forever. They settle
+/// within a couple of tiles when the tile is square with the start in the
+/// middle and clear lines from it to the edges.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let Ok(garden) = input.parse::<Garden>() else {
+        return Assumption::unparsable("expected a rectangle of `.` and `#` with one `S`");
+    };
+    let (h, w) = (garden.height(), garden.width());
+    let (row, col) = garden.start();
+    let rock_on = |cells: Vec<(usize, usize)>| {
+        cells.into_iter().find(|&(r, c)| garden.rocks[r][c]).map_or(Ok(()), |(r, c)| Err(format!("rock at ({}, {})", r, c)))
+    };
+    let start_lines = rock_on((0..h).map(|r| (r, col)).chain((0..w).map(|c| (row, c))).collect());
+    let edges = rock_on((0..h).flat_map(|r| [(r, 0), (r, w - 1)]).chain((0..w).flat_map(|c| [(0, c), (h - 1, c)])).collect());
+
+    vec![
+        Assumption::check("the tile is square", h == w, || format!("{} by {}", h, w)),
+        Assumption::check("S is in the middle", 2 * row + 1 == h && 2 * col + 1 == w, || {
+            format!("S at ({}, {}) in a {} by {} tile", row, col, h, w)
+        }),
+        Assumption::new("S's row and column are clear", start_lines),
+        Assumption::new("the edges of the tile are clear", edges),
+    ]
+}
+
+pub fn part_1(input: &str) -> usize {
+    let garden: Garden = input.parse().unwrap();
+    garden.reachable_in_tile(64)
+}
+
+pub fn part_2(input: &str) -> u128 {
+    let garden: Garden = input.parse().unwrap();
+    garden.reachable(26501365).expect("the distances never settle")
+}
 
 #[test]
 fn test() {
@@ -17,128 +294,44 @@ fn test() {
 .##.#.####.
 .##..##.##.
 ...........
-    ".trim() 
+    ".trim()
     };
 
-    // let input = include_str!("input");
-
-    let map = input.split("\n")
-        .map(|line| line.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let S = map.iter().enumerate()
-        .find_map(|(row, v)| {
-            if let Some(col) = v.iter().enumerate().find_map(|(col, ch)| if *ch=='S' {Some(col)}else{None}) {
-                Some((row, col))
-            } else {
-                None
-            }
-        })
-        .unwrap();
-    println!("S: {:?}", S);
-
-    let mut plots = HashSet::new();
-    plots.insert(S);
-
-    for _ in 0..64 {
-        let mut new_plots = HashSet::new();
-        for (row, col) in plots.iter() {
-            let (row, col) = (*row, *col);
-            // up
-            if row > 0 {
-                let row = row - 1;
-                if map[row][col] != '#' {
-                    new_plots.insert((row, col));
-                }
-            }
-            // down
-            if row < map.len() - 1 {
-                let row = row + 1;
-                if map[row][col] != '#' {
-                    new_plots.insert((row, col));
-                }
-            }
-            // left
-            if col > 0 {
-                let col = col - 1;
-                if map[row][col] != '#' {
-                    new_plots.insert((row, col));
-                }
-            }
-            // right
-            if col < map[0].len() - 1 {
-                let col = col + 1;
-                if map[row][col] != '#' {
-                    new_plots.insert((row, col));
-                }
-            }
-        }
+    let garden: Garden = input.parse().unwrap();
+    assert_eq!(garden.start(), (5, 5));
+    assert_eq!(garden.reachable_in_tile(6), 16);
 
-        plots = new_plots;
+    // Part Two
+    for (steps, plots) in [(6, 16), (10, 50), (50, 1594), (100, 6536)] {
+        assert_eq!(garden.reachable_brute_force(steps), plots);
+        assert_eq!(garden.reachable(steps as u64), Some(plots as u128));
     }
+    for steps in 0..40 {
+        assert_eq!(garden.reachable(steps as u64), Some(garden.reachable_brute_force(steps) as u128));
+    }
+    assert_eq!(garden.reachable(500), Some(167004));
+    assert_eq!(garden.reachable(1000), Some(668697));
+    assert_eq!(garden.reachable(5000), Some(16733044));
 
-    println!("{}", plots.len());
+    // a tile that isn't square repeats in squares of lcm(5, 7) on a side
+    let narrow: Garden = ".#.....\n...#.#.\n..S....\n.#...#.\n....#..".parse().unwrap();
+    for steps in [0, 1, 7, 20, 36, 80, 121] {
+        assert_eq!(narrow.reachable(steps as u64), Some(narrow.reachable_brute_force(steps) as u128), "{} steps", steps);
+    }
 
+    // the sample gets there without the real input's clear lines from S
+    let broken = assumptions(input).into_iter().filter(|a| !a.holds()).map(|a| a.name).collect::<Vec<_>>();
+    assert_eq!(broken, ["S's row and column are clear"]);
+}
 
-    // Part Two
-    {
-        let len = map.len() as i32;
-        let calc = |x: i32| {
-            if x >= 0 {
-                x % len
-            } else {
-                len - 1 - (-x - 1) % len
-            }
-        };
-
-        let mut plots = HashSet::with_capacity(7000000);
-        plots.insert((S.0 as i32, S.1 as i32));
-        let mut new_plots = HashSet::with_capacity(700000);
-
-        // 26501365 = 202300 * 131 + 65
-        for i in 0..5+11+11+11+11+11 {
-            println!("i: {}", i);
-            new_plots.clear();
-            for (row, col) in plots.iter() {
-                let (row, col) = (*row, *col);
-                // up
-                {
-                    let r = calc(row - 1);
-                    let c = calc(col);
-
-                    if map[r as usize][c as usize] != '#' {
-                        new_plots.insert((row-1, col));
-                    }
-                }
-                // down
-                {
-                    let r = calc(row + 1);
-                    let c = calc(col);
-                    if map[r as usize][c as usize] != '#' {
-                        new_plots.insert((row+1, col));
-                    }
-                }
-                // left
-                {
-                    let r = calc(row);
-                    let c = calc(col - 1);
-                    if map[r as usize][c as usize] != '#' {
-                        new_plots.insert((row, col-1));
-                    }
-                }
-                // right
-                {
-                    let r = calc(row);
-                    let c = calc(col + 1);
-                    if map[r as usize][c as usize] != '#' {
-                        new_plots.insert((row, col+1));
-                    }
-                }
-            }
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let garden: Garden = input.parse().unwrap();
 
-            std::mem::swap(&mut plots, &mut new_plots);
-        }
+    println!("{}", garden.reachable_in_tile(64));
 
-        println!("{}", plots.len());
-    }
+    // Part Two
+    assert_eq!(garden.reachable(300), Some(garden.reachable_brute_force(300) as u128));
+    println!("{:?}", garden.reachable(26501365));
 }
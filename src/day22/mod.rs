@@ -1,120 +1,313 @@
-use std::{str::FromStr, ops::RangeInclusive, collections::{HashMap, HashSet}};
+use std::{str::FromStr, collections::HashMap};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Pos {
-    x: i32,
-    y: i32,
-    z: i32,
-}
+use crate::{check::Assumption, dot::{Graph, ToDot}, input, interval::{Hyperbox, Interval}, vec3::{Aabb, Vec3}};
 
-impl Pos {
-    fn new(x: i32, y: i32, z: i32) -> Self {
-        Self { x, y, z }
-    }
-}
+pub type Pos = Vec3<i32>;
+
+const X: usize = 0;
+const Y: usize = 1;
+const Z: usize = 2;
 
-impl FromStr for Pos {
+/// The cubes a brick fills, as a box with sides along x, y and z.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Brick(Hyperbox<i32>);
+
+impl FromStr for Brick {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut it = s.split(",")
-            .map(|s|s.parse::<i32>().unwrap());
-        let x = it.next().unwrap();
-        let y = it.next().unwrap();
-        let z = it.next().unwrap();
-        Ok(Pos::new(x, y, z))
+        let (start, end) = s.split_once('~').ok_or(())?;
+        let start = start.parse::<Pos>()?;
+        let end = end.parse::<Pos>()?;
+        if start.x > end.x || start.y > end.y || start.z > end.z || start.z < 1 {
+            return Err(());
+        }
+        // small enough that one past the far corner, and a footprint's
+        // cells, are no trouble
+        if [start, end].iter().any(|p| [p.x, p.y, p.z].iter().any(|c| !(0..1 << 16).contains(c))) {
+            return Err(());
+        }
+        Ok(Self::new(start, end))
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Brick {
-    x: RangeInclusive<i32>,
-    y: RangeInclusive<i32>,
-    z: RangeInclusive<i32>,
+impl Brick {
+    /// The brick between two opposite corner cubes.
+    pub fn new(start: Pos, end: Pos) -> Self {
+        let Aabb { min, max } = Aabb::new(start, end);
+        Self(Hyperbox::new(vec![
+            Interval::inclusive(min.x, max.x),
+            Interval::inclusive(min.y, max.y),
+            Interval::inclusive(min.z, max.z),
+        ]))
+    }
+
+    /// The lowest and highest corner cubes.
+    pub fn corners(&self) -> Aabb<i32> {
+        let last = |i: Interval<i32>| i.last().unwrap();
+        Aabb::new(
+            Vec3::new(self.x().start, self.y().start, self.z().start),
+            Vec3::new(last(self.x()), last(self.y()), last(self.z())),
+        )
+    }
+
+    pub fn x(&self) -> Interval<i32> { self.0[X] }
+    pub fn y(&self) -> Interval<i32> { self.0[Y] }
+    pub fn z(&self) -> Interval<i32> { self.0[Z] }
+
+    /// The cells it covers seen from above.
+    pub fn footprint(&self) -> Hyperbox<i32> {
+        self.0.project(&[X, Y])
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.x().range().flat_map(move |x| self.y().range().map(move |y| (x, y)))
+    }
+
+    /// The same brick moved down so its bottom sits at `z`.
+    fn lowered_to(&self, z: i32) -> Self {
+        Self(self.0.shift(Z, self.z().start, z))
+    }
 }
 
-impl FromStr for Brick {
+/// Settled bricks and the support graph between them.
+///
+/// Bricks keep the id they were given, input order for `settle` and the next
+/// free one for `drop_brick`. Dropping in order of height means every brick
+/// comes after the ones holding it up, so the dominator tree of the support
+/// graph (rooted at the ground) grows one brick at a time as well.
+#[derive(Debug, Clone, Default)]
+pub struct SandStack {
+    bricks: Vec<Brick>,
+    /// Ids in the order they landed.
+    order: Vec<usize>,
+    /// 俯视, 当前 (x, y) 的位置上最高砖块之上第一个空的 z 坐标和砖块 id
+    ground: HashMap<(i32, i32), (i32, usize)>,
+    /// Bricks each brick rests on.
+    below: Vec<Vec<usize>>,
+    /// Bricks resting on each brick.
+    above: Vec<Vec<usize>>,
+    /// Immediate dominator, `None` for the ground.
+    idom: Vec<Option<usize>>,
+    /// Depth in the dominator tree, the ground being 0.
+    depth: Vec<usize>,
+}
+
+impl FromStr for SandStack {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut it = s.split("~");
-        let start = it.next().unwrap();
-        let end = it.next().unwrap();
-
-        let start = start.parse::<Pos>().unwrap();
-        let end = end.parse::<Pos>().unwrap();
-        Ok(Self {
-            x: start.x ..= end.x,
-            y: start.y ..= end.y,
-            z: start.z ..= end.z,
-        })
+        let bricks = input::lines(s).map_err(|_| ())?
+            .into_iter()
+            .map(|line| line.parse::<Brick>())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::settle(bricks))
     }
 }
 
-impl PartialOrd for Brick {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.z.start().partial_cmp(&other.z.start()) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
-        }
-        match self.z.end().partial_cmp(&other.z.end()) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
+impl SandStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lets every brick fall as far as it can, lowest first.
+    pub fn settle(bricks: Vec<Brick>) -> Self {
+        let n = bricks.len();
+        let mut order = (0..n).collect::<Vec<_>>();
+        order.sort_by_key(|id| bricks[*id].z().start);
+
+        let mut this = Self {
+            bricks,
+            order: Vec::with_capacity(n),
+            ground: HashMap::new(),
+            below: vec![Vec::new(); n],
+            above: vec![Vec::new(); n],
+            idom: vec![None; n],
+            depth: vec![0; n],
+        };
+        for id in order {
+            this.land(id);
         }
-        match self.x.start().partial_cmp(&other.x.start()) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
+        this
+    }
+
+    /// Drops one more brick from above onto the stack, returning its id.
+    pub fn drop_brick(&mut self, brick: Brick) -> usize {
+        let id = self.bricks.len();
+        self.bricks.push(brick);
+        self.below.push(Vec::new());
+        self.above.push(Vec::new());
+        self.idom.push(None);
+        self.depth.push(0);
+        self.land(id);
+        id
+    }
+
+    fn land(&mut self, id: usize) {
+        let brick = &self.bricks[id];
+        let top = brick.cells()
+            .filter_map(|cell| self.ground.get(&cell).map(|(z, _)| *z))
+            .max()
+            .unwrap_or(1);
+        let mut below = brick.cells()
+            .filter_map(|cell| self.ground.get(&cell).filter(|(z, _)| *z == top).map(|(_, id)| *id))
+            .collect::<Vec<_>>();
+        below.sort_unstable();
+        below.dedup();
+
+        let brick = brick.lowered_to(top);
+        for cell in brick.cells() {
+            self.ground.insert(cell, (brick.z().end, id));
         }
-        match self.x.end().partial_cmp(&other.x.end()) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
+        self.bricks[id] = brick;
+
+        for &b in below.iter() {
+            self.above[b].push(id);
         }
-        match self.y.start().partial_cmp(&other.y.start()) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
+        let idom = below.iter()
+            .map(|b| Some(*b))
+            .reduce(|a, b| self.lca(a, b))
+            .flatten();
+        self.idom[id] = idom;
+        self.depth[id] = idom.map_or(0, |d| self.depth[d]) + 1;
+        self.below[id] = below;
+        self.order.push(id);
+    }
+
+    fn lca(&self, mut a: Option<usize>, mut b: Option<usize>) -> Option<usize> {
+        let depth = |x: Option<usize>| x.map_or(0, |i| self.depth[i]);
+        while a != b {
+            if depth(a) >= depth(b) {
+                a = self.idom[a.unwrap()];
+            } else {
+                b = self.idom[b.unwrap()];
+            }
         }
-        self.y.end().partial_cmp(&other.y.end())
+        a
     }
-}
 
-impl Ord for Brick {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+    pub fn len(&self) -> usize {
+        self.bricks.len()
     }
-}
 
-struct Ground {
-    /// (x, y) -> z
-    /// 俯视, 当前 (x, y) 的位置上的地面 z 坐标
-    state: HashMap<(i32, i32), i32>,
-    id: HashMap<(i32, i32), usize>,
-}
+    pub fn is_empty(&self) -> bool {
+        self.bricks.is_empty()
+    }
 
-impl Ground {
-    fn new() -> Self {
-        Self { state: HashMap::new(), id: HashMap::new() }
+    /// Settled position of a brick.
+    pub fn brick(&self, id: usize) -> &Brick {
+        &self.bricks[id]
     }
-    fn get_z(&mut self, x: i32, y: i32) -> i32 {
-        if !self.state.contains_key(&(x, y)) {
-            self.state.insert((x, y), 0);
-        }
-        *self.state.get(&(x, y)).unwrap()
+
+    pub fn bricks(&self) -> &[Brick] {
+        &self.bricks
+    }
+
+    /// Bricks `id` rests on; empty if it rests on the ground.
+    pub fn supports(&self, id: usize) -> &[usize] {
+        &self.below[id]
     }
-    fn set_z(&mut self, x: i32, y: i32, z: i32) {
-        self.state.insert((x, y), z);
+
+    /// Bricks resting on `id`.
+    pub fn supported(&self, id: usize) -> &[usize] {
+        &self.above[id]
+    }
+
+    /// Bricks that can go without anything else falling, in id order.
+    pub fn safe_to_disintegrate(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|id| self.above[*id].iter().all(|up| self.below[*up].len() > 1))
+            .collect()
     }
-    fn get_id(&self, x: i32, y: i32) -> Option<usize> {
-        self.id.get(&(x, y)).map(|x|*x)
+
+    /// How many other bricks fall if `id` is disintegrated: the bricks it
+    /// dominates, since every path from the ground to them runs through it.
+    pub fn chain_reaction(&self, id: usize) -> usize {
+        self.chain_reactions()[id]
+    }
+
+    /// `chain_reaction` of every brick, indexed by id.
+    pub fn chain_reactions(&self) -> Vec<usize> {
+        let mut size = vec![1; self.len()];
+        for &id in self.order.iter().rev() {
+            if let Some(d) = self.idom[id] {
+                size[d] += size[id];
+            }
+        }
+        size.into_iter().map(|s| s - 1).collect()
     }
-    fn set_id(&mut self, x: i32, y: i32, id: usize) {
-        self.id.insert((x, y), id);
+
+    /// `chain_reaction` by removing `id` and knocking down whatever loses
+    /// all its supports, brick by brick.
+    pub fn chain_reaction_naive(&self, id: usize) -> usize {
+        let mut fallen = vec![false; self.len()];
+        fallen[id] = true;
+        let mut count = 0;
+        for &b in self.order.iter() {
+            if !fallen[b] && !self.below[b].is_empty() && self.below[b].iter().all(|d| fallen[*d]) {
+                fallen[b] = true;
+                count += 1;
+            }
+        }
+        count
     }
 }
 
-#[derive(Debug, Default)]
-struct Node {
-    up: HashSet<usize>,
-    down: HashSet<usize>,
+/// The support graph drawn bottom up, from the ground to each brick by id.
+impl ToDot for SandStack {
+    fn to_graph(&self) -> Graph {
+        let mut graph = Graph::directed("bricks");
+        graph.set("rankdir", "BT");
+        graph.node("ground").set("shape", "box");
+        for (id, b) in self.bricks.iter().enumerate() {
+            let label = format!("{}\nz={}..{}", id, b.z().start, b.z().end - 1);
+            graph.node(&id.to_string()).label(&label);
+        }
+        for &id in self.order.iter() {
+            if self.below[id].is_empty() {
+                graph.edge("ground", &id.to_string());
+            }
+            for b in self.below[id].iter() {
+                graph.edge(&b.to_string(), &id.to_string());
+            }
+        }
+        graph
+    }
+}
+
+/// Settling drops whole bricks by their footprints, which assumes the
+/// snapshot is physically possible to begin with.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let bricks = match input::lines(input) {
+        Ok(lines) => match lines.iter().map(|l| l.parse::<Brick>()).collect::<Result<Vec<_>, _>>() {
+            Ok(bricks) => bricks,
+            Err(()) => return Assumption::unparsable("expected `x,y,z~x,y,z` lines with z at least 1 and no coordinate past 65535"),
+        },
+        Err(e) => return Assumption::unparsable(e),
+    };
+    let bent = bricks.iter().position(|b| [b.x(), b.y(), b.z()].iter().filter(|i| i.len() > 1).count() > 1);
+    let overlap = (0..bricks.len())
+        .find_map(|i| (i + 1..bricks.len()).find(|j| bricks[i].0.overlaps(&bricks[*j].0)).map(|j| (i, j)));
+
+    vec![
+        Assumption::check("every brick is a straight line", bent.is_none(), || {
+            format!("brick on line {} is wider than one cube in two directions", bent.unwrap() + 1)
+        }),
+        Assumption::check("no two bricks overlap", overlap.is_none(), || {
+            let (i, j) = overlap.unwrap();
+            format!("bricks on lines {} and {}", i + 1, j + 1)
+        }),
+    ]
+}
+
+pub fn part_1(input: &str) -> usize {
+    let stack: SandStack = input.parse().unwrap();
+    stack.safe_to_disintegrate().len()
+}
+
+pub fn part_2(input: &str) -> usize {
+    let stack: SandStack = input.parse().unwrap();
+    stack.chain_reactions().iter().sum()
 }
 
 #[test]
@@ -129,154 +322,113 @@ fn test() {
 1,1,8~1,1,9".trim()
     };
 
-    let input = include_str!("input");
-
-    let mut bricks = input.split("\n")
-        .map(|line| line.parse::<Brick>().unwrap())
-        .collect::<Vec<_>>();
-
-    let origin = bricks.clone();
-
-    bricks.sort();
-
-    println!("{}", origin == bricks);
+    let stack: SandStack = input.parse().unwrap();
+    assert_eq!(stack.brick(6).z(), Interval::from(5..=6));
+    assert!(stack.brick(6).footprint().overlaps(&stack.brick(5).footprint()));
+    assert!(!stack.brick(6).footprint().overlaps(&stack.brick(3).footprint()));
+    assert_eq!(stack.brick(6).corners(), Aabb::new(Vec3::new(1, 1, 5), Vec3::new(1, 1, 6)));
+    assert_eq!(stack.supports(3), &[1, 2]);
+    assert_eq!(stack.supported(0), &[1, 2]);
+    assert_eq!(stack.safe_to_disintegrate(), vec![1, 2, 3, 4, 6]);
+    let dot = stack.to_dot();
+    assert!(dot.contains("\"ground\" -> \"0\";"));
+    assert!(dot.contains("\"2\" -> \"3\";"));
 
-    let mut up_to_down = HashMap::new();
-
-    let mut ground = Ground::new();
-
-    for (id, brick) in bricks.iter_mut().enumerate() {
-        let z = *brick.z.start();
-        let z_end = *brick.z.end();
+    // Part Two
+    let reactions = stack.chain_reactions();
+    assert_eq!(reactions, vec![6, 0, 0, 0, 0, 1, 0]);
+    for (id, r) in reactions.iter().enumerate() {
+        assert_eq!(*r, stack.chain_reaction_naive(id));
+    }
 
-        let mut max_ground = 0;
-        let mut occupied = HashSet::new();
+    // one more on top of the last brick makes that brick unsafe
+    let mut stack = stack;
+    let id = stack.drop_brick("1,1,20~1,1,20".parse().unwrap());
+    assert_eq!(id, 7);
+    assert_eq!(stack.brick(id).z(), Interval::from(7..=7));
+    assert_eq!(stack.supports(id), &[6]);
+    assert_eq!(stack.safe_to_disintegrate(), vec![1, 2, 3, 4, 7]);
+    assert_eq!(stack.chain_reaction(0), 7);
+    assert_eq!(stack.chain_reaction(6), 1);
+}
 
-        for x in brick.x.clone() {
-            for y in brick.y.clone() {
-                let g = ground.get_z(x, y);
-                if g != 0 && g < z {
-                    if g == max_ground {
-                        occupied.insert(ground.get_id(x, y).unwrap());
-                    }
-                    else if g > max_ground {
-                        max_ground = g;
-                        occupied.clear();
-                        occupied.insert(ground.get_id(x, y).unwrap());
-                    }
-                }
+#[test]
+fn differential() {
+    use crate::prop::{self, Config, Rng};
+
+    // `[x, y, z, axis, extra length]` in a 3x3 column, none overlapping
+    let brick = |b: &[i32; 5]| {
+        let start = Pos::new(b[0], b[1], b[2]);
+        let mut end = start.to_array();
+        end[b[3] as usize] += b[4];
+        Brick::new(start, Pos::new(end[0], end[1], end[2]))
+    };
+    let valid = |v: &Vec<[i32; 5]>| {
+        let bricks = v.iter().map(brick).collect::<Vec<_>>();
+        v.iter().all(|b| b[2] >= 1 && b[0] >= 0 && b[1] >= 0 && b[4] >= 0)
+            && (0..bricks.len()).all(|i| bricks[i + 1..].iter().all(|b| !b.0.overlaps(&bricks[i].0)))
+    };
+    let generate = |rng: &mut Rng| {
+        let mut v = Vec::new();
+        for _ in 0..rng.size(1..=10) {
+            let axis = rng.range(0..=2) as i32;
+            let len = rng.range(0..=2) as i32;
+            let mut b = [rng.range(0..=2) as i32, rng.range(0..=2) as i32, rng.range(1..=10) as i32, axis, len];
+            if axis < 2 {
+                b[axis as usize] = b[axis as usize].min(2 - len);
             }
-        }
-
-        // 降落
-        let new_z = max_ground + 1;
-        let z_end = z_end - (z - new_z);
-        brick.z = new_z ..= z_end;
-
-        for x in brick.x.clone() {
-            for y in brick.y.clone() {
-                ground.set_z(x, y, z_end);
-                ground.set_id(x, y, id);
+            v.push(b);
+            if !valid(&v) {
+                v.pop();
             }
         }
-
-        up_to_down.insert(id, occupied);
-    }
-
-    let mut nodes = HashMap::new();
-
-    up_to_down.iter()
-        .for_each(|(id, p)| {
-            if !nodes.contains_key(id) {
-                nodes.insert(*id, Node::default());
-            }
-
-            p.iter().for_each(|p_id| {
-                nodes.get_mut(id).unwrap().down.insert(*p_id);
-
-                if !nodes.contains_key(p_id) {
-                    nodes.insert(*p_id, Node::default());
+        v
+    };
+    let shrink = |v: &Vec<[i32; 5]>| {
+        prop::shrink_vec(v, 1, |b| {
+            let mut ret = Vec::new();
+            for (i, target) in [(0, 0), (1, 0), (2, 1), (4, 0)] {
+                for n in prop::shrink_int(b[i] as i64, target) {
+                    let mut c = *b;
+                    c[i] = n as i32;
+                    ret.push(c);
                 }
-
-                nodes.get_mut(p_id).unwrap().up.insert(*id);
-            });
-        });
-
-    
-    let mut can_be_disintergrated = HashSet::new();
-
-    nodes.iter()
-        .for_each(|(id, node)| {
-            // If no upper bricks depend on me, then I can be disintegrated
-            if node.up.is_empty() {
-                can_be_disintergrated.insert(*id);
             }
-            let mut without_id = HashSet::new();
-            without_id.insert(*id);
-            // If all my upper bricks can find a way down to the ground without me, then I'm ok to be disintergrated
-            if node.up.iter().all(|up_id| {
-                can_find_a_way_down(&bricks, &nodes, *up_id, &without_id)
-            }) {
-                can_be_disintergrated.insert(*id);
-            }
-        });
-
-    println!("{}", can_be_disintergrated.len());
-
-    // Part Two
-    let sum = nodes.iter()
-        .filter(|(id, _)| !can_be_disintergrated.contains(*id))
-        .map(|(id, _)| {
-            let without_id = HashSet::new();
-            let mut falling_ids = HashSet::new();
-            calc_fall_count(&bricks, &nodes, *id, without_id, &mut falling_ids);
-            println!("{}: {:?}", id, falling_ids);
-            falling_ids.len()
+            ret
         })
-        .sum::<usize>();
-
-    println!("sum: {}", sum);
-
-}
-
-fn can_find_a_way_down(bricks: &Vec<Brick>, nodes: &HashMap<usize, Node>, id: usize, without_id: &HashSet<usize>) -> bool {
-    if without_id.contains(&id) {
-        return false;
-    }
-    // 已经在地面上了
-    if *bricks.get(id).unwrap().z.start() == 1 {
-        return true;
-    }
-
-    let node = nodes.get(&id).unwrap();
-    if node.down.is_empty() {
-        return false;
-    }
-    node.down.iter()
-        .any(|id| can_find_a_way_down(bricks, nodes, *id, without_id))
+        .into_iter()
+        .filter(valid)
+        .collect::<Vec<_>>()
+    };
+    let stack = |v: &Vec<[i32; 5]>| SandStack::settle(v.iter().map(brick).collect());
+    prop::assert_agree(
+        &Config::default(),
+        generate,
+        shrink,
+        |v| {
+            let stack = stack(v);
+            let falls = (0..stack.len()).map(|id| stack.chain_reaction_naive(id)).collect::<Vec<_>>();
+            let safe = (0..stack.len()).filter(|id| falls[*id] == 0).collect::<Vec<_>>();
+            (falls, safe)
+        },
+        |v| {
+            let stack = stack(v);
+            (stack.chain_reactions(), stack.safe_to_disintegrate())
+        },
+    );
 }
 
-/// calculate the number of other bricks that would fall
-fn calc_fall_count(bricks: &Vec<Brick>, nodes: &HashMap<usize, Node>, id: usize, mut without_id: HashSet<usize>, falling_ids: &mut HashSet<usize>) {
-    let node = nodes.get(&id).unwrap();
-    if node.up.is_empty() {
-        return;
-    }
-    without_id.insert(id);
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let stack: SandStack = input.parse().unwrap();
 
-    let mut ids = Vec::new();
+    println!("{}", stack.safe_to_disintegrate().len());
 
-    for up_id in node.up.iter() {
-        if !can_find_a_way_down(bricks, nodes, *up_id, &without_id) {
-            without_id.insert(*up_id);
-            falling_ids.insert(*up_id);
-            ids.push(*up_id);
-        }
+    // Part Two
+    let reactions = stack.chain_reactions();
+    for (id, r) in reactions.iter().enumerate() {
+        assert_eq!(*r, stack.chain_reaction_naive(id));
     }
-
-    ids.iter()
-        .for_each(|up_id| {
-            let without_id = without_id.clone();
-            calc_fall_count(bricks, nodes, *up_id, without_id, falling_ids);
-        });
+    println!("sum: {}", reactions.iter().sum::<usize>());
 }
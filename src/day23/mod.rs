@@ -1,244 +1,431 @@
 
 
-// There's a map of nearby hiking trails (your puzzle input) 
+// There's a map of nearby hiking trails (your puzzle input)
 // that indicates paths (.), forest (#), and steep slopes (^, >, v, and <).
 
-use std::collections::HashSet;
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{check::Assumption, dot::{Graph, ToDot}, input};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Pos {
-    row: usize,
-    col: usize,
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
 }
 
 impl Pos {
-    fn new(row: usize, col: usize) -> Self {
+    pub fn new(row: usize, col: usize) -> Self {
         Self { row, col }
     }
 }
 
-#[test]
-fn test() {
-    let input = {"
-#.#####################
-#.......#########...###
-#######.#########.#.###
-###.....#.>.>.###.#.###
-###v#####.#v#.###.#.###
-###.>...#.#.#.....#...#
-###v###.#.#.#########.#
-###...#.#.#.......#...#
-#####.#.#.#######.#.###
-#.....#.#.#.......#...#
-#.#####.#.#.#########v#
-#.#...#...#...###...>.#
-#.#.#v#######v###.###v#
-#...#.>.#...>.>.#.###.#
-#####v#.#.###v#.#.###.#
-#.....#...#...#.#.#...#
-#.#########.###.#.#.###
-#...###...#...#...#.###
-###.###.#.###v#####v###
-#...#...#.#.>.>.#.>.###
-#.###.###.#.###.#.#v###
-#.....###...###...#...#
-#####################.#
-".trim()};
-
-    let input = include_str!("input");
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
 
-    let map = input.split("\n")
-        .map(|s|s.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
+impl Dir {
+    const ALL: [Dir; 4] = [Dir::Up, Dir::Down, Dir::Left, Dir::Right];
+}
 
-    let map_size = map.len();
-    assert_eq!(map_size, map[0].len());
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Tile {
+    /// `.`
+    Path,
+    /// `#`
+    Forest,
+    /// `^`, `>`, `v` or `<`, only walkable downhill
+    Slope(Dir),
+}
 
-    let start = Pos::new(0, 1);
-    let end = Pos::new(map_size - 1, map_size - 2);
+impl TryFrom<char> for Tile {
+    type Error = ();
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '.' => Ok(Self::Path),
+            '#' => Ok(Self::Forest),
+            '^' => Ok(Self::Slope(Dir::Up)),
+            'v' => Ok(Self::Slope(Dir::Down)),
+            '<' => Ok(Self::Slope(Dir::Left)),
+            '>' => Ok(Self::Slope(Dir::Right)),
+            _ => Err(()),
+        }
+    }
+}
 
-    assert_eq!(map[start.row][start.col], '.');
-    assert_eq!(map[end.row][end.col], '.');
+#[derive(Debug, Clone)]
+pub struct TrailMap {
+    tiles: Vec<Vec<Tile>>,
+    start: Pos,
+    end: Pos,
+}
 
-    let mut path = HashSet::new();
-    path.insert(start);
-    let steps = dfs(&map, path, start, end);
-    println!("steps: {:?}", steps-1);
+impl FromStr for TrailMap {
+    type Err = ();
 
-    // Part Two
-    let mut dead_ends = HashSet::new();
-    let mut path = HashSet::new();
-    path.insert(start);
-    let steps = dfs_part_2(&map, path, None, start, end, &mut dead_ends);
-    println!("steps: {:?}", steps-1);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tiles = input::grid(s).map_err(|_| ())?
+            .into_iter()
+            .map(|line| line.chars().map(Tile::try_from).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+        if tiles.len() < 2 {
+            return Err(());
+        }
+        let open = |row: usize| {
+            let mut it = tiles[row].iter().enumerate().filter(|(_, t)| **t == Tile::Path);
+            match (it.next(), it.next()) {
+                (Some((col, _)), None) => Ok(Pos::new(row, col)),
+                _ => Err(()),
+            }
+        };
+        let start = open(0)?;
+        let end = open(tiles.len() - 1)?;
+        Ok(Self { tiles, start, end })
+    }
 }
 
-fn dfs(map: &Vec<Vec<char>>, path: HashSet<Pos>, start: Pos, end: Pos) -> usize {
-    if start == end {
-        return path.len();
+impl TrailMap {
+    pub fn start(&self) -> Pos {
+        self.start
+    }
+
+    pub fn end(&self) -> Pos {
+        self.end
     }
 
-    let mut path = path;
-    let map_size = map.len();
-    let mut start = start;
+    pub fn tile(&self, pos: Pos) -> Tile {
+        self.tiles[pos.row][pos.col]
+    }
 
-    let mut new_positions = Vec::new();
+    fn step(&self, pos: Pos, dir: Dir) -> Option<Pos> {
+        let Pos { row, col } = pos;
+        let next = match dir {
+            Dir::Up => Pos::new(row.checked_sub(1)?, col),
+            Dir::Down => Pos::new(row + 1, col),
+            Dir::Left => Pos::new(row, col.checked_sub(1)?),
+            Dir::Right => Pos::new(row, col + 1),
+        };
+        let tile = self.tiles.get(next.row)?.get(next.col)?;
+        (*tile != Tile::Forest).then_some(next)
+    }
 
-    loop {
-        let Pos { row, col } = start;
+    fn neighbours(&self, pos: Pos) -> impl Iterator<Item = (Dir, Pos)> + '_ {
+        Dir::ALL.into_iter().filter_map(move |dir| self.step(pos, dir).map(|p| (dir, p)))
+    }
 
-        new_positions.clear();
+    /// Whether walking `dir` off `pos` is allowed.
+    fn can_leave(&self, pos: Pos, dir: Dir, slopes: bool) -> bool {
+        match self.tile(pos) {
+            Tile::Slope(d) if slopes => d == dir,
+            _ => true,
+        }
+    }
 
-        {
-            // up
-            if row > 0 {
-                let row = row - 1;
-                if map[row][col] != 'v' {
-                    new_positions.push(Pos::new(row, col));
+    /// Compresses the trails into the start, the end and every fork, joined
+    /// by the corridors between them. With `slopes` a corridor only counts
+    /// in the direction its slopes allow.
+    pub fn junction_graph(&self, slopes: bool) -> JunctionGraph {
+        let mut nodes = vec![self.start, self.end];
+        for (row, v) in self.tiles.iter().enumerate() {
+            for col in 0..v.len() {
+                let pos = Pos::new(row, col);
+                if self.tile(pos) != Tile::Forest && self.neighbours(pos).count() >= 3 {
+                    nodes.push(pos);
                 }
             }
-            // down
-            if row < map_size - 1 {
-                let row = row + 1;
-                if map[row][col] != '^' {
-                    new_positions.push(Pos::new(row, col));
+        }
+        let index = nodes.iter()
+            .enumerate()
+            .map(|(i, pos)| (*pos, i))
+            .collect::<HashMap<_, _>>();
+
+        let mut edges = vec![Vec::new(); nodes.len()];
+        for (from, &pos) in nodes.iter().enumerate() {
+            for (dir, next) in self.neighbours(pos) {
+                if !self.can_leave(pos, dir, slopes) {
+                    continue;
                 }
-            }
-            // left
-            if col > 0 {
-                let col = col - 1;
-                if map[row][col] != '>' {
-                    new_positions.push(Pos::new(row, col));
+                let mut cells = vec![next];
+                let (mut prev, mut cur) = (pos, next);
+                let mut ok = true;
+
+                while !index.contains_key(&cur) {
+                    let Some((d, n)) = self.neighbours(cur).find(|(_, n)| *n != prev) else {
+                        // dead end
+                        ok = false;
+                        break;
+                    };
+                    if !self.can_leave(cur, d, slopes) {
+                        ok = false;
+                        break;
+                    }
+                    (prev, cur) = (cur, n);
+                    cells.push(cur);
                 }
-            }
-            // right
-            if col < map_size - 1 {
-                let col = col + 1;
-                if map[row][col] != '<' {
-                    new_positions.push(Pos::new(row, col));
+                if ok {
+                    edges[from].push(Edge { to: index[&cur], cells });
                 }
             }
         }
 
-        new_positions = new_positions.into_iter()
-            .filter(|pos| !path.contains(pos))
-            .filter(|pos| map[pos.row][pos.col] != '#')
-            .collect();
-
-        if new_positions.len() == 0 {
-            return path.len();
-        } else if new_positions.len() == 1 {
-            let pos = new_positions[0];
-            path.insert(pos);
-            start = pos;
-        } else {
-            break;
-        }
+        JunctionGraph { nodes, edges }
     }
+}
 
-    {
-        new_positions.into_iter()
-            .map(|pos| {
-                // let ch = map[pos.row][pos.col];
-                let mut path = path.clone();
-                path.insert(pos);
-                dfs(map, path, pos, end)
-            })
-            .max()
-            .unwrap()
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub to: usize,
+    /// Cells walked after leaving the junction, ending on `to`.
+    pub cells: Vec<Pos>,
 }
 
-fn dfs_part_2(map: &Vec<Vec<char>>, path: HashSet<Pos>, mut pre_pos: Option<Pos>, start: Pos, end: Pos, dead_ends: &mut HashSet<Pos>) -> usize {
-    if start == end {
-        return path.len();
+impl Edge {
+    pub fn len(&self) -> usize {
+        self.cells.len()
     }
 
-    let this_start = start;
-
-    let mut path = path;
-    let map_size = map.len();
-    let mut start = start;
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
 
-    let mut new_positions = Vec::new();
+/// Junctions of the trail map; node 0 is the start and node 1 the end.
+#[derive(Debug, Clone)]
+pub struct JunctionGraph {
+    pub nodes: Vec<Pos>,
+    pub edges: Vec<Vec<Edge>>,
+}
 
-    loop {
-        let Pos { row, col } = start;
+/// A longest hike and the cells it walks, start and end included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hike {
+    pub steps: usize,
+    pub route: Vec<Pos>,
+}
 
-        new_positions.clear();
+struct Search<'a> {
+    graph: &'a JunctionGraph,
+    /// Longest edge out of every node, for the upper bound.
+    best_out: Vec<usize>,
+    /// The only junction leading into the end, if there is just one.
+    last: Option<usize>,
+    best: Option<usize>,
+    best_path: Vec<usize>,
+    path: Vec<usize>,
+}
 
-        {
-            // up
-            if row > 0 {
-                let row = row - 1;
-                let pos = Pos::new(row, col);
-                if !matches!(pre_pos, Some(p) if p==pos) && map[row][col] != '#' {new_positions.push(Pos::new(row, col));}
-            }
-            // down
-            if row < map_size - 1 {
-                let row = row + 1;
-                let pos = Pos::new(row, col);
-                if !matches!(pre_pos, Some(p) if p==pos) && map[row][col] != '#' {new_positions.push(Pos::new(row, col));}
+impl Search<'_> {
+    /// `remaining`: sum of `best_out` over unvisited nodes.
+    fn dfs(&mut self, node: usize, visited: &mut [u64], steps: usize, remaining: usize) {
+        if node == 1 {
+            if self.best.is_none_or(|best| steps > best) {
+                self.best = Some(steps);
+                self.best_path = self.path.clone();
             }
-            // left
-            if col > 0 {
-                let col = col - 1;
-                let pos = Pos::new(row, col);
-                if !matches!(pre_pos, Some(p) if p==pos) && map[row][col] != '#' {new_positions.push(Pos::new(row, col));}
+            return;
+        }
+        if self.best.is_some_and(|best| steps + self.best_out[node] + remaining <= best) {
+            return;
+        }
+
+        for (i, edge) in self.graph.edges[node].iter().enumerate() {
+            let (word, bit) = (edge.to / 64, 1 << (edge.to % 64));
+            if visited[word] & bit != 0 {
+                continue;
             }
-            // right
-            if col < map_size - 1 {
-                let col = col + 1;
-                let pos = Pos::new(row, col);
-                if !matches!(pre_pos, Some(p) if p==pos) && map[row][col] != '#' {new_positions.push(Pos::new(row, col));}
+            // leaving the last junction anywhere but the end can't come back
+            if Some(node) == self.last && edge.to != 1 {
+                continue;
             }
+            self.path.push(i);
+            visited[word] |= bit;
+            self.dfs(edge.to, visited, steps + edge.len(), remaining - self.best_out[edge.to]);
+            visited[word] &= !bit;
+            self.path.pop();
+        }
+    }
+}
+
+impl JunctionGraph {
+    /// The longest walk from start to end that never visits a cell twice.
+    pub fn longest_hike(&self) -> Option<Hike> {
+        let n = self.nodes.len();
+
+        let best_out = self.edges.iter()
+            .map(|v| v.iter().map(|e| e.len()).max().unwrap_or(0))
+            .collect::<Vec<_>>();
+        let into_end = (0..n)
+            .filter(|from| self.edges[*from].iter().any(|e| e.to == 1))
+            .collect::<Vec<_>>();
+
+        let mut search = Search {
+            graph: self,
+            best_out: best_out.clone(),
+            last: if into_end.len() == 1 { Some(into_end[0]) } else { None },
+            best: None,
+            best_path: Vec::new(),
+            path: Vec::new(),
+        };
+        let remaining = best_out.iter().sum::<usize>() - best_out[0];
+        // one bit per junction
+        let mut visited = vec![0; n.div_ceil(64)];
+        visited[0] = 1;
+        search.dfs(0, &mut visited, 0, remaining);
+        let steps = search.best?;
+
+        let mut route = vec![self.nodes[0]];
+        let mut node = 0;
+        for &i in search.best_path.iter() {
+            let edge = &self.edges[node][i];
+            route.extend(edge.cells.iter().copied());
+            node = edge.to;
         }
+        Some(Hike { steps, route })
+    }
+
+    /// The junctions `hike` passes through, as node indices in order.
+    pub fn junctions_on(&self, hike: &Hike) -> Vec<usize> {
+        hike.route.iter()
+            .filter_map(|pos| self.nodes.iter().position(|n| n == pos))
+            .collect()
+    }
+}
 
-        // dead end
-        if new_positions.is_empty() {
-            return 0;
+/// Junctions by node index, labelled with their position, and corridors
+/// labelled with their length. Without slopes every corridor runs both
+/// ways, so it's drawn once in an undirected graph.
+impl ToDot for JunctionGraph {
+    fn to_graph(&self) -> Graph {
+        let undirected = self.edges.iter()
+            .enumerate()
+            .all(|(from, v)| v.iter().all(|e| self.edges[e.to].iter().any(|b| b.to == from)));
+        let mut graph = if undirected { Graph::undirected("trails") } else { Graph::directed("trails") };
+        for (i, pos) in self.nodes.iter().enumerate() {
+            let node = graph.node(&i.to_string());
+            node.label(&format!("{},{}", pos.row, pos.col));
+            if i < 2 {
+                node.set("shape", "doublecircle");
+            }
+        }
+        for (from, v) in self.edges.iter().enumerate() {
+            for e in v.iter().filter(|e| !undirected || from < e.to) {
+                graph.edge(&from.to_string(), &e.to.to_string()).label(&e.len().to_string());
+            }
         }
+        graph
+    }
+}
 
-        new_positions = new_positions.into_iter()
-            .filter(|pos| !path.contains(pos))
-            .filter(|pos| !dead_ends.contains(pos))
-            .filter(|pos| map[pos.row][pos.col] != '#')
-            .collect();
-
-        if new_positions.len() == 0 {
-            return 0;
-        } else if new_positions.len() == 1 {
-            let pos = new_positions[0];
-            path.insert(pos);
-            pre_pos = Some(start);
-            start = pos;
-
-            // println!("{:?}", pos);
-
-            if start == end {
-                return path.len();
-            }            
-        } else {
-            break;
+/// Both parts look for the longest hike, so there has to be one: the
+/// slopes mustn't cut the end off from the start.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let Ok(map) = input.parse::<TrailMap>() else {
+        return Assumption::unparsable("expected a grid of `#.^>v<` with one gap in the top and bottom rows");
+    };
+    let graph = map.junction_graph(true);
+    let mut seen = vec![false; graph.nodes.len()];
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        if !std::mem::replace(&mut seen[node], true) {
+            stack.extend(graph.edges[node].iter().map(|e| e.to));
         }
     }
+    vec![
+        Assumption::check("a hike reaches the end", seen[1], || "the slopes cut it off".to_string()),
+    ]
+}
 
-    {
-        new_positions.into_iter()
-            .map(|pos| {
-                let mut path = path.clone();
-                path.insert(pos);
-                let len = dfs_part_2(map, path, Some(start), pos, end, dead_ends);
+pub fn part_1(input: &str) -> usize {
+    let map: TrailMap = input.parse().unwrap();
+    map.junction_graph(true).longest_hike().unwrap().steps
+}
 
-                // dead end
-                if len == 0 {
-                    dead_ends.insert(pos);
-                    0
-                } else {
-                    len
-                }
-            })
-            .max()
-            .unwrap()
+/// Slopes are as easy to walk as paths.
+pub fn part_2(input: &str) -> usize {
+    let map: TrailMap = input.parse().unwrap();
+    map.junction_graph(false).longest_hike().unwrap().steps
+}
+
+#[test]
+fn test() {
+    let input = {"
+#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#
+".trim()};
+
+    let map: TrailMap = input.parse().unwrap();
+    assert_eq!(map.start(), Pos::new(0, 1));
+    assert_eq!(map.end(), Pos::new(22, 21));
+
+    let graph = map.junction_graph(true);
+    assert_eq!(graph.nodes.len(), 9);
+    let hike = graph.longest_hike().unwrap();
+    assert_eq!(hike.steps, 94);
+    assert_eq!(hike.route.len(), 95);
+    assert_eq!(hike.route.first(), Some(&map.start()));
+    assert_eq!(hike.route.last(), Some(&map.end()));
+    for w in hike.route.windows(2) {
+        assert_eq!(w[0].row.abs_diff(w[1].row) + w[0].col.abs_diff(w[1].col), 1);
     }
-}
\ No newline at end of file
+    let junctions = graph.junctions_on(&hike);
+    assert_eq!(junctions.first(), Some(&0));
+    assert_eq!(junctions.last(), Some(&1));
+    assert!(graph.to_dot().starts_with("digraph trails {"));
+
+    // Part Two
+    let hike = map.junction_graph(false).longest_hike().unwrap();
+    assert_eq!(hike.steps, 154);
+    assert!(map.junction_graph(false).to_dot().starts_with("graph trails {"));
+    let mut cells = hike.route.clone();
+    cells.sort();
+    cells.dedup();
+    assert_eq!(cells.len(), hike.route.len());
+
+    // more junctions than one word holds, in a line from start to end
+    let order = [0].into_iter().chain(2..200).chain([1]).collect::<Vec<_>>();
+    let nodes = (0..200).map(|i| Pos::new(i, 0)).collect::<Vec<_>>();
+    let mut edges = vec![Vec::new(); 200];
+    for w in order.windows(2) {
+        edges[w[0]].push(Edge { to: w[1], cells: vec![nodes[w[1]]] });
+    }
+    let hike = JunctionGraph { nodes, edges }.longest_hike().unwrap();
+    assert_eq!(hike.steps, 199);
+}
+
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let map: TrailMap = input.parse().unwrap();
+
+    let steps = map.junction_graph(true).longest_hike().unwrap().steps;
+    println!("steps: {:?}", steps);
+
+    // Part Two
+    let steps = map.junction_graph(false).longest_hike().unwrap().steps;
+    println!("steps: {:?}", steps);
+}
@@ -1,101 +1,187 @@
-use std::{str::FromStr, ops::Sub};
+use std::ops::RangeInclusive;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Pos {
-    x: i64,
-    y: i64,
-    z: i64,
+use num::{BigInt, BigRational, Signed, Zero};
+
+use crate::{check::Assumption, format::FromFormat, input, vec3::{BigVec3, Line, RatVec3, Vec3}};
+
+pub type Pos = Vec3<i64>;
+
+/// Where two hailstone paths cross and when each stone passes there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crossing {
+    pub point: RatVec3,
+    pub t_self: BigRational,
+    pub t_other: BigRational,
 }
 
-impl Pos {
-    fn new(x: i64, y: i64, z: i64) -> Self { Self { x, y, z } }
-    fn cross(&self, other: &Self) -> i64 {
-        self.x * other.y - self.y * other.x
+impl Crossing {
+    /// Neither stone has to go back in time to get there.
+    pub fn in_future(&self) -> bool {
+        !self.t_self.is_negative() && !self.t_other.is_negative()
     }
 }
 
-impl Sub for Pos {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromFormat)]
+#[format("{position} @ {velocity}")]
+pub struct Hailstone {
+    pub position: Pos,
+    pub velocity: Pos,
 }
 
-impl FromStr for Pos {
-    type Err = ();
+impl Hailstone {
+    pub fn new(position: Pos, velocity: Pos) -> Self { Self { position, velocity } }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut it = s.split(",").map(|s|s.trim()).filter(|s|!s.is_empty());
-        let x = it.next().unwrap().parse().unwrap();
-        let y = it.next().unwrap().parse().unwrap();
-        let z = it.next().unwrap().parse().unwrap();
-        Ok(Self::new(x, y, z))
+    pub fn path(&self) -> Line<i64> {
+        Line::new(self.position, self.velocity)
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-struct FPos {
-    x: f64,
-    y: f64,
-    z: f64,
-}
+    /// 返回碰撞点, looking only at x and y; `None` for parallel paths.
+    /// The crossing's `z` is where `self` is at that moment.
+    pub fn intersect_2d(&self, other: &Self) -> Option<Crossing> {
+        let (t_self, t_other) = self.path().intersect_xy(&other.path())?;
+        Some(Crossing { point: self.path().at(&t_self), t_self, t_other })
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Hailstone {
-    // position
-    position: Pos,
-    // velocity 
-    velocity: Pos,
-}
+    /// Where the two paths meet in space, at whatever times; `None` if they
+    /// are parallel or skew.
+    pub fn intersect_3d(&self, other: &Self) -> Option<Crossing> {
+        let (t_self, t_other) = self.path().intersect(&other.path())?;
+        Some(Crossing { point: self.path().at(&t_self), t_self, t_other })
+    }
 
-impl Hailstone {
-    fn new(position: Pos, velocity: Pos) -> Self { Self { position, velocity } }
-    /// 返回碰撞点
-    fn intersect_point(&self, other: &Self) -> Option<FPos> {
-        // a x b = x1 * y2 - x2 * y1
-        // if < 0 then b 在 a 的顺时针方向, =0 平行, > 0 b 在 a 的逆时针方向
-        let x1 = self.velocity.cross(&other.velocity);
-        if x1 == 0 {
-            return None;
+    /// Whether the two meet at the same place at the same time, now or later.
+    pub fn hits(&self, other: &Self) -> bool {
+        let dp = other.position.to_big() - self.position.to_big();
+        let dv = self.velocity.to_big() - other.velocity.to_big();
+        // dp = t * dv for a single t >= 0
+        if dv.is_zero() {
+            return dp.is_zero();
         }
-        let v = other.position - self.position;
-        let x2 = self.velocity.cross(&v);
+        dp.cross(&dv).is_zero() && !dp.dot(&dv).is_negative()
+    }
+}
 
-        // 符号相同
-        if x1.signum() * x2.signum() >= 0 {
-            return None;
-        }
+/// Pairs of hailstones whose paths cross inside the square `area` in x and y,
+/// in the future for both.
+pub fn count_crossings(hailstones: &[Hailstone], area: RangeInclusive<i64>) -> usize {
+    let min = BigRational::from_integer((*area.start()).into());
+    let max = BigRational::from_integer((*area.end()).into());
+    let inside = |v: &BigRational| *v >= min && *v <= max;
 
-        let x1 = other.velocity.cross(&self.velocity);
-        if x1 == 0 {
-            return None;
+    let mut sum = 0;
+    for i in 0..hailstones.len() {
+        for j in i+1..hailstones.len() {
+            if let Some(c) = hailstones[i].intersect_2d(&hailstones[j]) {
+                if c.in_future() && inside(&c.point.x) && inside(&c.point.y) {
+                    sum += 1;
+                }
+            }
         }
-        let v = self.position - other.position;
-        let x2 = other.velocity.cross(&v);
+    }
+    sum
+}
 
-        // 符号相同
-        if x1.signum() * x2.signum() >= 0 {
-            return None;
+/// Gaussian elimination; `None` if the system is singular.
+fn solve_linear(mut a: Vec<Vec<BigRational>>, mut b: Vec<BigRational>) -> Option<Vec<BigRational>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).find(|row| !a[*row][col].is_zero())?;
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (pivot_row, pivot_b) = (a[col].clone(), b[col].clone());
+        for row in 0..n {
+            if row == col || a[row][col].is_zero() { continue; }
+            let f = &a[row][col] / &pivot_row[col];
+            for (x, p) in a[row].iter_mut().zip(pivot_row.iter()).skip(col) {
+                *x -= &f * p;
+            }
+            b[row] -= &f * &pivot_b;
         }
+    }
+    Some((0..n).map(|i| &b[i] / &a[i][i]).collect())
+}
 
-        // 相交
-        {
-            let k1 = self.velocity.y as f64 / self.velocity.x as f64;
-            let n1 = self.position.y as f64 - self.position.x as f64 * k1;
-            let k2 = other.velocity.y as f64 / other.velocity.x as f64;
-            let n2 = other.position.y as f64 - other.position.x as f64 * k2;
+/// The rock that, thrown from `position` at `velocity`, hits every hailstone.
+///
+/// For each hailstone `(P - p) x (V - v) = 0`; the only nonlinear term,
+/// `P x V`, is shared by all of them, so subtracting two such equations
+/// leaves three linear ones. Two pairs give six equations for six unknowns,
+/// and the answer is checked against every other stone.
+pub fn throw_rock(hailstones: &[Hailstone]) -> Option<Hailstone> {
+    let rows = |i: usize, j: usize| {
+        let (a, b) = (&hailstones[i], &hailstones[j]);
+        let dv = a.velocity.to_big() - b.velocity.to_big();
+        let dp = a.position.to_big() - b.position.to_big();
+        let cross = |h: &Hailstone| h.position.to_big().cross(&h.velocity.to_big());
+        let c: BigVec3 = cross(a) - cross(b);
+        let z = BigInt::zero;
+        // unknowns: P.x P.y P.z V.x V.y V.z, for P x dv + dp x V = c
+        let coefficients = [
+            [z(), dv.z.clone(), -&dv.y, z(), -&dp.z, dp.y.clone()],
+            [-&dv.z, z(), dv.x.clone(), dp.z.clone(), z(), -&dp.x],
+            [dv.y.clone(), -&dv.x, z(), -&dp.y, dp.x.clone(), z()],
+        ];
+        coefficients.into_iter()
+            .zip(c.to_array())
+            .map(|(row, c)| {
+                (row.into_iter().map(BigRational::from_integer).collect::<Vec<_>>(), BigRational::from_integer(c))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let n = hailstones.len();
+    for k in 2..n {
+        for j in 1..k {
+            let (a, b): (Vec<_>, Vec<_>) = rows(0, j).into_iter().chain(rows(0, k)).unzip();
+            let Some(x) = solve_linear(a, b) else { continue; };
+            if x.iter().any(|v| !v.is_integer()) {
+                continue;
+            }
+            let Some(v) = x.iter().map(|v| i64::try_from(v.to_integer()).ok()).collect::<Option<Vec<_>>>() else { continue; };
+            let rock = Hailstone::new(Pos::new(v[0], v[1], v[2]), Pos::new(v[3], v[4], v[5]));
+            if hailstones.iter().all(|h| rock.hits(h)) {
+                return Some(rock);
+            }
+        }
+    }
+    None
+}
 
-            let x = (n2 - n1) / (k1 - k2);
-            let y = (k1 * n2 - k2 * n1) / (k1 - k2);
+pub fn parse(input: &str) -> Option<Vec<Hailstone>> {
+    input::lines(input).ok()?
+        .into_iter()
+        .map(|s| s.parse().ok())
+        .collect()
+}
 
-            // need to check if the crossed point is in the past for self or other
+/// Part one looks at the paths from above. Part two solves for the rock
+/// from three hailstones and checks the answer against the rest.
+pub fn assumptions(input: &str) -> Vec<Assumption> {
+    let hailstones = match input::lines(input) {
+        Ok(lines) => match lines.iter().map(|l| l.parse::<Hailstone>()).collect::<Result<Vec<_>, _>>() {
+            Ok(hailstones) => hailstones,
+            Err(e) => return Assumption::unparsable(e),
+        },
+        Err(e) => return Assumption::unparsable(e),
+    };
+    let still = hailstones.iter().position(|h| h.velocity.x == 0 && h.velocity.y == 0);
+    let rock = hailstones.len() < 3 || throw_rock(&hailstones).is_some();
+    vec![
+        Assumption::check("at least three hailstones", hailstones.len() >= 3, || format!("only {}", hailstones.len())),
+        Assumption::check("every hailstone moves in x and y", still.is_none(), || {
+            format!("the one on line {} only moves up or down", still.unwrap() + 1)
+        }),
+        Assumption::check("one rock hits them all", rock, || "no whole-number throw does".to_string()),
+    ]
+}
 
-            return Some(FPos{x, y, z: 0.})
-        }
+pub fn part_1(input: &str) -> usize {
+    count_crossings(&parse(input).unwrap(), 200000000000000..=400000000000000)
+}
 
-        None
-    }
+pub fn part_2(input: &str) -> i128 {
+    let p = throw_rock(&parse(input).unwrap()).unwrap().position;
+    p.x as i128 + p.y as i128 + p.z as i128
 }
 
 #[test]
@@ -108,58 +194,41 @@ fn test() {
 20, 19, 15 @  1, -5, -3
 ".trim()};
 
-    let input = include_str!("input");
-
-    let hailstones = input.split("\n")
-        .map(|s| {
-            let mut it = s.split(" @ ");
-            let position = it.next().unwrap().parse().unwrap();
-            let velocity = it.next().unwrap().parse().unwrap();
-            Hailstone::new(position, velocity)
-        })
-        .collect::<Vec<_>>();
+    let hailstones = parse(input).unwrap();
+    assert_eq!(count_crossings(&hailstones, 7..=27), 2);
 
+    let ratio = |n: i64, d: i64| BigRational::new(n.into(), d.into());
+    let c = hailstones[0].intersect_2d(&hailstones[1]).unwrap();
+    assert_eq!(c.point.x, ratio(43, 3));
+    assert_eq!(c.point.y, ratio(46, 3));
+    assert!(c.in_future());
+    assert!(hailstones[1].intersect_2d(&hailstones[2]).is_none());
+    assert!(!hailstones[0].intersect_2d(&hailstones[4]).unwrap().in_future());
 
-    let min = 200000000000000_f64;
-    let max = 400000000000000_f64;
-
-    let mut sum = 0;
-
-    for i in 0..hailstones.len() {
-        let stone = &hailstones[i];
-        for j in i+1..hailstones.len() {
-            if let Some(p) = stone.intersect_point(&hailstones[j]) {
-                // println!("A:{:?}, B:{:?} @ (x={},y={})", stone.position, hailstones[j].position, p.x, p.y);
-                if p.x > min && p.x < max && p.y > min && p.y < max {
-                    sum += 1;
-                }
-            }
-        }
+    // Part Two
+    let rock = throw_rock(&hailstones).unwrap();
+    assert_eq!(rock, Hailstone::new(Pos::new(24, 13, 10), Pos::new(-3, 1, 2)));
+    let p = rock.position;
+    assert_eq!(p.x + p.y + p.z, 47);
+
+    for h in hailstones.iter() {
+        let c = rock.intersect_3d(h).unwrap();
+        assert_eq!(c.t_self, c.t_other);
     }
+    let skew = Hailstone::new(Pos::new(0, 0, 1), Pos::new(0, 1, 0));
+    assert!(Hailstone::new(Pos::new(0, 0, 0), Pos::new(1, 0, 0)).intersect_3d(&skew).is_none());
+}
+
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let hailstones = parse(input).unwrap();
 
+    let sum = count_crossings(&hailstones, 200000000000000..=400000000000000);
     println!("{}", sum);
 
     // Part Two
-    let mut min_x = i64::MAX;
-    let mut min_y = i64::MAX;
-    let mut min_z = i64::MAX;
-    for stone in hailstones.iter() {
-        let pos = &stone.position;
-        min_x = min_x.min(pos.x);
-        min_y = min_y.min(pos.y);
-        min_z = min_z.min(pos.z);
-    }
-
-    let min_pos = Pos::new(min_x, min_y, min_z);
-    println!("{:?}", min_pos);
-
-    let mut hailstones = hailstones;
-    hailstones.iter_mut()
-        .for_each(|stone| {
-            let pos = stone.position - min_pos;
-            stone.position = pos;
-            println!("{}, {}, {} @ {}, {}, {}", pos.x, pos.y, pos.z, stone.velocity.x, stone.velocity.y, stone.velocity.z);
-        });
+    let rock = throw_rock(&hailstones).unwrap();
+    let p = rock.position;
+    println!("{:?}: {}", rock, p.x as i128 + p.y as i128 + p.z as i128);
 }
-
-
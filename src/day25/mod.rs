@@ -1,4 +1,193 @@
+use std::{collections::{HashMap, VecDeque}, str::FromStr};
 
+use crate::{dot::{Graph, ToDot}, input};
+
+/// Components and the wires between them, each wire stored once.
+#[derive(Debug, Clone, Default)]
+pub struct Wiring {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    wires: Vec<(usize, usize)>,
+    /// `(neighbour, wire)` for every component.
+    adj: Vec<Vec<(usize, usize)>>,
+}
+
+impl FromStr for Wiring {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut this = Self::default();
+        for line in input::lines(s).map_err(|_| ())? {
+            let (name, others) = line.split_once(':').ok_or(())?;
+            let from = this.component(name.trim())?;
+            for other in others.split_whitespace() {
+                let to = this.component(other)?;
+                if to == from {
+                    return Err(());
+                }
+                if !this.adj[from].iter().any(|(n, _)| *n == to) {
+                    let wire = this.wires.len();
+                    this.wires.push((from, to));
+                    this.adj[from].push((to, wire));
+                    this.adj[to].push((from, wire));
+                }
+            }
+        }
+        Ok(this)
+    }
+}
+
+/// A minimum set of wires splitting the components in two.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// Cut wires as component ids.
+    pub wires: Vec<(usize, usize)>,
+    /// Whether each component ends up on the same side as component 0.
+    pub side: Vec<bool>,
+}
+
+impl Cut {
+    pub fn sizes(&self) -> (usize, usize) {
+        let a = self.side.iter().filter(|s| **s).count();
+        (a, self.side.len() - a)
+    }
+
+    pub fn product(&self) -> usize {
+        let (a, b) = self.sizes();
+        a * b
+    }
+
+    /// Draws the cut wires red and the two sides in different colours, to
+    /// check against `neato`/`sfdp` output.
+    pub fn style(&self, wiring: &Wiring, graph: &mut Graph) {
+        for (id, side) in self.side.iter().enumerate() {
+            let color = if *side { "lightblue" } else { "palegreen" };
+            graph.node(wiring.name(id)).set("style", "filled").set("fillcolor", color);
+        }
+        let wires = self.wires.iter()
+            .map(|(a, b)| (wiring.name(*a), wiring.name(*b)))
+            .collect::<Vec<_>>();
+        graph.highlight_edges(&wires, "red");
+    }
+}
+
+impl Wiring {
+    fn component(&mut self, name: &str) -> Result<usize, ()> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(());
+        }
+        if let Some(&id) = self.index.get(name) {
+            return Ok(id);
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), id);
+        self.adj.push(Vec::new());
+        Ok(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn wires(&self) -> &[(usize, usize)] {
+        &self.wires
+    }
+
+    /// Edmonds–Karp with every wire of capacity one, stopping once the flow
+    /// reaches `limit`. Returns the flow and, if it stayed below `limit`,
+    /// which components the source can still reach in the residual graph.
+    fn max_flow(&self, s: usize, t: usize, limit: usize) -> (usize, Option<Vec<bool>>) {
+        // +1: one unit from `wires[i].0` to `wires[i].1`, -1 the other way
+        let mut flow = vec![0_i8; self.wires.len()];
+        let residual = |flow: &[i8], u: usize, wire: usize| {
+            if self.wires[wire].0 == u { flow[wire] < 1 } else { flow[wire] > -1 }
+        };
+
+        let mut total = 0;
+        loop {
+            if total >= limit {
+                return (total, None);
+            }
+            let mut prev = vec![None; self.len()];
+            let mut seen = vec![false; self.len()];
+            let mut queue = VecDeque::new();
+            seen[s] = true;
+            queue.push_back(s);
+            while let Some(u) = queue.pop_front() {
+                if u == t {
+                    break;
+                }
+                for &(v, wire) in self.adj[u].iter() {
+                    if !seen[v] && residual(&flow, u, wire) {
+                        seen[v] = true;
+                        prev[v] = Some((u, wire));
+                        queue.push_back(v);
+                    }
+                }
+            }
+            if !seen[t] {
+                return (total, Some(seen));
+            }
+
+            let mut v = t;
+            while let Some((u, wire)) = prev[v] {
+                flow[wire] += if self.wires[wire].0 == u { 1 } else { -1 };
+                v = u;
+            }
+            total += 1;
+        }
+    }
+
+    /// Global minimum cut: the smallest of the `0`-`t` max flows over every
+    /// other component `t`. `None` with fewer than two components.
+    pub fn min_cut(&self) -> Option<Cut> {
+        let mut best: Option<(usize, Vec<bool>)> = None;
+        for t in 1..self.len() {
+            let limit = best.as_ref().map_or(usize::MAX, |(f, _)| *f);
+            if let (f, Some(side)) = self.max_flow(0, t, limit) {
+                best = Some((f, side));
+                if f == 0 {
+                    break;
+                }
+            }
+        }
+        let (_, side) = best?;
+        let wires = self.wires.iter()
+            .filter(|(a, b)| side[*a] != side[*b])
+            .copied()
+            .collect();
+        Some(Cut { wires, side })
+    }
+}
+
+impl ToDot for Wiring {
+    fn to_graph(&self) -> Graph {
+        let mut graph = Graph::undirected("wiring");
+        for &(a, b) in self.wires.iter() {
+            graph.edge(&self.names[a], &self.names[b]);
+        }
+        graph
+    }
+}
+
+
+pub fn part_1(input: &str) -> usize {
+    let wiring: Wiring = input.parse().unwrap();
+    wiring.min_cut().unwrap().product()
+}
 
 #[test]
 fn test() {
@@ -17,5 +206,47 @@ lsr: lhk
 rzs: qnr cmg lsr rsh
 frs: qnr lhk lsr
     ".trim()};
-    
+
+    let wiring: Wiring = input.parse().unwrap();
+    assert_eq!(wiring.len(), 15);
+    assert_eq!(wiring.wires().len(), 33);
+
+    let cut = wiring.min_cut().unwrap();
+    let mut wires = cut.wires.iter()
+        .map(|(a, b)| {
+            let mut pair = [wiring.name(*a), wiring.name(*b)];
+            pair.sort();
+            pair
+        })
+        .collect::<Vec<_>>();
+    wires.sort();
+    assert_eq!(wires, vec![["bvb", "cmg"], ["hfx", "pzl"], ["jqt", "nvd"]]);
+    assert_eq!(cut.product(), 54);
+
+    assert!(!wiring.to_dot().contains("color"));
+    let mut graph = wiring.to_graph();
+    cut.style(&wiring, &mut graph);
+    let dot = graph.to_string();
+    assert_eq!(dot.matches("color=red").count(), 3);
+    assert!(dot.contains("\"jqt\" -- \"nvd\" [color=red, penwidth=3];"));
+    assert!(dot.contains("\"jqt\" [style=filled, fillcolor=lightblue];"));
+
+    // already split in two
+    let wiring: Wiring = "a: b\nc: d e".parse().unwrap();
+    let cut = wiring.min_cut().unwrap();
+    assert!(cut.wires.is_empty());
+    assert_eq!(cut.sizes(), (2, 3));
+
+    assert!("a: a".parse::<Wiring>().is_err());
+    assert!("a b".parse::<Wiring>().is_err());
+}
+
+#[test]
+fn solve() {
+    let input = include_str!("input");
+    let wiring: Wiring = input.parse().unwrap();
+
+    let cut = wiring.min_cut().unwrap();
+    assert_eq!(cut.wires.len(), 3);
+    println!("{}", cut.product());
 }
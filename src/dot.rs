@@ -0,0 +1,325 @@
+//! Graphviz DOT export for the puzzles that are graphs in disguise.
+//!
+//! Each day builds a [`Graph`] through [`ToDot`]; styling is applied
+//! afterwards by id, so the same graph can have a path or a cut highlighted
+//! before it's rendered.
+
+use std::{collections::HashMap, fmt};
+
+use crate::{day08, day19, day20, day22, day23, day25};
+
+type Attrs = Vec<(String, String)>;
+
+fn set(attrs: &mut Attrs, key: &str, value: &str) {
+    match attrs.iter_mut().find(|(k, _)| k == key) {
+        Some((_, v)) => *v = value.to_string(),
+        None => attrs.push((key.to_string(), value.to_string())),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub id: String,
+    pub attrs: Attrs,
+}
+
+impl Node {
+    /// Sets an attribute, replacing any earlier value.
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        set(&mut self.attrs, key, value);
+        self
+    }
+
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.set("label", label)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub attrs: Attrs,
+}
+
+impl Edge {
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        set(&mut self.attrs, key, value);
+        self
+    }
+
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.set("label", label)
+    }
+}
+
+/// A `digraph` or `graph`, rendered by `Display`.
+///
+/// Nodes keep the order they were first mentioned in; edges don't create
+/// nodes, Graphviz does that for any id without a line of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    name: String,
+    directed: bool,
+    attrs: Attrs,
+    nodes: Vec<Node>,
+    index: HashMap<String, usize>,
+    edges: Vec<Edge>,
+}
+
+impl Graph {
+    pub fn directed(name: &str) -> Self {
+        Self::new(name, true)
+    }
+
+    pub fn undirected(name: &str) -> Self {
+        Self::new(name, false)
+    }
+
+    fn new(name: &str, directed: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            directed,
+            attrs: Vec::new(),
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Graph-wide attribute such as `rankdir`.
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        set(&mut self.attrs, key, value);
+        self
+    }
+
+    /// The node with this id, added if it's new.
+    pub fn node(&mut self, id: &str) -> &mut Node {
+        let i = *self.index.entry(id.to_string()).or_insert_with(|| {
+            self.nodes.push(Node { id: id.to_string(), attrs: Vec::new() });
+            self.nodes.len() - 1
+        });
+        &mut self.nodes[i]
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str) -> &mut Edge {
+        self.edges.push(Edge { from: from.to_string(), to: to.to_string(), attrs: Vec::new() });
+        self.edges.last_mut().unwrap()
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Edges from `from` to `to`, either way round if undirected.
+    pub fn edges_between<'a>(&'a mut self, from: &'a str, to: &'a str) -> impl Iterator<Item = &'a mut Edge> + 'a {
+        let directed = self.directed;
+        self.edges.iter_mut().filter(move |e| {
+            (e.from == from && e.to == to) || (!directed && e.from == to && e.to == from)
+        })
+    }
+
+    pub fn highlight_nodes<S: AsRef<str>>(&mut self, ids: &[S], color: &str) {
+        for id in ids {
+            self.node(id.as_ref()).set("color", color).set("penwidth", "3");
+        }
+    }
+
+    pub fn highlight_edges<S: AsRef<str>>(&mut self, pairs: &[(S, S)], color: &str) {
+        for (from, to) in pairs {
+            for e in self.edges_between(from.as_ref(), to.as_ref()) {
+                e.set("color", color).set("penwidth", "3");
+            }
+        }
+    }
+
+    /// Every node of `path` and the edges between consecutive ones.
+    pub fn highlight_path<S: AsRef<str>>(&mut self, path: &[S], color: &str) {
+        self.highlight_nodes(path, color);
+        let pairs = path.windows(2)
+            .map(|w| (w[0].as_ref(), w[1].as_ref()))
+            .collect::<Vec<_>>();
+        self.highlight_edges(&pairs, color);
+    }
+}
+
+/// Quotes `s` unless Graphviz would read it as a plain id or number.
+fn quote(s: &str) -> String {
+    let id = s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !s.starts_with(|c: char| c.is_ascii_digit());
+    let number = s.strip_prefix('-').unwrap_or(s).chars().all(|c| c.is_ascii_digit() || c == '.')
+        && s.matches('.').count() <= 1
+        && s.contains(|c: char| c.is_ascii_digit());
+    let plain = !s.is_empty() && (id || number);
+    if plain {
+        s.to_string()
+    } else {
+        format!("\"{}\"", s.replace('"', "\\\"").replace('\n', "\\n"))
+    }
+}
+
+fn attr_list(attrs: &Attrs) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+    let list = attrs.iter()
+        .map(|(k, v)| format!("{}={}", k, quote(v)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(" [{}]", list)
+}
+
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, arrow) = if self.directed { ("digraph", "->") } else { ("graph", "--") };
+        writeln!(f, "{} {} {{", kind, quote(&self.name))?;
+        for (k, v) in self.attrs.iter() {
+            writeln!(f, "    {}={};", k, quote(v))?;
+        }
+        for node in self.nodes.iter() {
+            writeln!(f, "    \"{}\"{};", node.id.replace('"', "\\\""), attr_list(&node.attrs))?;
+        }
+        for edge in self.edges.iter() {
+            writeln!(f, "    \"{}\" {} \"{}\"{};",
+                edge.from.replace('"', "\\\""), arrow, edge.to.replace('"', "\\\""), attr_list(&edge.attrs))?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+pub trait ToDot {
+    fn to_graph(&self) -> Graph;
+
+    fn to_dot(&self) -> String {
+        self.to_graph().to_string()
+    }
+}
+
+/// Days with a DOT export.
+pub const DAYS: [u32; 6] = [8, 19, 20, 22, 23, 25];
+
+/// Parses `input` as the puzzle of `day` and renders it; with `highlight`,
+/// whatever answers that day is drawn on top: the walk from `AAA` to `ZZZ`,
+/// workflows no part can reach, the subgraphs feeding `rx`, bricks safe to
+/// disintegrate, the longest hike or the three wires to cut.
+pub fn export(day: u32, input: &str, highlight: bool) -> Result<String, String> {
+    let bad_input = || format!("day {}: can't parse input", day);
+    let graph = match day {
+        8 => {
+            let network = day08::Network::parse(input).ok_or_else(bad_input)?;
+            let mut graph = network.to_graph();
+            if highlight {
+                if let Some(path) = network.walk("AAA", |n| n == "ZZZ") {
+                    graph.highlight_path(&path, "red");
+                }
+            }
+            graph
+        },
+        19 => {
+            let system: day19::System = input.parse().map_err(|_| bad_input())?;
+            let mut graph = system.to_graph();
+            if highlight {
+                let engine = day19::Engine::xmas(&system.workflows).map_err(|e| e.to_string())?;
+                for label in engine.unreachable_workflows() {
+                    graph.node(label).set("style", "dashed").set("color", "gray");
+                }
+            }
+            graph
+        },
+        20 => {
+            let circuit: day20::Circuit = input.parse().map_err(|_| bad_input())?;
+            let mut graph = circuit.to_graph();
+            if highlight {
+                let colors = ["red", "blue", "darkgreen", "orange", "purple", "brown"];
+                for (sub, color) in circuit.feeding_subgraphs("rx").map(|(_, v)| v).unwrap_or_default().iter().zip(colors.iter().cycle()) {
+                    graph.highlight_nodes(&sub.modules, color);
+                }
+            }
+            graph
+        },
+        22 => {
+            let stack: day22::SandStack = input.parse().map_err(|_| bad_input())?;
+            let mut graph = stack.to_graph();
+            if highlight {
+                let safe = stack.safe_to_disintegrate()
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>();
+                graph.highlight_nodes(&safe, "darkgreen");
+            }
+            graph
+        },
+        23 => {
+            let map: day23::TrailMap = input.parse().map_err(|_| bad_input())?;
+            let graph = map.junction_graph(true);
+            let mut dot = graph.to_graph();
+            if highlight {
+                if let Some(hike) = graph.longest_hike() {
+                    let path = graph.junctions_on(&hike)
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>();
+                    dot.highlight_path(&path, "red");
+                }
+            }
+            dot
+        },
+        25 => {
+            let wiring: day25::Wiring = input.parse().map_err(|_| bad_input())?;
+            let mut graph = wiring.to_graph();
+            if highlight {
+                if let Some(cut) = wiring.min_cut() {
+                    cut.style(&wiring, &mut graph);
+                }
+            }
+            graph
+        },
+        _ => return Err(format!("day {} has no graph to export", day)),
+    };
+    Ok(graph.to_string())
+}
+
+#[test]
+fn test() {
+    let mut graph = Graph::directed("g");
+    graph.set("rankdir", "LR");
+    graph.node("a").label("&a").set("shape", "box");
+    graph.node("b");
+    graph.edge("a", "b").label("x<3");
+    graph.edge("b", "c");
+    graph.node("a").set("shape", "diamond");
+    assert_eq!(graph.to_string(), "\
+digraph g {
+    rankdir=LR;
+    \"a\" [label=\"&a\", shape=diamond];
+    \"b\";
+    \"a\" -> \"b\" [label=\"x<3\"];
+    \"b\" -> \"c\";
+}
+");
+
+    graph.highlight_path(&["a", "b", "c"], "red");
+    assert_eq!(graph.nodes().len(), 3);
+    assert!(graph.edges().iter().all(|e| e.attrs.contains(&("color".into(), "red".into()))));
+
+    // undirected edges match either way round
+    let mut graph = Graph::undirected("g");
+    graph.edge("a", "b");
+    graph.highlight_edges(&[("b", "a")], "red");
+    assert_eq!(graph.to_string(), "graph g {\n    \"a\" -- \"b\" [color=red, penwidth=3];\n}\n");
+
+    assert_eq!(quote("12"), "12");
+    assert_eq!(quote("1,2"), "\"1,2\"");
+    assert_eq!(quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+
+    assert!(export(1, "", false).is_err());
+    assert!(export(25, "a b", false).is_err());
+}
@@ -0,0 +1,205 @@
+//! Runtime side of `#[derive(FromFormat)]`.
+//!
+//! The derive turns `#[format("{name} = ({left}, {right})")]` into calls
+//! on a [`Scanner`], which walks the input and says what went wrong, and
+//! where, if it doesn't fit the pattern.
+
+use std::{fmt, str::FromStr};
+
+pub use format_derive::FromFormat;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// `literal` isn't at column `at` (1-based).
+    Expected { literal: &'static str, at: usize },
+    /// Nothing after `field` matched the `literal` that should end it.
+    Missing { literal: &'static str, field: &'static str },
+    /// `value` didn't parse as `field`'s type.
+    Field { field: &'static str, value: String, message: String },
+    /// The pattern is done but the input goes on from column `at`.
+    Trailing { at: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    pub pattern: &'static str,
+    pub input: String,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::Expected { literal, at } => write!(f, "expected {literal:?} at column {at}")?,
+            ErrorKind::Missing { literal, field } => write!(f, "no {literal:?} after `{{{field}}}`")?,
+            ErrorKind::Field { field, value, message } => {
+                write!(f, "`{{{field}}}` can't be {value:?}")?;
+                if message != "()" {
+                    write!(f, ": {message}")?;
+                }
+            }
+            ErrorKind::Trailing { at } => write!(f, "unexpected text from column {at}")?,
+        }
+        write!(f, " in {:?}, reading {:?}", self.input, self.pattern)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Walks `input` along a pattern, left to right.
+#[derive(Debug)]
+pub struct Scanner<'a> {
+    pattern: &'static str,
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(pattern: &'static str, input: &'a str) -> Self {
+        Self { pattern, input, pos: 0 }
+    }
+
+    fn error(&self, kind: ErrorKind) -> FormatError {
+        FormatError { pattern: self.pattern, input: self.input.to_string(), kind }
+    }
+
+    /// Where `literal` would end if it matched at `at`. A space in it
+    /// matches one or more whitespace characters.
+    fn match_at(&self, at: usize, literal: &str) -> Option<usize> {
+        let mut rest = self.input[at..].chars().peekable();
+        let mut end = at;
+        let mut lit = literal.chars().peekable();
+        while let Some(c) = lit.next() {
+            if c == ' ' {
+                while lit.peek() == Some(&' ') {
+                    lit.next();
+                }
+                if !rest.peek()?.is_whitespace() {
+                    return None;
+                }
+                while let Some(w) = rest.next_if(|w| w.is_whitespace()) {
+                    end += w.len_utf8();
+                }
+            } else {
+                if rest.next()? != c {
+                    return None;
+                }
+                end += c.len_utf8();
+            }
+        }
+        Some(end)
+    }
+
+    pub fn literal(&mut self, literal: &'static str) -> Result<(), FormatError> {
+        match self.match_at(self.pos, literal) {
+            Some(end) => {
+                self.pos = end;
+                Ok(())
+            }
+            None => Err(self.error(ErrorKind::Expected { literal, at: self.pos + 1 })),
+        }
+    }
+
+    /// Everything up to the next `literal`, which is skipped over too.
+    pub fn until(&mut self, field: &'static str, literal: &'static str) -> Result<&'a str, FormatError> {
+        let start = self.pos;
+        let found = self.input[start..].char_indices()
+            .map(|(i, _)| start + i)
+            .find_map(|at| Some((at, self.match_at(at, literal)?)));
+        match found {
+            Some((at, end)) => {
+                self.pos = end;
+                Ok(&self.input[start..at])
+            }
+            None => Err(self.error(ErrorKind::Missing { literal, field })),
+        }
+    }
+
+    pub fn rest(&mut self) -> &'a str {
+        let start = self.pos;
+        self.pos = self.input.len();
+        &self.input[start..]
+    }
+
+    pub fn finish(&self) -> Result<(), FormatError> {
+        if self.pos == self.input.len() {
+            Ok(())
+        } else {
+            Err(self.error(ErrorKind::Trailing { at: self.pos + 1 }))
+        }
+    }
+
+    pub fn field<T: FromStr>(&self, field: &'static str, value: &str) -> Result<T, FormatError>
+    where
+        T::Err: fmt::Debug,
+    {
+        value.trim().parse().map_err(|err| self.error(ErrorKind::Field {
+            field,
+            value: value.to_string(),
+            message: format!("{err:?}"),
+        }))
+    }
+
+    /// `value` split on `sep`, each piece trimmed and parsed; a `sep` of
+    /// just whitespace splits on any run of it.
+    pub fn list<T: FromStr>(&self, field: &'static str, value: &str, sep: &str) -> Result<Vec<T>, FormatError>
+    where
+        T::Err: fmt::Debug,
+    {
+        if value.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        if sep.trim().is_empty() {
+            value.split_whitespace().map(|s| self.field(field, s)).collect()
+        } else {
+            value.split(sep).map(|s| self.field(field, s)).collect()
+        }
+    }
+}
+
+#[test]
+fn test() {
+    #[derive(Debug, PartialEq, FromFormat)]
+    #[format("{name} = ({left}, {right})")]
+    struct Node<'a> {
+        name: &'a str,
+        left: &'a str,
+        right: &'a str,
+    }
+
+    #[derive(Debug, PartialEq, FromFormat)]
+    #[format("Card {id}: {{{winning: } | {have: }}}")]
+    struct Card {
+        id: u32,
+        winning: Vec<u32>,
+        have: Vec<u32>,
+    }
+
+    assert_eq!(
+        Node::from_format("GXT = (MQM, CHN)"),
+        Ok(Node { name: "GXT", left: "MQM", right: "CHN" }),
+    );
+    assert_eq!(
+        "Card   1: {41 48 | 83  86 6}".parse(),
+        Ok(Card { id: 1, winning: vec![41, 48], have: vec![83, 86, 6] }),
+    );
+    assert_eq!("Card 2: { | }".parse::<Card>().unwrap().have, vec![]);
+
+    let err = Node::from_format("GXT = MQM, CHN)").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Missing { literal: " = (", field: "name" });
+    assert_eq!(
+        err.to_string(),
+        r#"no " = (" after `{name}` in "GXT = MQM, CHN)", reading "{name} = ({left}, {right})""#,
+    );
+    let err = "Cart 1: {1 | 2}".parse::<Card>().unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Expected { literal: "Card ", at: 1 });
+    let err = Node::from_format("GXT = (MQM CHN)").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Missing { literal: ", ", field: "left" });
+    let err = Node::from_format("GXT = (MQM, CHN) x").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Trailing { at: 17 });
+    let err = "Card 1: {41 | 8x}".parse::<Card>().unwrap_err();
+    assert!(matches!(&err.kind, ErrorKind::Field { field: "have", value, .. } if value == "8x"));
+    assert!(err.to_string().starts_with(r#"`{have}` can't be "8x": ParseIntError"#));
+    let err = "Card 1: {1 | 2}!".parse::<Card>().unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Trailing { at: 16 });
+}
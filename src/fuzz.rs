@@ -0,0 +1,142 @@
+//! Every day's parser, and its solvers where that's quick, run on
+//! arbitrary input.
+//!
+//! The parsers have to take anything: text that was never a puzzle input,
+//! a grid with a multi-byte character in it, a number too big for its type.
+//! They say no rather than panic. A solver only gets input its parser took,
+//! that's at most [`SOLVE_LIMIT`] bytes, and that the day's
+//! [`check`](crate::check) assumptions hold for; even then some parts take
+//! exponential time or never finish on the wrong input, and are skipped.
+//!
+//! cargo fuzz builds with overflow checks, as do the tests, so a number
+//! too big for the arithmetic a solver does on it has to be turned away by
+//! the parser.
+//!
+//! The libFuzzer targets in `fuzz/` call [`run`]. The test here does the
+//! same on the bundled inputs, cut down and mutated.
+
+use std::{ops::RangeInclusive, str};
+
+use crate::{check, runner, *};
+
+pub const DAYS: RangeInclusive<u32> = 1..=25;
+
+/// Inputs longer than this are parsed, but not solved.
+pub const SOLVE_LIMIT: usize = 1024;
+
+/// Whether `day`'s parser takes `input`.
+pub fn parses(day: u32, input: &str) -> bool {
+    match day {
+        1 => day01::parse(input).is_some(),
+        2 => day02::parse(input).is_some(),
+        3 => day03::parse(input).is_some(),
+        4 => day04::parse(input).is_some(),
+        5 => day05::parse(input).is_some(),
+        6 => day06::parse(input).is_some(),
+        7 => day07::parse(input).is_some(),
+        8 => day08::Network::parse(input).is_some(),
+        9 => day09::parse(input).is_some(),
+        10 => day10::parse_maze(input).is_some(),
+        11 => day11::parse(input).is_some(),
+        12 => day12::parse(input).is_some(),
+        13 => day13::parse(input).is_some(),
+        14 => day14::parse(input).is_some(),
+        15 => day15::parse_sequence(input).is_ok(),
+        16 => input.parse::<day16::Contraption>().is_ok(),
+        17 => input.parse::<day17::HeatMap>().is_ok(),
+        18 => input.parse::<day18::DigPlan>().is_ok(),
+        19 => input.parse::<day19::System>().is_ok(),
+        20 => input.parse::<day20::Circuit>().is_ok(),
+        21 => input.parse::<day21::Garden>().is_ok(),
+        22 => input.parse::<day22::SandStack>().is_ok(),
+        23 => input.parse::<day23::TrailMap>().is_ok(),
+        24 => day24::parse(input).is_some(),
+        25 => input.parse::<day25::Wiring>().is_ok(),
+        _ => false,
+    }
+}
+
+/// The parts worth solving on any small input that parses.
+fn parts(day: u32) -> &'static [u8] {
+    match day {
+        // tries every way of placing the damaged springs
+        12 => &[],
+        _ => &[1, 2],
+    }
+}
+
+/// Parses `data` as `day`'s input and, if it's small and fits the
+/// solvers' assumptions, solves it. Panics on a bug, never on bad input.
+pub fn run(day: u32, data: &[u8]) {
+    let Ok(input) = str::from_utf8(data) else { return };
+    if !parses(day, input) || input.len() > SOLVE_LIMIT {
+        return;
+    }
+    if check::DAYS.contains(&day) && !check::assumptions(day, input).is_ok_and(|v| v.iter().all(|a| a.holds())) {
+        return;
+    }
+    for &part in parts(day) {
+        if let Some(solve) = runner::solver(day, part) {
+            solve(input);
+        }
+    }
+}
+
+/// Things that tend to upset a parser: the puzzles' own punctuation,
+/// multi-byte characters, numbers at the edge of `u32` and `i64`, and
+/// numbers too big for anything.
+#[cfg(test)]
+const PIECES: [&str; 34] = [
+    "0", "1", "9", "-", " ", "\t", "\n", "\r\n", ",", ":", "=", "{", "}", "#", ".", "?", "S", "O",
+    "|", "/", "\\", "^", "v", "<", ">", "%", "&", "->", "@", "~", "é€",
+    "4294967295", "9223372036854775807", "99999999999999999999999",
+];
+
+/// A run of the input's lines, with a few characters changed.
+#[cfg(test)]
+fn mutate(rng: &mut prop::Rng, input: &str) -> String {
+    let lines = input.lines().collect::<Vec<_>>();
+    let start = rng.size(0..=lines.len() - 1);
+    let len = if rng.chance(1, 4) { lines.len() } else { rng.size(1..=12) };
+    let mut chars = lines[start..].iter().take(len)
+        .flat_map(|l| l.chars().chain(['\n']))
+        .collect::<Vec<_>>();
+    for _ in 0..rng.size(0..=6) {
+        let at = rng.size(0..=chars.len());
+        match rng.below(3) {
+            0 if at < chars.len() => { chars.remove(at); },
+            1 if at < chars.len() => chars[at] = rng.pick(&PIECES).chars().next().unwrap(),
+            _ => chars.splice(at..at, rng.pick(&PIECES).chars()).for_each(drop),
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// Without a line, or else without a character.
+#[cfg(test)]
+fn shrink(input: &str) -> Vec<String> {
+    let lines = input.split_inclusive('\n').collect::<Vec<_>>();
+    let mut ret = prop::shrink_vec(&lines, 1, |_| vec![]).into_iter()
+        .map(|v| v.concat())
+        .collect::<Vec<_>>();
+    if input.len() < 256 {
+        ret.extend(input.char_indices().map(|(i, c)| input[..i].to_string() + &input[i + c.len_utf8()..]));
+    }
+    ret
+}
+
+#[test]
+fn test() {
+    for day in DAYS {
+        let input = std::fs::read_to_string(format!("src/day{:02}/input", day)).unwrap();
+        assert!(parses(day, &input), "day {} doesn't parse its own input", day);
+        let config = prop::Config::default();
+        let result = prop::check(&config, |rng| mutate(rng, &input), |s: &String| shrink(s), |input| {
+            run(day, input.as_bytes());
+            Ok(())
+        });
+        if let Err(failure) = result {
+            panic!("day {}: {}", day, failure);
+        }
+    }
+}
@@ -0,0 +1,868 @@
+//! Made-up puzzle inputs of any size, for seeing how the solvers scale.
+//!
+//! Every generator builds its input the way the real ones are built, so
+//! what comes out parses and passes the day's [`check`](crate::check)
+//! assumptions. At a scale of 1 an input is about as big as the bundled
+//! one: as many lines, or for a grid as many cells, so its sides grow with
+//! the square root of the scale. Days 6 and 20 stay the same size, as
+//! their answers would stop fitting anything bigger.
+
+use std::collections::HashSet;
+
+use crate::prop::Rng;
+
+pub const DAYS: std::ops::RangeInclusive<u32> = 1..=25;
+
+/// The days with something that [`Options::density`] makes thicker on the
+/// ground: symbols, galaxies, rocks and mirrors.
+pub const DENSITY_DAYS: [u32; 5] = [3, 11, 14, 16, 21];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    pub seed: u64,
+    /// How much bigger than the real input.
+    pub scale: f64,
+    /// The fraction of cells taken by obstacles, for [`DENSITY_DAYS`];
+    /// each day has its own default.
+    pub density: Option<f64>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { seed: 0, scale: 1.0, density: None }
+    }
+}
+
+impl Options {
+    /// `base` things, scaled.
+    fn count(&self, base: usize) -> usize {
+        ((base as f64 * self.scale).round() as usize).max(1)
+    }
+
+    /// The side of a square `base` on a side, scaled by area.
+    fn side(&self, base: usize, min: usize) -> usize {
+        ((base as f64 * self.scale.sqrt()).round() as usize).max(min)
+    }
+
+    fn density(&self, default: f64) -> f64 {
+        self.density.unwrap_or(default)
+    }
+}
+
+/// An input for `day`.
+pub fn generate(day: u32, options: &Options) -> Result<String, String> {
+    if !(options.scale > 0.0 && options.scale.is_finite()) {
+        return Err(format!("scale must be positive, not {}", options.scale));
+    }
+    if let Some(density) = options.density {
+        if !DENSITY_DAYS.contains(&day) {
+            return Err(format!("day {} has no density; try one of {:?}", day, DENSITY_DAYS));
+        }
+        if !(0.0..=1.0).contains(&density) {
+            return Err(format!("density must be between 0 and 1, not {}", density));
+        }
+    }
+    let rng = &mut Rng::new(options.seed);
+    let lines = match day {
+        1 => calibration(rng, options),
+        2 => games(rng, options),
+        3 => schematic(rng, options),
+        4 => scratchcards(rng, options),
+        5 => almanac(rng, options),
+        6 => races(rng),
+        7 => hands(rng, options),
+        8 => network(rng, options),
+        9 => histories(rng, options),
+        10 => pipe_maze(rng, options),
+        11 => galaxies(rng, options),
+        12 => springs(rng, options),
+        13 => mirrors(rng, options),
+        14 => platform(rng, options),
+        15 => init_sequence(rng, options),
+        16 => contraption(rng, options),
+        17 => heat_map(rng, options),
+        18 => dig_plan(rng, options),
+        19 => workflows(rng, options),
+        20 => circuit(rng),
+        21 => garden(rng, options),
+        22 => bricks(rng, options),
+        23 => trails(rng, options),
+        24 => hailstones(rng, options),
+        25 => wiring(rng, options),
+        _ => return Err(format!("not a day: {}", day)),
+    };
+    Ok(lines.join("\n") + "\n")
+}
+
+fn shuffle<T>(rng: &mut Rng, v: &mut [T]) {
+    for i in (1..v.len()).rev() {
+        v.swap(i, rng.below(i as u64 + 1) as usize);
+    }
+}
+
+/// `k` different numbers out of `range`, in no particular order.
+fn distinct(rng: &mut Rng, k: usize, range: std::ops::RangeInclusive<i64>) -> Vec<i64> {
+    let mut seen = HashSet::new();
+    while seen.len() < k {
+        seen.insert(rng.range(range.clone()));
+    }
+    let mut v = seen.into_iter().collect::<Vec<_>>();
+    v.sort_unstable();
+    shuffle(rng, &mut v);
+    v
+}
+
+fn letter(rng: &mut Rng, letters: &[u8]) -> char {
+    *rng.pick(letters) as char
+}
+
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// `n` different names, at least `min_len` long, ending in one of `last`;
+/// longer if there are too many for that.
+fn names(rng: &mut Rng, n: usize, min_len: usize, letters: &[u8], last: &[u8], taken: &HashSet<String>) -> Vec<String> {
+    let mut len = min_len.max(1);
+    while (letters.len() as f64).powi(len as i32 - 1) * (last.len() as f64) < 4.0 * (n + taken.len()) as f64 {
+        len += 1;
+    }
+    let mut seen = taken.clone();
+    let mut ret = Vec::new();
+    while ret.len() < n {
+        let mut name = (1..len).map(|_| letter(rng, letters)).collect::<String>();
+        name.push(letter(rng, last));
+        if seen.insert(name.clone()) {
+            ret.push(name);
+        }
+    }
+    ret
+}
+
+/// A grid of `fill`, `side` on a side, with each cell replaced by one of
+/// `obstacles` at `density`.
+fn scatter(rng: &mut Rng, h: usize, w: usize, fill: char, obstacles: &[u8], density: f64) -> Vec<Vec<char>> {
+    let per_million = (density * 1e6) as u64;
+    (0..h)
+        .map(|_| (0..w).map(|_| if rng.below(1_000_000) < per_million { letter(rng, obstacles) } else { fill }).collect())
+        .collect()
+}
+
+fn render(grid: &[Vec<char>]) -> Vec<String> {
+    grid.iter().map(|row| row.iter().collect()).collect()
+}
+
+/// Letters with a digit, or a digit spelled out, somewhere in them.
+fn calibration(rng: &mut Rng, options: &Options) -> Vec<String> {
+    const WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+    (0..options.count(1000))
+        .map(|_| {
+            let mut line = String::new();
+            for _ in 0..rng.size(1..=6) {
+                for _ in 0..rng.size(0..=4) {
+                    line.push(letter(rng, LOWER));
+                }
+                if rng.chance(1, 2) {
+                    line.push(char::from(b'1' + rng.below(9) as u8));
+                } else {
+                    line += *rng.pick(&WORDS);
+                }
+            }
+            if !line.chars().any(|c| c.is_ascii_digit()) {
+                line.push(char::from(b'1' + rng.below(9) as u8));
+            }
+            line
+        })
+        .collect()
+}
+
+fn games(rng: &mut Rng, options: &Options) -> Vec<String> {
+    (1..=options.count(100))
+        .map(|id| {
+            let draws = (0..rng.size(1..=6))
+                .map(|_| {
+                    let mut colors = vec!["red", "green", "blue"];
+                    shuffle(rng, &mut colors);
+                    colors.truncate(rng.size(1..=3));
+                    colors.iter().map(|c| format!("{} {}", rng.range(1..=20), c)).collect::<Vec<_>>().join(", ")
+                })
+                .collect::<Vec<_>>();
+            format!("Game {}: {}", id, draws.join("; "))
+        })
+        .collect()
+}
+
+/// Numbers of up to three digits, and symbols at `density` among them.
+fn schematic(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let side = options.side(140, 3);
+    let mut grid = scatter(rng, side, side, '.', b"*#+$/@%=&-", options.density(0.04));
+    for row in grid.iter_mut() {
+        let mut col = 0;
+        while col < side {
+            let digits = rng.size(1..=3);
+            if rng.chance(1, 6) && col + digits <= side {
+                let n = rng.range(10i64.pow(digits as u32 - 1)..=10i64.pow(digits as u32) - 1);
+                for (i, c) in n.to_string().chars().enumerate() {
+                    row[col + i] = c;
+                }
+                col += digits;
+                if col < side {
+                    row[col] = '.';
+                }
+            }
+            col += 1;
+        }
+    }
+    render(&grid)
+}
+
+/// Ten winning numbers and twenty-five of your own, never winning copies
+/// of cards past the end of their run of twenty. Copies multiply down a
+/// run, so without the breaks they'd soon overflow.
+fn scratchcards(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let n = options.count(200);
+    let nums = |v: &[i64]| v.iter().map(|x| format!("{:>2}", x)).collect::<Vec<_>>().join(" ");
+    (0..n)
+        .map(|i| {
+            let all = distinct(rng, 35, 1..=99);
+            let (win, rest) = all.split_at(10);
+            let run_end = ((i / 20 + 1) * 20).min(n) - 1;
+            let matches = rng.size(0..=10).min(run_end - i);
+            let mut mine = win[..matches].iter().chain(&rest[..25 - matches]).copied().collect::<Vec<_>>();
+            shuffle(rng, &mut mine);
+            format!("Card {:>width$}: {} | {}", i + 1, nums(win), nums(&mine), width = n.to_string().len())
+        })
+        .collect()
+}
+
+/// Ten seed ranges, then seven maps whose source ranges don't overlap.
+fn almanac(rng: &mut Rng, options: &Options) -> Vec<String> {
+    const STAGES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+    const TOP: i64 = 1 << 32;
+    let seeds = (0..10)
+        .flat_map(|_| [rng.range(0..=TOP - (1 << 28)), rng.range(1..=1 << 28)])
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+    let mut lines = vec![format!("seeds: {}", seeds.join(" "))];
+    for stage in STAGES.windows(2) {
+        lines.push(String::new());
+        lines.push(format!("{}-to-{} map:", stage[0], stage[1]));
+        let mut cuts = distinct(rng, 2 * options.count(30), 0..=TOP);
+        cuts.sort_unstable();
+        let mut ranges = cuts.chunks(2)
+            .map(|c| {
+                let len = c[1] - c[0];
+                format!("{} {} {}", rng.range(0..=TOP - len), c[0], len)
+            })
+            .collect::<Vec<_>>();
+        shuffle(rng, &mut ranges);
+        lines.extend(ranges);
+    }
+    lines
+}
+
+/// Four races, every one winnable; any more and the joined race of part
+/// two is too long for an `f64`.
+fn races(rng: &mut Rng) -> Vec<String> {
+    loop {
+        let times = (0..4).map(|i| rng.range(if i == 0 { 10..=89 } else { 10..=99 })).collect::<Vec<_>>();
+        let distances = times.iter().map(|t| rng.range(t * t / 8..=t * t / 4 - 1)).collect::<Vec<_>>();
+        let joined = |v: &[i64]| v.iter().map(|n| n.to_string()).collect::<String>().parse::<i128>().unwrap();
+        let (t, d) = (joined(&times), joined(&distances));
+        if t * t < 1 << 53 && t * t > 4 * d {
+            let row = |v: &[i64]| v.iter().map(|n| format!("{:>6}", n)).collect::<String>();
+            return vec![format!("Time:   {}", row(&times)), format!("Distance:{}", row(&distances))];
+        }
+    }
+}
+
+fn hands(rng: &mut Rng, options: &Options) -> Vec<String> {
+    (0..options.count(1000))
+        .map(|_| {
+            let cards = (0..5).map(|_| letter(rng, b"23456789TJQKA")).collect::<String>();
+            format!("{} {}", cards, rng.range(1..=1000))
+        })
+        .collect()
+}
+
+/// Six ghosts, each on a loop as many whole rounds of instructions long as
+/// its prime, its `..Z` right before the loop closes. The branch not taken
+/// goes anywhere.
+fn network(rng: &mut Rng, options: &Options) -> Vec<String> {
+    const PRIMES: [usize; 10] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29];
+    let mut primes = PRIMES.to_vec();
+    shuffle(rng, &mut primes);
+    primes.truncate(6);
+    let rounds = (options.count(740) / primes.iter().sum::<usize>()).max(1);
+    let instructions = (0..rounds).map(|_| letter(rng, b"LR")).collect::<Vec<_>>();
+
+    let mut taken = HashSet::from(["AAA".to_string(), "ZZZ".to_string()]);
+    let starts = names(rng, primes.len() - 1, 3, b"ABCDEFGHIJKLMNOPQRSTUVWXYZ", b"A", &taken);
+    taken.extend(starts.iter().cloned());
+    let ends = names(rng, primes.len() - 1, 3, b"ABCDEFGHIJKLMNOPQRSTUVWXYZ", b"Z", &taken);
+    taken.extend(ends.iter().cloned());
+    let inner = primes.iter().map(|p| p * rounds - 1).sum();
+    let mut inner = names(rng, inner, 3, b"ABCDEFGHIJKLMNOPQRSTUVWXYZ", b"BCDEFGHIJKLMNOPQRSTUVWXY", &taken).into_iter();
+
+    // every node with its branch for each instruction, `None` for the one not taken
+    let mut nodes = Vec::new();
+    let starts = ["AAA".to_string()].into_iter().chain(starts);
+    let ends = ["ZZZ".to_string()].into_iter().chain(ends);
+    for ((start, end), p) in starts.zip(ends).zip(primes) {
+        let steps = p * rounds;
+        // `path[i]` is where the ghost is after `i` steps
+        let mut path = vec![start];
+        path.extend(inner.by_ref().take(steps - 1));
+        path.push(end.clone());
+        for i in 0..steps {
+            nodes.push((path[i].clone(), instructions[i % rounds], path[i + 1].clone()));
+        }
+        nodes.push((end, instructions[0], path[1].clone()));
+    }
+    let all = nodes.iter().map(|(name, _, _)| name.clone()).collect::<Vec<_>>();
+    let mut lines = nodes.into_iter()
+        .map(|(name, instruction, next)| {
+            let other = rng.pick(&all).clone();
+            let (left, right) = if instruction == 'L' { (next, other) } else { (other, next) };
+            format!("{} = ({}, {})", name, left, right)
+        })
+        .collect::<Vec<_>>();
+    shuffle(rng, &mut lines);
+    [instructions.into_iter().collect(), String::new()].into_iter().chain(lines).collect()
+}
+
+/// Twenty-one values of a polynomial of degree at most ten.
+fn histories(rng: &mut Rng, options: &Options) -> Vec<String> {
+    (0..options.count(200))
+        .map(|_| {
+            // in the binomial basis the values are whole numbers
+            let coefficients = (0..=rng.size(0..=10)).map(|_| rng.range(-10..=10)).collect::<Vec<_>>();
+            let offset = rng.range(-20..=20);
+            (0..21i64)
+                .map(|x| {
+                    let mut binomial = 1;
+                    let mut value = offset;
+                    for (j, a) in coefficients.iter().enumerate() {
+                        value += a * binomial;
+                        binomial = binomial * (x - j as i64) / (j as i64 + 1);
+                    }
+                    value.to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// Columns of squares side by side, each `(top, bottom)` overlapping the
+/// next so that the outline around them is simple; `w` columns between
+/// `0..=h`.
+fn monotone_columns(rng: &mut Rng, w: usize, h: i64, step: i64) -> Vec<(i64, i64)> {
+    let mut cols = Vec::<(i64, i64)>::new();
+    while cols.len() < w {
+        let (top, bottom) = match cols.last() {
+            None => (rng.range(0..=h / 2), rng.range(h / 2 + 1..=h)),
+            Some(&(t, b)) => (rng.range((t - step).max(0)..=t + step), rng.range(b - step..=(b + step).min(h))),
+        };
+        let overlaps = cols.last().is_none_or(|&(t, b)| top.max(t) < bottom.min(b));
+        if top < bottom && overlaps {
+            cols.push((top, bottom));
+        }
+    }
+    cols
+}
+
+/// The corners of the outline of `cols`, one unit wide each, clockwise
+/// from the top left, in `(row, col)`.
+fn outline(cols: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let n = cols.len() as i64;
+    let mut points = vec![(cols[0].0, 0)];
+    for (c, &(top, _)) in cols.iter().enumerate() {
+        points.push((top, c as i64 + 1));
+        if let Some(&(next, _)) = cols.get(c + 1) {
+            points.push((next, c as i64 + 1));
+        }
+    }
+    points.push((cols[cols.len() - 1].1, n));
+    for (c, &(_, bottom)) in cols.iter().enumerate().rev() {
+        points.push((bottom, c as i64));
+        if c > 0 {
+            points.push((cols[c - 1].1, c as i64));
+        }
+    }
+    points.dedup();
+    if points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+/// The outline of some columns as the loop, among pipes going nowhere;
+/// none of those lead into `S`, which sits where the loop doesn't run
+/// alongside itself.
+fn pipe_maze(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let side = options.side(140, 5);
+    let mut grid = scatter(rng, side, side, '.', b"|-LJ7F", 0.7);
+    let width = rng.size(side / 2..=side - 1);
+    let left = rng.size(0..=side - 1 - width);
+    let corners = outline(&monotone_columns(rng, width, side as i64 - 1, 3));
+    // every cell the loop goes through
+    let mut cells = Vec::new();
+    for i in 0..corners.len() {
+        let ((r0, c0), (r1, c1)) = (corners[i], corners[(i + 1) % corners.len()]);
+        let (dr, dc) = ((r1 - r0).signum(), (c1 - c0).signum());
+        let (mut r, mut c) = (r0, c0);
+        while (r, c) != (r1, c1) {
+            cells.push((r, c + left as i64));
+            r += dr;
+            c += dc;
+        }
+    }
+    let n = cells.len();
+    for i in 0..n {
+        let (r, c) = cells[i];
+        let towards = |(r1, c1): (i64, i64)| (r1 - r, c1 - c);
+        let mut ends = [towards(cells[(i + n - 1) % n]), towards(cells[(i + 1) % n])];
+        ends.sort_unstable();
+        grid[r as usize][c as usize] = match ends {
+            [(-1, 0), (1, 0)] => '|',
+            [(0, -1), (0, 1)] => '-',
+            [(-1, 0), (0, 1)] => 'L',
+            [(-1, 0), (0, -1)] => 'J',
+            [(0, -1), (1, 0)] => '7',
+            [(0, 1), (1, 0)] => 'F',
+            _ => unreachable!(),
+        };
+    }
+    let on = cells.iter().copied().collect::<HashSet<_>>();
+    let neighbours = |(r, c): (i64, i64)| [(r - 1, c), (r + 1, c), (r, c - 1), (r, c + 1)];
+    let lone = (0..n)
+        .filter(|&i| neighbours(cells[i]).iter().filter(|p| on.contains(p)).count() == 2)
+        .collect::<Vec<_>>();
+    let i = *rng.pick(&lone);
+    let (r, c) = cells[i];
+    let on_loop = [cells[(i + n - 1) % n], cells[(i + 1) % n]];
+    for (nr, nc) in neighbours((r, c)) {
+        if (0..side as i64).contains(&nr) && (0..side as i64).contains(&nc) && !on_loop.contains(&(nr, nc)) {
+            grid[nr as usize][nc as usize] = '.';
+        }
+    }
+    grid[r as usize][c as usize] = 'S';
+    render(&grid)
+}
+
+/// Galaxies at `density`, some rows and columns left empty.
+fn galaxies(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let side = options.side(140, 2);
+    let mut grid = scatter(rng, side, side, '.', b"#", options.density(0.025));
+    for _ in 0..side / 20 + 1 {
+        let (row, col) = (rng.size(0..=side - 1), rng.size(0..=side - 1));
+        grid[row].fill('.');
+        grid.iter_mut().for_each(|r| r[col] = '.');
+    }
+    render(&grid)
+}
+
+/// A row of springs that fits its groups, some of them then unknown.
+fn springs(rng: &mut Rng, options: &Options) -> Vec<String> {
+    (0..options.count(1000))
+        .map(|_| {
+            let groups = (0..rng.size(1..=6)).map(|_| rng.size(1..=5)).collect::<Vec<_>>();
+            let mut row = ".".repeat(rng.size(0..=2));
+            for (i, g) in groups.iter().enumerate() {
+                if i > 0 {
+                    row += &".".repeat(rng.size(1..=2));
+                }
+                row += &"#".repeat(*g);
+            }
+            row += &".".repeat(rng.size(0..=2));
+            let row = row.chars().map(|c| if rng.chance(1, 2) { '?' } else { c }).collect::<String>();
+            let groups = groups.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+            format!("{} {}", row, groups.join(","))
+        })
+        .collect()
+}
+
+/// Patterns mirrored exactly across a column, and across a row but for one
+/// cell outside what the column mirror reaches; half of them on their side.
+fn mirrors(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let mut lines = Vec::new();
+    for i in 0..options.count(100) {
+        let (h, w) = (rng.size(5..=17), rng.size(5..=17));
+        let col = rng.size(1..=(w - 1) / 2);
+        let row = rng.size(1..=h - 1);
+        let mut grid = (0..h)
+            .map(|_| {
+                let mut r = (0..w).map(|_| letter(rng, b"#.")).collect::<Vec<_>>();
+                for j in 0..col {
+                    r[col + j] = r[col - 1 - j];
+                }
+                r
+            })
+            .collect::<Vec<_>>();
+        let reach = row.min(h - row);
+        for j in 0..reach {
+            grid[row + j] = grid[row - 1 - j].clone();
+        }
+        let smudged = row - 1 - rng.size(0..=reach - 1);
+        let at = rng.size(2 * col..=w - 1);
+        grid[smudged][at] = if grid[smudged][at] == '#' { '.' } else { '#' };
+        if rng.chance(1, 2) {
+            grid = (0..w).map(|c| (0..h).map(|r| grid[r][c]).collect()).collect();
+        }
+        if i > 0 {
+            lines.push(String::new());
+        }
+        lines.extend(render(&grid));
+    }
+    lines
+}
+
+/// Cube rocks at `density`, and round ones on a fifth of what's left.
+fn platform(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let side = options.side(100, 1);
+    let mut grid = scatter(rng, side, side, '.', b"#", options.density(0.1));
+    for cell in grid.iter_mut().flatten() {
+        if *cell == '.' && rng.chance(1, 5) {
+            *cell = 'O';
+        }
+    }
+    render(&grid)
+}
+
+fn init_sequence(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let labels = (0..options.count(500))
+        .map(|_| (0..rng.size(2..=6)).map(|_| letter(rng, LOWER)).collect::<String>())
+        .collect::<Vec<_>>();
+    let steps = (0..options.count(4000))
+        .map(|_| {
+            let label = rng.pick(&labels);
+            if rng.chance(1, 3) { format!("{}-", label) } else { format!("{}={}", label, rng.range(1..=9)) }
+        })
+        .collect::<Vec<_>>();
+    vec![steps.join(",")]
+}
+
+/// Mirrors and splitters at `density`.
+fn contraption(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let side = options.side(110, 1);
+    render(&scatter(rng, side, side, '.', b"/\\|-", options.density(0.1)))
+}
+
+fn heat_map(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let side = options.side(141, 13);
+    (0..side).map(|_| (0..side).map(|_| char::from(b'1' + rng.below(9) as u8)).collect()).collect()
+}
+
+/// The outline of some columns, the colours tracing the same outline with
+/// every distinct row and column spread apart by up to a few thousand.
+fn dig_plan(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let cols = options.count(155);
+    let corners = outline(&monotone_columns(rng, cols, 2 * cols.min(200) as i64, 8));
+    // a random increasing map from each coordinate to a wider spaced one
+    let mut spread = |values: Vec<i64>| {
+        let mut values = values;
+        values.sort_unstable();
+        values.dedup();
+        let gap = (1 << 19) / values.len().max(1) as i64;
+        let mut at = 0;
+        values.into_iter().map(|v| {
+            at += rng.range(1..=gap.max(1));
+            (v, at)
+        }).collect::<std::collections::HashMap<_, _>>()
+    };
+    let rows = spread(corners.iter().map(|p| p.0).collect());
+    let columns = spread(corners.iter().map(|p| p.1).collect());
+    let far = corners.iter().map(|&(r, c)| (rows[&r], columns[&c])).collect::<Vec<_>>();
+
+    let step = |a: (i64, i64), b: (i64, i64)| {
+        let (dr, dc) = (b.0 - a.0, b.1 - a.1);
+        match (dr.signum(), dc.signum()) {
+            (0, 1) => ('R', 0, dc),
+            (1, 0) => ('D', 1, dr),
+            (0, -1) => ('L', 2, -dc),
+            _ => ('U', 3, -dr),
+        }
+    };
+    (0..corners.len())
+        .map(|i| {
+            let j = (i + 1) % corners.len();
+            let (dir, _, len) = step(corners[i], corners[j]);
+            let (_, code, far_len) = step(far[i], far[j]);
+            format!("{} {} (#{:05x}{})", dir, len, far_len, code)
+        })
+        .collect()
+}
+
+/// A tree of workflows from `in`, every one ending in a fallback, then
+/// parts rated from 1 to 4000.
+fn workflows(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let n = options.count(570);
+    let mut labels = names(rng, n - 1, 2, LOWER, LOWER, &HashSet::from(["in".to_string()])).into_iter();
+    let mut queue = std::collections::VecDeque::from(["in".to_string()]);
+    let mut made = 1;
+    let mut lines = Vec::new();
+    while let Some(label) = queue.pop_front() {
+        let rules = rng.size(2..=4);
+        let mut targets = Vec::new();
+        for i in 0..rules {
+            let last = i == rules - 1 && queue.is_empty() && targets.iter().all(|t: &String| t.len() == 1);
+            if made < n && (last || rng.chance(1, 2)) {
+                let child = labels.next().unwrap();
+                queue.push_back(child.clone());
+                targets.push(child);
+                made += 1;
+            } else {
+                targets.push(letter(rng, b"AR").to_string());
+            }
+        }
+        let fallback = targets.pop().unwrap();
+        let conditions = targets.into_iter()
+            .map(|t| format!("{}{}{}:{}", letter(rng, b"xmas"), letter(rng, b"<>"), rng.range(1..=4000), t))
+            .collect::<Vec<_>>();
+        lines.push(format!("{}{{{},{}}}", label, conditions.join(","), fallback));
+    }
+    shuffle(rng, &mut lines);
+    lines.push(String::new());
+    for _ in 0..options.count(200) {
+        let r = (0..4).map(|_| rng.range(1..=4000)).collect::<Vec<_>>();
+        lines.push(format!("{{x={},m={},a={},s={}}}", r[0], r[1], r[2], r[3]));
+    }
+    lines
+}
+
+/// Four twelve-bit counters, each counting up to a different prime before
+/// its conjunction fires and resets it, and the four of them feeding the
+/// conjunction in front of `rx`. Any more and part two's answer wouldn't
+/// fit a `u64`.
+fn circuit(rng: &mut Rng) -> Vec<String> {
+    let primes = (2049..4096u64).filter(|n| (2..n.isqrt() + 1).all(|d| n % d != 0)).collect::<Vec<_>>();
+    let mut primes = distinct(rng, 4, 0..=primes.len() as i64 - 1).into_iter().map(|i| primes[i as usize]).collect::<Vec<_>>();
+    primes.sort_unstable();
+    let mut names = names(rng, 4 * 14 + 1, 2, LOWER, LOWER, &HashSet::from(["rx".to_string()])).into_iter();
+    let last = names.next().unwrap();
+    let mut lines = Vec::new();
+    let mut firsts = Vec::new();
+    for p in primes {
+        let bits = (0..12).map(|_| names.next().unwrap()).collect::<Vec<_>>();
+        let (counter, inverter) = (names.next().unwrap(), names.next().unwrap());
+        let mut back = Vec::new();
+        for (i, bit) in bits.iter().enumerate() {
+            let mut outputs = bits.get(i + 1).into_iter().cloned().collect::<Vec<_>>();
+            if p >> i & 1 == 1 {
+                outputs.push(counter.clone());
+            }
+            if i == 0 || p >> i & 1 == 0 {
+                back.push(bit.clone());
+            }
+            lines.push(format!("%{} -> {}", bit, outputs.join(", ")));
+        }
+        back.push(inverter.clone());
+        lines.push(format!("&{} -> {}", counter, back.join(", ")));
+        lines.push(format!("&{} -> {}", inverter, last));
+        firsts.push(bits[0].clone());
+    }
+    lines.push(format!("&{} -> rx", last));
+    lines.push(format!("broadcaster -> {}", firsts.join(", ")));
+    shuffle(rng, &mut lines);
+    lines
+}
+
+/// Rocks at `density`, an odd square with `S` in the middle and its row,
+/// its column and the edges left clear.
+fn garden(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let side = options.side(131, 3) | 1;
+    let mid = side / 2;
+    let mut grid = scatter(rng, side, side, '.', b"#", options.density(0.1));
+    for i in 0..side {
+        for (r, c) in [(mid, i), (i, mid), (0, i), (side - 1, i), (i, 0), (i, side - 1)] {
+            grid[r][c] = '.';
+        }
+    }
+    grid[mid][mid] = 'S';
+    render(&grid)
+}
+
+/// Straight bricks of up to five cubes, none overlapping, in a column about
+/// a tenth full.
+fn bricks(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let n = options.count(1270);
+    let side = options.side(10, 3) as i64;
+    let height = (25 * n as i64 / (side * side)).max(10);
+    let mut taken = HashSet::new();
+    let mut lines = Vec::new();
+    while lines.len() < n {
+        let axis = rng.size(0..=2);
+        let len = rng.range(0..=if axis < 2 { 4.min(side - 1) } else { 4 });
+        let mut start = [rng.range(0..=side - 1), rng.range(0..=side - 1), rng.range(1..=height)];
+        if axis < 2 {
+            start[axis] = start[axis].min(side - 1 - len);
+        }
+        let cubes = (0..=len)
+            .map(|i| {
+                let mut p = start;
+                p[axis] += i;
+                p
+            })
+            .collect::<Vec<_>>();
+        if cubes.iter().any(|p| taken.contains(p)) {
+            continue;
+        }
+        taken.extend(cubes.iter().copied());
+        let end = cubes[cubes.len() - 1];
+        lines.push(format!("{},{},{}~{},{},{}", start[0], start[1], start[2], end[0], end[1], end[2]));
+    }
+    lines
+}
+
+/// A lattice of junctions joined by winding corridors, with slopes down and
+/// to the right into and out of every one. Past scale 1 there are still 6
+/// by 6 junctions, as in the real input, only further apart: the longest
+/// hike takes time exponential in the junctions, not the corridors.
+fn trails(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let size = options.side(141, 25);
+    let k = (size / 22).clamp(2, 6);
+    let spacing = (size / k).max(12);
+    let size = spacing * k + 1;
+    let at = |i: usize| spacing * i + spacing / 2;
+    let mut grid = vec![vec!['#'; size]; size];
+    let wiggle = (spacing / 4 - 2) as i64;
+
+    // a corridor from junction `a` to `b`, along a row when `across`
+    let corridor = |grid: &mut Vec<Vec<char>>, fixed: usize, from: usize, to: usize, across: bool, rng: &mut Rng| {
+        let mut set = |along: usize, off: usize, c: char| {
+            if across { grid[off][along] = c } else { grid[along][off] = c }
+        };
+        let (turn_in, turn_out) = (from + spacing / 4, to - spacing / 4);
+        let d = rng.range(-wiggle..=wiggle);
+        let side = (fixed as i64 + d) as usize;
+        for x in from + 1..to {
+            set(x, if (turn_in..=turn_out).contains(&x) { side } else { fixed }, '.');
+        }
+        for y in fixed.min(side)..=fixed.max(side) {
+            set(turn_in, y, '.');
+            set(turn_out, y, '.');
+        }
+        let slope = if across { '>' } else { 'v' };
+        set(from + 1, fixed, slope);
+        set(to - 1, fixed, slope);
+    };
+    for i in 0..k {
+        for j in 0..k {
+            grid[at(i)][at(j)] = '.';
+            if j + 1 < k {
+                corridor(&mut grid, at(i), at(j), at(j + 1), true, rng);
+            }
+            if i + 1 < k {
+                corridor(&mut grid, at(j), at(i), at(i + 1), false, rng);
+            }
+        }
+    }
+    // in from the top left, out at the bottom right
+    grid[..=at(0)].iter_mut().for_each(|row| row[1] = '.');
+    grid[at(0)][1..at(0)].fill('.');
+    grid[at(0)][at(0) - 1] = '>';
+    let last = at(k - 1);
+    grid[last + 1..size - 1].iter_mut().for_each(|row| row[last] = '.');
+    grid[size - 2][last..size - 1].fill('.');
+    grid[size - 1][size - 2] = '.';
+    grid[last + 1][last] = 'v';
+    render(&grid)
+}
+
+/// Hailstones placed so that one rock thrown from a whole-number position,
+/// at a whole-number velocity, hits each of them at a different time.
+fn hailstones(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let n = options.count(300);
+    let mut axis = || (rng.range(150_000_000_000_000..=350_000_000_000_000), rng.range(-300..=300));
+    let rock = [axis(), axis(), axis()];
+    let times = distinct(rng, n, 1_000_000_000..=200_000_000_000);
+    times.into_iter()
+        .map(|t| {
+            let (mut p, mut v) = ([0; 3], [0; 3]);
+            for i in 0..3 {
+                v[i] = loop {
+                    let v = rng.range(-300..=300);
+                    if v != 0 {
+                        break v;
+                    }
+                };
+                p[i] = rock[i].0 + (rock[i].1 - v[i]) * t;
+            }
+            format!("{}, {}, {} @ {}, {}, {}", p[0], p[1], p[2], v[0], v[1], v[2])
+        })
+        .collect()
+}
+
+/// Two halves, each at least four edges apart everywhere, joined by three
+/// wires; every wire listed under one of its ends.
+fn wiring(rng: &mut Rng, options: &Options) -> Vec<String> {
+    let total = options.count(1500).max(12);
+    let a = rng.size(total * 2 / 5..=total * 3 / 5);
+    let names = names(rng, total, 3, LOWER, LOWER, &HashSet::new());
+    let mut edges = Vec::new();
+    for (offset, size) in [(0, a), (a, total - a)] {
+        // a complete five, then everything else joined to four before it
+        for i in 0..size {
+            let earlier = if i < 5 { (0..i as i64).collect() } else { distinct(rng, 4, 0..=i as i64 - 1) };
+            edges.extend(earlier.into_iter().map(|j| (offset + i, offset + j as usize)));
+        }
+    }
+    let left = distinct(rng, 3, 0..=a as i64 - 1);
+    let right = distinct(rng, 3, a as i64..=total as i64 - 1);
+    edges.extend(left.into_iter().zip(right).map(|(l, r)| (l as usize, r as usize)));
+
+    let mut wires = vec![Vec::new(); total];
+    for (u, v) in edges {
+        let (from, to) = if rng.chance(1, 2) { (u, v) } else { (v, u) };
+        wires[from].push(names[to].clone());
+    }
+    let mut lines = wires.into_iter()
+        .enumerate()
+        .filter(|(_, to)| !to.is_empty())
+        .map(|(from, to)| format!("{}: {}", names[from], to.join(" ")))
+        .collect::<Vec<_>>();
+    shuffle(rng, &mut lines);
+    lines
+}
+
+#[test]
+fn test() {
+    use crate::{check, fuzz, runner};
+
+    let small = Options { seed: 7, scale: 0.05, density: None };
+    assert_eq!(generate(3, &small), generate(3, &small));
+    assert_ne!(generate(3, &small), generate(3, &Options { seed: 8, ..small }));
+    assert!(generate(4, &Options { density: Some(0.5), ..small }).is_err());
+    assert!(generate(11, &Options { density: Some(1.5), ..small }).is_err());
+    assert!(generate(26, &small).is_err());
+
+    // small ones, and some ten times the real size
+    let options = (0..3).map(|seed| Options { seed, ..small })
+        .chain([Options { scale: 10.0, ..Options::default() }]);
+    for options in options {
+        for day in DAYS {
+            let input = generate(day, &options).unwrap();
+            assert!(fuzz::parses(day, &input), "day {} {:?}:\n{}", day, options, input);
+            if check::DAYS.contains(&day) {
+                let broken = check::assumptions(day, &input).unwrap().into_iter().filter(|a| !a.holds()).collect::<Vec<_>>();
+                assert!(broken.is_empty(), "day {} {:?}: {:?}\n{}", day, options, broken, input);
+            }
+        }
+    }
+
+    // the answers the generators build in
+    let input = generate(20, &small).unwrap();
+    let mut presses = runner::solver(20, 2).unwrap()(&input).parse::<u64>().unwrap();
+    let mut periods = Vec::new();
+    for p in 2049..4096 {
+        while presses % p == 0 {
+            presses /= p;
+            periods.push(p);
+        }
+    }
+    assert_eq!((presses, periods.len()), (1, 4), "{:?}", periods);
+
+    let input = generate(25, &small).unwrap();
+    let cut = input.parse::<crate::day25::Wiring>().unwrap().min_cut().unwrap();
+    assert_eq!(cut.wires.len(), 3);
+    assert_eq!(runner::solver(25, 1).unwrap()(&input), cut.product().to_string());
+}
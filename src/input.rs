@@ -0,0 +1,126 @@
+//! Splitting puzzle input into lines, paragraphs and grids.
+//!
+//! All of these take `\r\n` as well as `\n`, skip blank lines before and
+//! after the input, and complain about blank lines anywhere else they
+//! shouldn't be, rather than handing a parser an empty line to choke on.
+//! Lines borrow from the input and keep any other whitespace.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    /// Nothing but whitespace.
+    Empty,
+    /// A blank line where every line should have something on it.
+    BlankLine { line: usize },
+    /// More than one blank line between paragraphs.
+    ExtraBlankLine { line: usize },
+    /// A grid row not as wide as the first one.
+    Ragged { line: usize, width: usize, expected: usize },
+    /// A character outside ASCII in a grid, at `column` (1-based).
+    NotAscii { line: usize, column: usize },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Empty => write!(f, "input is empty"),
+            InputError::BlankLine { line } => write!(f, "line {line}: unexpected blank line"),
+            InputError::ExtraBlankLine { line } => write!(f, "line {line}: more than one blank line between paragraphs"),
+            InputError::Ragged { line, width, expected } => {
+                write!(f, "line {line}: row is {width} wide, expected {expected}")
+            }
+            InputError::NotAscii { line, column } => write!(f, "line {line}, column {column}: not an ASCII character"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// Every line, numbered from 1, with any `\r` taken off the end and the
+/// blank lines at either end left out.
+fn numbered(input: &str) -> Result<Vec<(usize, &str)>, InputError> {
+    let mut lines = input.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|(_, l)| is_blank(l)) {
+        lines.pop();
+    }
+    let first = lines.iter().position(|(_, l)| !is_blank(l)).ok_or(InputError::Empty)?;
+    lines.drain(..first);
+    Ok(lines)
+}
+
+/// One entry per line. A blank line in between is an error.
+pub fn lines(input: &str) -> Result<Vec<&str>, InputError> {
+    numbered(input)?.into_iter()
+        .map(|(n, line)| if is_blank(line) { Err(InputError::BlankLine { line: n }) } else { Ok(line) })
+        .collect()
+}
+
+/// Runs of lines separated by a single blank line.
+pub fn paragraphs(input: &str) -> Result<Vec<Vec<&str>>, InputError> {
+    let mut ret = vec![Vec::new()];
+    let mut blank = false;
+    for (n, line) in numbered(input)? {
+        if !is_blank(line) {
+            ret.last_mut().unwrap().push(line);
+            blank = false;
+        } else if blank {
+            return Err(InputError::ExtraBlankLine { line: n });
+        } else {
+            ret.push(Vec::new());
+            blank = true;
+        }
+    }
+    Ok(ret)
+}
+
+/// [`lines`], which must all be as wide as the first. Grids are indexed
+/// by byte, so only ASCII is let through.
+pub fn grid(input: &str) -> Result<Vec<&str>, InputError> {
+    let rows = numbered(input)?;
+    let expected = rows[0].1.chars().count();
+    for &(line, row) in rows.iter() {
+        let width = row.chars().count();
+        if is_blank(row) {
+            return Err(InputError::BlankLine { line });
+        }
+        if let Some(column) = row.chars().position(|c| !c.is_ascii()) {
+            return Err(InputError::NotAscii { line, column: column + 1 });
+        }
+        if width != expected {
+            return Err(InputError::Ragged { line, width, expected });
+        }
+    }
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
+
+#[test]
+fn test() {
+    assert_eq!(lines("a\nb\n"), Ok(vec!["a", "b"]));
+    assert_eq!(lines("\r\na\r\nb\r\n\r\n"), Ok(vec!["a", "b"]));
+    assert_eq!(lines("  x \n"), Ok(vec!["  x "]));
+    assert_eq!(lines("a\n\nb"), Err(InputError::BlankLine { line: 2 }));
+    assert_eq!(lines(" \r\n\n"), Err(InputError::Empty));
+
+    let input = "\nseeds: 1\n\na\r\nb\n\nc\n\n";
+    assert_eq!(paragraphs(input), Ok(vec![vec!["seeds: 1"], vec!["a", "b"], vec!["c"]]));
+    assert_eq!(paragraphs("a\n\n\nb"), Err(InputError::ExtraBlankLine { line: 3 }));
+    assert_eq!(paragraphs("a\r\n \r\nb"), Ok(vec![vec!["a"], vec!["b"]]));
+
+    assert_eq!(grid("#.\r\n.#\r\n"), Ok(vec!["#.", ".#"]));
+    assert_eq!(grid("#.\n.#.\n"), Err(InputError::Ragged { line: 2, width: 3, expected: 2 }));
+    assert_eq!(grid("#.\n\n.#"), Err(InputError::BlankLine { line: 2 }));
+    assert_eq!(grid("#.\n.é"), Err(InputError::NotAscii { line: 2, column: 2 }));
+    assert_eq!(
+        grid("\n#.\n.").unwrap_err().to_string(),
+        "line 3: row is 1 wide, expected 2",
+    );
+}
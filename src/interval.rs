@@ -0,0 +1,365 @@
+//! Half-open integer intervals, sets of them, and boxes made of them.
+//!
+//! Everything is `[start, end)`, like `Range`, and an interval with
+//! `start >= end` is empty. Lengths and volumes are `u128` so that a full
+//! `i64` or `u64` span still fits.
+
+use std::{
+    fmt,
+    ops::{Add, Index, IndexMut, Range, RangeInclusive, Sub},
+};
+
+/// Integers an interval can be made of.
+pub trait Coord: Copy + Ord + fmt::Debug + Add<Output = Self> + Sub<Output = Self> {
+    const ONE: Self;
+
+    /// `end - start` for `start <= end`, without overflowing.
+    fn distance(start: Self, end: Self) -> u128;
+}
+
+macro_rules! coord {
+    ($($t:ty),*) => {$(
+        impl Coord for $t {
+            const ONE: Self = 1;
+
+            fn distance(start: Self, end: Self) -> u128 {
+                end.abs_diff(start) as u128
+            }
+        }
+    )*};
+}
+
+coord!(i32, i64, i128, u32, u64, u128, usize);
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Coord> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+
+    /// `first..=last`.
+    pub fn inclusive(first: T, last: T) -> Self {
+        Self::new(first, last + T::ONE)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn len(&self) -> u128 {
+        if self.is_empty() { 0 } else { T::distance(self.start, self.end) }
+    }
+
+    /// The last value inside, `None` if empty.
+    pub fn last(&self) -> Option<T> {
+        (!self.is_empty()).then(|| self.end - T::ONE)
+    }
+
+    pub fn contains(&self, v: T) -> bool {
+        self.start <= v && v < self.end
+    }
+
+    pub fn range(&self) -> Range<T> {
+        self.start..self.end
+    }
+
+    /// The common part, possibly empty.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    /// What's left of `self` below and above `other`, either possibly empty.
+    pub fn difference(&self, other: &Self) -> (Self, Self) {
+        if !self.overlaps(other) {
+            return (*self, Self::new(self.end, self.end));
+        }
+        (Self::new(self.start, other.start), Self::new(other.end, self.end))
+    }
+
+    /// The values below `at` and the rest, either possibly empty.
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let at = at.clamp(self.start, self.end.max(self.start));
+        (Self::new(self.start, at), Self::new(at, self.end))
+    }
+
+    /// Moved so that `from` lands on `to`, e.g. a source range onto its
+    /// destination. For unsigned `T` the interval can't start below `from`.
+    pub fn shift(&self, from: T, to: T) -> Self {
+        if from <= to {
+            Self::new(self.start + (to - from), self.end + (to - from))
+        } else {
+            Self::new(self.start - (from - to), self.end - (from - to))
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}..{:?}", self.start, self.end)
+    }
+}
+
+impl<T: Coord> From<Range<T>> for Interval<T> {
+    fn from(r: Range<T>) -> Self {
+        Self::new(r.start, r.end)
+    }
+}
+
+impl<T: Coord> From<RangeInclusive<T>> for Interval<T> {
+    fn from(r: RangeInclusive<T>) -> Self {
+        Self::inclusive(*r.start(), *r.end())
+    }
+}
+
+/// Disjoint intervals, sorted and with no two touching, so equal sets
+/// compare equal.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Coord> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { intervals: Vec::new() }
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many values are in the set.
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().map(|i| i.len()).sum()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.intervals.last().and_then(|i| i.last())
+    }
+
+    pub fn contains(&self, v: T) -> bool {
+        let i = self.intervals.partition_point(|i| i.end <= v);
+        self.intervals.get(i).is_some_and(|i| i.contains(v))
+    }
+
+    pub fn insert(&mut self, interval: impl Into<Interval<T>>) {
+        let interval = interval.into();
+        if interval.is_empty() {
+            return;
+        }
+        // everything touching `interval` is merged into it
+        let lo = self.intervals.partition_point(|i| i.end < interval.start);
+        let hi = self.intervals.partition_point(|i| i.start <= interval.end);
+        let merged = self.intervals[lo..hi].iter().fold(interval, |acc, i| {
+            Interval::new(acc.start.min(i.start), acc.end.max(i.end))
+        });
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ret = self.clone();
+        for i in other.intervals.iter() {
+            ret.insert(*i);
+        }
+        ret
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut a, mut b) = (0, 0);
+        while a < self.intervals.len() && b < other.intervals.len() {
+            let (x, y) = (self.intervals[a], other.intervals[b]);
+            let common = x.intersect(&y);
+            if !common.is_empty() {
+                intervals.push(common);
+            }
+            if x.end < y.end { a += 1 } else { b += 1 }
+        }
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        for i in self.intervals.iter() {
+            let mut rest = *i;
+            let first = other.intervals.partition_point(|o| o.end <= rest.start);
+            for o in other.intervals[first..].iter().take_while(|o| o.start < i.end) {
+                let (below, above) = rest.difference(o);
+                if !below.is_empty() {
+                    intervals.push(below);
+                }
+                rest = above;
+            }
+            if !rest.is_empty() {
+                intervals.push(rest);
+            }
+        }
+        Self { intervals }
+    }
+
+    /// The values below `at` and the rest.
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let (mut below, mut above) = (Vec::new(), Vec::new());
+        for i in self.intervals.iter() {
+            let (b, a) = i.split_at(at);
+            if !b.is_empty() { below.push(b) }
+            if !a.is_empty() { above.push(a) }
+        }
+        (Self { intervals: below }, Self { intervals: above })
+    }
+
+    /// Every interval moved so that `from` lands on `to`.
+    pub fn shift(&self, from: T, to: T) -> Self {
+        Self { intervals: self.intervals.iter().map(|i| i.shift(from, to)).collect() }
+    }
+}
+
+impl<T: Coord, I: Into<Interval<T>>> FromIterator<I> for IntervalSet<T> {
+    fn from_iter<It: IntoIterator<Item = I>>(iter: It) -> Self {
+        let mut set = Self::new();
+        for i in iter {
+            set.insert(i);
+        }
+        set
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.intervals.iter()).finish()
+    }
+}
+
+/// A box with one interval per axis; empty if any side is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hyperbox<T> {
+    sides: Vec<Interval<T>>,
+}
+
+impl<T: Coord> Hyperbox<T> {
+    pub fn new(sides: Vec<Interval<T>>) -> Self {
+        Self { sides }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.sides.len()
+    }
+
+    pub fn sides(&self) -> &[Interval<T>] {
+        &self.sides
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sides.iter().any(|s| s.is_empty())
+    }
+
+    /// How many points are inside; the product of the side lengths.
+    pub fn volume(&self) -> u128 {
+        self.sides.iter().map(|s| s.len()).product()
+    }
+
+    pub fn contains(&self, point: &[T]) -> bool {
+        point.len() == self.dims() && self.sides.iter().zip(point).all(|(s, v)| s.contains(*v))
+    }
+
+    pub fn intersect(&self, other: &Self) -> Self {
+        assert_eq!(self.dims(), other.dims());
+        Self::new(self.sides.iter().zip(other.sides.iter()).map(|(a, b)| a.intersect(b)).collect())
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    /// The part below `at` along `axis` and the rest.
+    pub fn split_at(&self, axis: usize, at: T) -> (Self, Self) {
+        let (below, above) = self.sides[axis].split_at(at);
+        let (mut low, mut high) = (self.clone(), self.clone());
+        low.sides[axis] = below;
+        high.sides[axis] = above;
+        (low, high)
+    }
+
+    /// Moved along `axis` so that `from` lands on `to`.
+    pub fn shift(&self, axis: usize, from: T, to: T) -> Self {
+        let mut ret = self.clone();
+        ret.sides[axis] = self.sides[axis].shift(from, to);
+        ret
+    }
+
+    /// Only the given axes, e.g. the footprint of a 3D box.
+    pub fn project(&self, axes: &[usize]) -> Self {
+        Self::new(axes.iter().map(|a| self.sides[*a]).collect())
+    }
+}
+
+impl<T> Index<usize> for Hyperbox<T> {
+    type Output = Interval<T>;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        &self.sides[axis]
+    }
+}
+
+impl<T> IndexMut<usize> for Hyperbox<T> {
+    fn index_mut(&mut self, axis: usize) -> &mut Self::Output {
+        &mut self.sides[axis]
+    }
+}
+
+#[test]
+fn test() {
+    let i = Interval::new(3, 8);
+    assert_eq!(i.len(), 5);
+    assert_eq!(Interval::new(8, 3).len(), 0);
+    assert_eq!(Interval::from(3..=7), i);
+    assert_eq!(i.last(), Some(7));
+    assert_eq!(i.intersect(&Interval::new(5, 10)), Interval::new(5, 8));
+    assert!(!i.overlaps(&Interval::new(8, 10)));
+    assert_eq!(i.difference(&Interval::new(4, 6)), (Interval::new(3, 4), Interval::new(6, 8)));
+    assert_eq!(i.split_at(0), (Interval::new(3, 3), i));
+    assert_eq!(i.shift(3, 0), Interval::new(0, 5));
+    assert_eq!(Interval::new(10_u128, 20).shift(10, 0), Interval::new(0, 10));
+    assert_eq!(Interval::new(i64::MIN, i64::MAX).len(), u64::MAX as u128);
+
+    // touching intervals merge
+    let a: IntervalSet<i64> = [0..2, 5..7, 2..3, 10..12].into_iter().collect();
+    assert_eq!(a.intervals(), &[Interval::new(0, 3), Interval::new(5, 7), Interval::new(10, 12)]);
+    assert_eq!(a.len(), 7);
+    assert_eq!((a.min(), a.max()), (Some(0), Some(11)));
+    assert!(a.contains(6) && !a.contains(7) && !a.contains(-1));
+
+    let b: IntervalSet<i64> = [1..6, 11..20].into_iter().collect();
+    assert_eq!(a.union(&b), [0..7, 10..20].into_iter().collect());
+    assert_eq!(a.intersection(&b), [1..3, 5..6, 11..12].into_iter().collect());
+    assert_eq!(a.difference(&b), [0..1, 6..7, 10..11].into_iter().collect());
+    assert_eq!(b.difference(&a), [3..5, 12..20].into_iter().collect());
+    assert_eq!(a.split_at(6), ([0..3, 5..6].into_iter().collect(), [6..7, 10..12].into_iter().collect()));
+    assert_eq!(a.shift(0, 100).min(), Some(100));
+    assert!(a.difference(&a).is_empty());
+
+    let cube = Hyperbox::new(vec![Interval::new(0, 4); 3]);
+    assert_eq!(cube.volume(), 64);
+    let (low, high) = cube.split_at(1, 1);
+    assert_eq!((low.volume(), high.volume()), (16, 48));
+    assert!(!low.overlaps(&high));
+    assert_eq!(cube.intersect(&cube.shift(2, 0, 3)).volume(), 16);
+    assert!(cube.contains(&[0, 3, 1]) && !cube.contains(&[0, 4, 1]));
+    assert_eq!(cube.project(&[0, 1]).volume(), 16);
+}
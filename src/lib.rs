@@ -23,3 +23,18 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+
+pub mod answers;
+pub mod check;
+pub mod dot;
+pub mod format;
+pub mod fuzz;
+pub mod generate;
+pub mod input;
+pub mod interval;
+pub mod math;
+pub mod polygon;
+pub mod prop;
+pub mod runner;
+pub mod vec3;
+pub mod viz;
@@ -1,2 +1,201 @@
+use std::{env, fs, io, path::{Path, PathBuf}, process::ExitCode, thread, time::Duration};
 
-fn main() {}
+use advent_of_code_2023::{answers::{self, Answers, Verdict}, check, dot, generate, runner::{self, Status}, viz};
+
+const USAGE: &str = "\
+usage:
+    advent-of-code-2023 run [days...] [--threads=N] [--timeout=SECS] [--json] [--answers=FILE]
+    advent-of-code-2023 solve <day> <part> [input]
+    advent-of-code-2023 check [days...] [--input=FILE]
+    advent-of-code-2023 record <day> <part> [answer] [--answers=FILE]
+    advent-of-code-2023 dot <day> [input] [--highlight]
+    advent-of-code-2023 viz <day> [input] [--fps=N] [--every=N] [--steps=N] [--no-color] [--export=DIR]
+    advent-of-code-2023 generate <day> [--seed=N] [--scale=X] [--density=F]";
+
+fn input_path(day: u32) -> PathBuf {
+    PathBuf::from(format!("src/day{:02}/input", day))
+}
+
+/// The puzzle input of `day`, `src/dayNN/input` unless a path is given.
+fn read_input(day: u32, path: Option<&str>) -> Result<String, String> {
+    let path = path.map_or_else(|| input_path(day), PathBuf::from);
+    fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// The recorded answers; a missing file just means none are known yet.
+fn read_answers(path: &str) -> Result<Answers, String> {
+    match fs::read_to_string(path) {
+        Ok(s) => s.parse(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+        Err(e) => Err(format!("{}: {}", path, e)),
+    }
+}
+
+fn parse_part(s: Option<&str>) -> Result<u8, String> {
+    match s {
+        Some("1") => Ok(1),
+        Some("2") => Ok(2),
+        s => Err(format!("not a part: {}", s.unwrap_or("none"))),
+    }
+}
+
+fn parse_day(s: Option<&str>) -> Result<u32, String> {
+    let s = s.ok_or("missing day")?;
+    match s.parse() {
+        Ok(day @ 1..=25) => Ok(day),
+        _ => Err(format!("not a day: {}", s)),
+    }
+}
+
+/// The value of `--name=value`, if given.
+fn flag<'a>(flags: &[&'a str], name: &str) -> Option<&'a str> {
+    flags.iter().find_map(|f| f.strip_prefix("--")?.strip_prefix(name)?.strip_prefix('='))
+}
+
+fn flag_num<T: std::str::FromStr>(flags: &[&str], name: &str) -> Result<Option<T>, String> {
+    flag(flags, name)
+        .map(|v| v.parse().map_err(|_| format!("bad --{}: {}", name, v)))
+        .transpose()
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (flags, args): (Vec<&str>, Vec<&str>) = args.iter()
+        .map(|s| s.as_str())
+        .partition(|s| s.starts_with("--"));
+
+    match args.first().copied() {
+        Some("run") => {
+            let days = match &args[1..] {
+                [] => (1..=25).collect(),
+                days => days.iter().map(|d| parse_day(Some(d))).collect::<Result<Vec<_>, _>>()?,
+            };
+            let threads = match flag_num(&flags, "threads")? {
+                Some(n) => n,
+                None => thread::available_parallelism().map_or(1, |n| n.get()),
+            };
+            let timeout = Duration::from_secs_f64(flag_num(&flags, "timeout")?.unwrap_or(60.0));
+            let exe = env::current_exe().map_err(|e| e.to_string())?;
+            let answers = read_answers(flag(&flags, "answers").unwrap_or(answers::PATH))?;
+
+            let outcomes = runner::run_pool(&runner::jobs(&days), threads, |day, part| {
+                runner::run_child(&exe, day, part, &input_path(day), timeout)
+            });
+            if flags.contains(&"--json") {
+                println!("{}", runner::to_json(&outcomes, &answers));
+            } else {
+                print!("{}", runner::table(&outcomes, &answers));
+            }
+            if outcomes.iter().any(|o| matches!(o.verdict(&answers), Some(Verdict::Wrong { .. }))) {
+                Err("some answers don't match the recorded ones".to_string())
+            } else if outcomes.iter().all(|o| matches!(o.status, Status::Solved(_))) {
+                Ok(())
+            } else {
+                Err("some parts were not solved".to_string())
+            }
+        },
+        Some("record") => {
+            let day = parse_day(args.get(1).copied())?;
+            let part = parse_part(args.get(2).copied())?;
+            let answer = match args.get(3) {
+                Some(answer) => answer.to_string(),
+                None => match runner::solve(day, part, &read_input(day, None)?) {
+                    Status::Solved(answer) => answer,
+                    status => return Err(format!("day {} part {}: {:?}", day, part, status)),
+                },
+            };
+            let path = flag(&flags, "answers").unwrap_or(answers::PATH);
+            let mut answers = read_answers(path)?;
+            match answers.record(day, part, &answer)? {
+                Some(old) if old == answer => eprintln!("day {} part {} already recorded as {}", day, part, answer),
+                Some(old) => eprintln!("day {} part {}: {} replaces {}", day, part, answer, old),
+                None => eprintln!("day {} part {}: recorded {}", day, part, answer),
+            }
+            fs::write(path, answers.to_string()).map_err(|e| format!("{}: {}", path, e))
+        },
+        Some("solve") => {
+            let day = parse_day(args.get(1).copied())?;
+            let part = parse_part(args.get(2).copied())?;
+            let input = read_input(day, args.get(3).copied())?;
+            runner::silence_panics();
+            match runner::solve(day, part, &input) {
+                Status::Solved(answer) => {
+                    println!("{}", answer);
+                    Ok(())
+                },
+                Status::Failed(e) => Err(format!("day {} part {} failed: {}", day, part, e)),
+                status => Err(format!("day {} part {}: {:?}", day, part, status)),
+            }
+        },
+        Some("check") => {
+            let days = match &args[1..] {
+                [] => check::DAYS.to_vec(),
+                days => days.iter().map(|d| parse_day(Some(d))).collect::<Result<Vec<_>, _>>()?,
+            };
+            let path = flag(&flags, "input");
+            if path.is_some() && days.len() != 1 {
+                return Err("--input needs exactly one day".to_string());
+            }
+            let results = days.iter()
+                .map(|&day| Ok((day, check::assumptions(day, &read_input(day, path)?)?)))
+                .collect::<Result<Vec<_>, String>>()?;
+            print!("{}", check::report(&results));
+            if results.iter().flat_map(|(_, v)| v).all(|a| a.holds()) {
+                Ok(())
+            } else {
+                Err("some assumptions don't hold; the answers may be wrong".to_string())
+            }
+        },
+        Some("dot") => {
+            let day = parse_day(args.get(1).copied())?;
+            if !dot::DAYS.contains(&day) {
+                return Err(format!("day {} has no graph; try one of {:?}", day, dot::DAYS));
+            }
+            let input = read_input(day, args.get(2).copied())?;
+            print!("{}", dot::export(day, &input, flags.contains(&"--highlight"))?);
+            Ok(())
+        },
+        Some("viz") => {
+            let day = parse_day(args.get(1).copied())?;
+            if !viz::DAYS.contains(&day) {
+                return Err(format!("day {} has no animation; try one of {:?}", day, viz::DAYS));
+            }
+            let input = read_input(day, args.get(2).copied())?;
+            let frames = viz::frames(day, &input, flag_num(&flags, "steps")?)?;
+            let frames = viz::sample(frames, flag_num(&flags, "every")?.unwrap_or(1));
+
+            if let Some(dir) = flag(&flags, "export") {
+                viz::export(&frames, Path::new(dir)).map_err(|e| format!("{}: {}", dir, e))?;
+                eprintln!("{} frames written to {}", frames.len(), dir);
+                return Ok(());
+            }
+            let player = viz::Player {
+                fps: flag_num(&flags, "fps")?.unwrap_or(viz::Player::default().fps),
+                color: !flags.contains(&"--no-color"),
+            };
+            player.play(&frames, &mut io::stdout().lock()).map_err(|e| e.to_string())
+        },
+        Some("generate") => {
+            let day = parse_day(args.get(1).copied())?;
+            let defaults = generate::Options::default();
+            let options = generate::Options {
+                seed: flag_num(&flags, "seed")?.unwrap_or(defaults.seed),
+                scale: flag_num(&flags, "scale")?.unwrap_or(defaults.scale),
+                density: flag_num(&flags, "density")?,
+            };
+            print!("{}", generate::generate(day, &options)?);
+            Ok(())
+        },
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        },
+    }
+}
@@ -0,0 +1,180 @@
+//! Number theory and sequence helpers shared by the days that need them.
+
+use std::{collections::HashMap, hash::Hash};
+
+use num::{BigInt, BigRational, CheckedMul, Integer, One, Zero};
+
+/// `(g, x, y)` with `a*x + b*y = g = gcd(a, b)` and `g >= 0`.
+pub fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < 0 { (-r0, -x0, -y0) } else { (r0, x0, y0) }
+}
+
+/// Merges `x = r1 (mod m1)` and `x = r2 (mod m2)` into `x = r (mod lcm)`;
+/// the moduli need not be coprime. `None` if no `x` fits both, or if the
+/// combined modulus overflows.
+pub fn crt(r1: i128, m1: i128, r2: i128, m2: i128) -> Option<(i128, i128)> {
+    let (g, p, _) = ext_gcd(m1, m2);
+    if (r2 - r1) % g != 0 {
+        return None;
+    }
+    let lcm = (m1 / g).checked_mul(m2)?;
+    let k = ((r2 - r1) / g % (m2 / g)).checked_mul(p)?.rem_euclid(m2 / g);
+    Some((r1.checked_add(m1.checked_mul(k)?)?.rem_euclid(lcm), lcm))
+}
+
+/// [`crt`] over any number of `(remainder, modulus)` pairs.
+pub fn crt_all(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    congruences.into_iter().try_fold((0, 1), |(r, m), (r2, m2)| crt(r, m, r2, m2))
+}
+
+/// Least common multiple of everything, 1 if empty; `None` on overflow.
+pub fn lcm<T: Integer + CheckedMul + Copy>(values: impl IntoIterator<Item = T>) -> Option<T> {
+    values.into_iter().try_fold(T::one(), |acc, v| {
+        if acc.is_zero() || v.is_zero() {
+            return Some(T::zero());
+        }
+        (acc / acc.gcd(&v)).checked_mul(&v)
+    })
+}
+
+pub fn rational(n: i128) -> BigRational {
+    BigRational::from_integer(BigInt::from(n))
+}
+
+/// The polynomial of least degree through `points`, evaluated at `x`.
+/// The `x`s must be distinct.
+pub fn lagrange(points: &[(BigRational, BigRational)], x: &BigRational) -> BigRational {
+    let mut sum = BigRational::zero();
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        let mut term = yi.clone();
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                term = term * (x - xj) / (xi - xj);
+            }
+        }
+        sum += term;
+    }
+    sum
+}
+
+/// `f(0), Δf(0), Δ²f(0), ...` of `values = f(0), f(1), ...`.
+pub fn forward_differences(values: &[BigRational]) -> Vec<BigRational> {
+    let mut row = values.to_vec();
+    let mut ret = Vec::with_capacity(values.len());
+    while let Some(first) = row.first() {
+        ret.push(first.clone());
+        row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+    }
+    ret
+}
+
+/// The degree of polynomial that `values = f(0), f(1), ...` follow, if the
+/// samples show it: some row of differences must be zero with at least one
+/// entry to spare. `None` if there are too few samples to tell.
+pub fn sequence_degree(values: &[BigRational]) -> Option<usize> {
+    let mut row = values.to_vec();
+    let mut degree = 0;
+    while row.len() >= 2 {
+        let next = row.windows(2).map(|w| &w[1] - &w[0]).collect::<Vec<_>>();
+        if next.iter().all(|d| d.is_zero()) {
+            return Some(degree);
+        }
+        row = next;
+        degree += 1;
+    }
+    None
+}
+
+/// `f(t)` for the polynomial through `values = f(0), f(1), ...` by Newton's
+/// forward differences; `t` may lie outside the samples, or be negative.
+pub fn extrapolate(values: &[BigRational], t: &BigRational) -> BigRational {
+    let mut sum = BigRational::zero();
+    // t choose k
+    let mut binomial = BigRational::one();
+    for (k, d) in forward_differences(values).into_iter().enumerate() {
+        sum += &binomial * d;
+        let k = rational(k as i128);
+        binomial = binomial * (t - &k) / (k + BigRational::one());
+    }
+    sum
+}
+
+/// Where a deterministic sequence of states starts repeating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<S> {
+    /// Every state seen, `states[i]` after `i` steps.
+    pub states: Vec<S>,
+    /// The first state that comes round again.
+    pub start: usize,
+    pub len: usize,
+}
+
+impl<S> Cycle<S> {
+    /// The state after `n` steps.
+    pub fn nth(&self, n: usize) -> &S {
+        let i = if n < self.start { n } else { self.start + (n - self.start) % self.len };
+        &self.states[i]
+    }
+}
+
+/// Steps from `init` until some state repeats. Only ends if the sequence
+/// does eventually repeat.
+pub fn find_cycle<S: Clone + Eq + Hash>(init: S, mut step: impl FnMut(&S) -> S) -> Cycle<S> {
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = init;
+    loop {
+        if let Some(&start) = seen.get(&state) {
+            let len = states.len() - start;
+            return Cycle { states, start, len };
+        }
+        seen.insert(state.clone(), states.len());
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+#[test]
+fn test() {
+    assert_eq!(ext_gcd(240, 46), (2, -9, 47));
+    assert_eq!(ext_gcd(-4, 6), (2, 1, 1));
+    assert_eq!(ext_gcd(0, 0).0, 0);
+
+    assert_eq!(crt(2, 4, 3, 6), None);
+    assert_eq!(crt(1, 4, 3, 6), Some((9, 12)));
+    assert_eq!(crt_all([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    assert_eq!(crt_all([]), Some((0, 1)));
+    assert_eq!(crt(0, i128::MAX, 1, i128::MAX - 1), None);
+
+    assert_eq!(lcm([4_u64, 6, 10]), Some(60));
+    assert_eq!(lcm(Vec::<u32>::new()), Some(1));
+    assert_eq!(lcm([3_u8, 0]), Some(0));
+    assert_eq!(lcm([255_u8, 2]), None);
+
+    let squares = (0..5).map(|n| rational(n * n + 1)).collect::<Vec<_>>();
+    assert_eq!(sequence_degree(&squares), Some(2));
+    assert_eq!(sequence_degree(&squares[..3]), None);
+    assert_eq!(extrapolate(&squares, &rational(10)), rational(101));
+    assert_eq!(extrapolate(&squares, &rational(-3)), rational(10));
+    let points = [(rational(1), rational(2)), (rational(3), rational(10)), (rational(4), rational(17))];
+    assert_eq!(lagrange(&points, &rational(10)), rational(101));
+    assert_eq!(lagrange(&points, &(rational(1) / rational(2))), rational(5) / rational(4));
+
+    // 3, 0, 1, 2, 5, 6, 7, 0, ...
+    let cycle = find_cycle(3_u64, |n| (n * n + 1) % 10);
+    assert_eq!((cycle.start, cycle.len), (1, 6));
+    assert_eq!(cycle.states, vec![3, 0, 1, 2, 5, 6, 7]);
+    assert_eq!(*cycle.nth(0), 3);
+    assert_eq!(*cycle.nth(7), 0);
+    assert_eq!(*cycle.nth(1_000_000), cycle.states[1 + (1_000_000 - 1) % 6]);
+}
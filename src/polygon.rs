@@ -0,0 +1,162 @@
+//! Polygons on the integer lattice.
+//!
+//! Vertices are any integer type that widens to `i128`, and all the
+//! arithmetic happens there. That can't overflow while every coordinate is
+//! less than [`LIMIT`] in size and there are fewer than 2^28 vertices:
+//! a product of two differences stays under 2^98, and the shoelace sum of
+//! them under 2^127. Part two of day 18 is nowhere near either.
+//! Orientation is named for x to the right and y up; with `(row, col)`
+//! points, as the grid days use, clockwise and counter-clockwise swap.
+
+use num::integer::gcd;
+
+/// Coordinates must be smaller than this in size.
+pub const LIMIT: i128 = 1 << 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+    /// No area at all, e.g. every vertex on one line.
+    Degenerate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// A closed polygon; the last vertex joins back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon<T> {
+    vertices: Vec<(T, T)>,
+}
+
+impl<T: Copy + Into<i128> + PartialEq> Polygon<T> {
+    /// A repeated first vertex at the end is dropped, so a path that walks
+    /// back to its start can be passed as it is.
+    pub fn new(mut vertices: Vec<(T, T)>) -> Self {
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        debug_assert!(vertices.len() < 1 << 28, "too many vertices to be sure of no overflow");
+        debug_assert!(
+            vertices.iter().all(|&(x, y)| x.into().abs() < LIMIT && y.into().abs() < LIMIT),
+            "a vertex too far out to be sure of no overflow",
+        );
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[(T, T)] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = ((i128, i128), (i128, i128))> + '_ {
+        let wide = |(x, y): (T, T)| (x.into(), y.into());
+        let n = self.vertices.len();
+        (0..n).map(move |i| (wide(self.vertices[i]), wide(self.vertices[(i + 1) % n])))
+    }
+
+    /// Twice the signed area by the shoelace formula, positive when
+    /// counter-clockwise. Twice, so that it stays an integer.
+    pub fn double_signed_area(&self) -> i128 {
+        self.edges().map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1).sum()
+    }
+
+    pub fn double_area(&self) -> i128 {
+        self.double_signed_area().abs()
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        match self.double_signed_area() {
+            a if a > 0 => Orientation::CounterClockwise,
+            a if a < 0 => Orientation::Clockwise,
+            _ => Orientation::Degenerate,
+        }
+    }
+
+    /// Lattice points on the edges.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges().map(|((x1, y1), (x2, y2))| gcd(x2 - x1, y2 - y1)).sum()
+    }
+
+    /// Lattice points strictly inside, by Pick's theorem `A = I + B/2 - 1`.
+    /// Only meaningful for a simple polygon.
+    pub fn interior_points(&self) -> i128 {
+        (self.double_area() - self.boundary_points()) / 2 + 1
+    }
+
+    /// Lattice points inside or on the boundary.
+    pub fn lattice_points(&self) -> i128 {
+        (self.double_area() + self.boundary_points()) / 2 + 1
+    }
+
+    /// Where `p` is relative to the polygon, by counting crossings of a ray
+    /// going right from it.
+    pub fn locate(&self, p: (T, T)) -> Location {
+        let (px, py) = (p.0.into(), p.1.into());
+        let mut inside = false;
+        for ((ax, ay), (bx, by)) in self.edges() {
+            let cross = (bx - ax) * (py - ay) - (px - ax) * (by - ay);
+            if cross == 0 && ax.min(bx) <= px && px <= ax.max(bx) && ay.min(by) <= py && py <= ay.max(by) {
+                return Location::Boundary;
+            }
+            // the edge straddles the ray's line and crosses it right of `p`
+            if (ay > py) != (by > py) && (cross > 0) == (by > ay) {
+                inside = !inside;
+            }
+        }
+        if inside { Location::Inside } else { Location::Outside }
+    }
+
+    /// Inside or on the boundary.
+    pub fn contains(&self, p: (T, T)) -> bool {
+        self.locate(p) != Location::Outside
+    }
+}
+
+#[test]
+fn test() {
+    // 4x3 rectangle
+    let rect = Polygon::new(vec![(0_i64, 0), (4, 0), (4, 3), (0, 3)]);
+    assert_eq!(rect.double_signed_area(), 24);
+    assert_eq!(rect.orientation(), Orientation::CounterClockwise);
+    assert_eq!(rect.boundary_points(), 14);
+    assert_eq!(rect.interior_points(), 3 * 2);
+    assert_eq!(rect.lattice_points(), 5 * 4);
+    assert_eq!(rect.locate((1, 1)), Location::Inside);
+    assert_eq!(rect.locate((4, 2)), Location::Boundary);
+    assert_eq!(rect.locate((0, 0)), Location::Boundary);
+    assert_eq!(rect.locate((5, 1)), Location::Outside);
+    assert_eq!(rect.locate((-1, 3)), Location::Outside);
+
+    // the same walked the other way, closed explicitly
+    let rev = Polygon::new(vec![(0_i64, 0), (0, 3), (4, 3), (4, 0), (0, 0)]);
+    assert_eq!(rev.vertices().len(), 4);
+    assert_eq!(rev.orientation(), Orientation::Clockwise);
+    assert_eq!(rev.interior_points(), 6);
+
+    // a triangle with a slanted edge, and a concave notch
+    let tri = Polygon::new(vec![(0_i128, 0), (4, 0), (0, 4)]);
+    assert_eq!((tri.double_area(), tri.boundary_points(), tri.interior_points()), (16, 12, 3));
+    assert_eq!(tri.locate((2, 2)), Location::Boundary);
+    assert_eq!(tri.locate((1, 2)), Location::Inside);
+    let notch = Polygon::new(vec![(0_i32, 0), (4, 0), (4, 4), (2, 1), (0, 4)]);
+    assert!(notch.contains((1, 1)) && notch.contains((3, 2)));
+    assert!(!notch.contains((2, 2)));
+
+    // no overflow at the edge of the supported range
+    let r = LIMIT as i64 - 1;
+    let far = Polygon::new(vec![(-r, -r), (r, -r), (r, r), (-r, r)]);
+    let side = 2 * r as i128;
+    assert_eq!(far.boundary_points(), 4 * side);
+    assert_eq!(far.double_signed_area(), 2 * side * side);
+    assert_eq!(far.lattice_points(), (side + 1) * (side + 1));
+    assert_eq!(far.locate((r, 0)), Location::Boundary);
+    assert_eq!(far.locate((-r + 1, r - 1)), Location::Inside);
+
+    let line = Polygon::new(vec![(0_i64, 0), (2, 2), (4, 4)]);
+    assert_eq!(line.orientation(), Orientation::Degenerate);
+}
@@ -0,0 +1,286 @@
+//! Property testing with shrinking, mostly for pitting a clever solver
+//! against a slow one that's obviously right.
+//!
+//! Cases come from a seeded [`Rng`], so a failure can be replayed with the
+//! seed it prints. When a case fails it's shrunk greedily: the first
+//! simpler candidate that still fails replaces it, until none does. A panic
+//! in the property counts as a failure like any other.
+//!
+//! `PROP_SEED` and `PROP_CASES` in the environment override the defaults.
+
+use std::{env, fmt, fmt::Debug, ops::RangeInclusive, panic};
+
+/// splitmix64; small, fast and good enough for test inputs.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must not be zero.
+    pub fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (lo, hi) = range.into_inner();
+        lo + self.below((hi - lo) as u64 + 1) as i64
+    }
+
+    /// Uniform in the range, for sizes and indices.
+    pub fn size(&mut self, range: RangeInclusive<usize>) -> usize {
+        self.range(*range.start() as i64..=*range.end() as i64) as usize
+    }
+
+    /// True with probability `num / den`.
+    pub fn chance(&mut self, num: u64, den: u64) -> bool {
+        self.below(den) < num
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub cases: usize,
+    pub seed: u64,
+    /// Give up shrinking after this many simpler failing cases.
+    pub max_shrinks: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let var = |name| env::var(name).ok().and_then(|v| v.parse().ok());
+        Self {
+            cases: var("PROP_CASES").map_or(200, |n| n as usize),
+            seed: var("PROP_SEED").unwrap_or(0x2023_1225),
+            max_shrinks: 1000,
+        }
+    }
+}
+
+impl Config {
+    pub fn cases(self, cases: usize) -> Self {
+        Self { cases, ..self }
+    }
+}
+
+/// A case the property doesn't hold for, before and after shrinking.
+#[derive(Debug, Clone)]
+pub struct Failure<T> {
+    pub seed: u64,
+    pub case: usize,
+    pub original: T,
+    pub minimal: T,
+    /// Why the minimal case fails.
+    pub message: String,
+    pub shrinks: usize,
+}
+
+impl<T: Debug> fmt::Display for Failure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "property failed on case {} (PROP_SEED={})", self.case, self.seed)?;
+        writeln!(f, "minimal counterexample after {} shrinks:", self.shrinks)?;
+        writeln!(f, "{:#?}", self.minimal)?;
+        writeln!(f, "{}", self.message)?;
+        write!(f, "originally: {:?}", self.original)
+    }
+}
+
+fn run<T>(property: &impl Fn(&T) -> Result<(), String>, value: &T) -> Result<(), String> {
+    match panic::catch_unwind(panic::AssertUnwindSafe(|| property(value))) {
+        Ok(result) => result,
+        Err(e) => Err(match e.downcast_ref::<&str>() {
+            Some(s) => format!("panicked: {}", s),
+            None => format!("panicked: {}", e.downcast_ref::<String>().map_or("?", |s| s.as_str())),
+        }),
+    }
+}
+
+/// Runs `property` on `config.cases` generated values, shrinking the
+/// first one it fails on.
+pub fn check<T: Clone>(
+    config: &Config,
+    generate: impl Fn(&mut Rng) -> T,
+    shrink: impl Fn(&T) -> Vec<T>,
+    property: impl Fn(&T) -> Result<(), String>,
+) -> Result<(), Failure<T>> {
+    let mut rng = Rng::new(config.seed);
+    for case in 0..config.cases {
+        let original = generate(&mut rng);
+        let Err(mut message) = run(&property, &original) else { continue };
+
+        let mut minimal = original.clone();
+        let mut shrinks = 0;
+        'shrink: while shrinks < config.max_shrinks {
+            for candidate in shrink(&minimal) {
+                if let Err(m) = run(&property, &candidate) {
+                    minimal = candidate;
+                    message = m;
+                    shrinks += 1;
+                    continue 'shrink;
+                }
+            }
+            break;
+        }
+        return Err(Failure { seed: config.seed, case, original, minimal, message, shrinks });
+    }
+    Ok(())
+}
+
+/// A property that `oracle` and `fast` give the same answer.
+pub fn differential<T, O: PartialEq + Debug>(
+    oracle: impl Fn(&T) -> O,
+    fast: impl Fn(&T) -> O,
+) -> impl Fn(&T) -> Result<(), String> {
+    move |value| {
+        let (expected, actual) = (oracle(value), fast(value));
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(format!("oracle gave {:?}, fast gave {:?}", expected, actual))
+        }
+    }
+}
+
+/// [`check`] with [`differential`], panicking with the minimal
+/// counterexample if the two ever disagree.
+pub fn assert_agree<T: Clone + Debug, O: PartialEq + Debug>(
+    config: &Config,
+    generate: impl Fn(&mut Rng) -> T,
+    shrink: impl Fn(&T) -> Vec<T>,
+    oracle: impl Fn(&T) -> O,
+    fast: impl Fn(&T) -> O,
+) {
+    if let Err(failure) = check(config, generate, shrink, differential(oracle, fast)) {
+        panic!("{}", failure);
+    }
+}
+
+/// Smaller integers, nearest `target` first.
+pub fn shrink_int(n: i64, target: i64) -> Vec<i64> {
+    let mut ret = Vec::new();
+    if n == target {
+        return ret;
+    }
+    ret.push(target);
+    let half = target + (n - target) / 2;
+    if half != target && half != n {
+        ret.push(half);
+    }
+    let step = if n > target { n - 1 } else { n + 1 };
+    if !ret.contains(&step) {
+        ret.push(step);
+    }
+    ret
+}
+
+/// Shorter vectors, then ones with a simpler element; never below `min_len`.
+pub fn shrink_vec<T: Clone>(v: &[T], min_len: usize, elem: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut ret = Vec::new();
+    let n = v.len();
+    if n > min_len {
+        if n / 2 >= min_len && n >= 2 {
+            ret.push(v[..n / 2].to_vec());
+            ret.push(v[n / 2..].to_vec());
+        }
+        for i in 0..n {
+            let mut w = v.to_vec();
+            w.remove(i);
+            ret.push(w);
+        }
+    }
+    for i in 0..n {
+        for e in elem(&v[i]) {
+            let mut w = v.to_vec();
+            w[i] = e;
+            ret.push(w);
+        }
+    }
+    ret
+}
+
+/// Grids with a row or column fewer, or one cell turned into `blank`; at
+/// least one row and column is kept.
+pub fn shrink_grid<T: Clone + PartialEq>(grid: &[Vec<T>], blank: &T) -> Vec<Vec<Vec<T>>> {
+    let mut ret = Vec::new();
+    let (h, w) = (grid.len(), grid.first().map_or(0, |r| r.len()));
+    if h > 1 {
+        for r in 0..h {
+            let mut g = grid.to_vec();
+            g.remove(r);
+            ret.push(g);
+        }
+    }
+    if w > 1 {
+        for c in 0..w {
+            ret.push(grid.iter().map(|row| {
+                let mut row = row.clone();
+                row.remove(c);
+                row
+            }).collect());
+        }
+    }
+    for r in 0..h {
+        for c in 0..w {
+            if grid[r][c] != *blank {
+                let mut g = grid.to_vec();
+                g[r][c] = blank.clone();
+                ret.push(g);
+            }
+        }
+    }
+    ret
+}
+
+/// Rows of characters joined into a puzzle input.
+pub fn render_grid(grid: &[Vec<char>]) -> String {
+    grid.iter().map(|row| row.iter().collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn test() {
+    let mut rng = Rng::new(1);
+    assert!((0..1000).all(|_| (3..=7).contains(&rng.range(3..=7))));
+    assert!((0..1000).map(|_| rng.size(0..=2)).any(|n| n == 2));
+    assert_eq!(Rng::new(5).next_u64(), Rng::new(5).next_u64());
+
+    assert_eq!(shrink_int(10, 0), vec![0, 5, 9]);
+    assert_eq!(shrink_int(-3, 1), vec![1, -1, -2]);
+    assert!(shrink_int(4, 4).is_empty());
+    assert_eq!(shrink_vec(&[1, 2], 1, |_| vec![]), vec![vec![1], vec![2], vec![2], vec![1]]);
+    assert_eq!(shrink_grid(&[vec!['#']], &'.'), vec![vec![vec!['.']]]);
+    assert_eq!(render_grid(&[vec!['#', '.'], vec!['.', '#']]), "#.\n.#\n");
+
+    // "no element reaches 50" fails, and shrinks down to exactly [50]
+    let config = Config { cases: 100, seed: 7, max_shrinks: 1000 };
+    let failure = check(
+        &config,
+        |rng| (0..rng.size(0..=10)).map(|_| rng.range(0..=100)).collect::<Vec<_>>(),
+        |v| shrink_vec(v, 0, |n| shrink_int(*n, 0)),
+        |v| if v.iter().all(|n| *n < 50) { Ok(()) } else { Err(format!("{:?}", v)) },
+    ).unwrap_err();
+    assert_eq!(failure.minimal, vec![50]);
+    assert!(failure.to_string().contains("PROP_SEED=7"));
+
+    // panics count, and the message is kept
+    let failure = check(&config, |rng| rng.range(0..=9), |n| shrink_int(*n, 0), |n| {
+        assert!(*n < 3, "too big");
+        Ok(())
+    }).unwrap_err();
+    assert_eq!((failure.minimal, failure.message.as_str()), (3, "panicked: too big"));
+
+    assert!(check(&config, |rng| rng.range(0..=9), |_| vec![], differential(|n| n * 2, |n| n + n)).is_ok());
+}
@@ -0,0 +1,353 @@
+//! Runs every day's solvers side by side and reports what came out.
+//!
+//! Solvers are plain `fn(&str) -> String`, registered in [`solver`]. The pool
+//! in [`run_pool`] doesn't care how a job runs: tests call [`solve`] in
+//! process, while the CLI starts a child per job with [`run_child`] so that a
+//! solver that hangs or eats all the memory can be killed.
+
+use std::{
+    any::Any,
+    fmt::Write as _,
+    io::Read,
+    panic,
+    path::Path,
+    process::{Command, Stdio},
+    sync::{atomic::{AtomicUsize, Ordering}, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{answers::{Answers, Verdict}, *};
+
+pub type Solver = fn(&str) -> String;
+
+macro_rules! answer {
+    ($f:path) => { Some(|input: &str| $f(input).to_string()) };
+}
+
+/// The solver for one part of one day, if there is one.
+pub fn solver(day: u32, part: u8) -> Option<Solver> {
+    match (day, part) {
+        (1, 1) => answer!(day01::part_1),
+        (1, 2) => answer!(day01::part_2),
+        (2, 1) => answer!(day02::part_1),
+        (2, 2) => answer!(day02::part_2),
+        (3, 1) => answer!(day03::part_1),
+        (3, 2) => answer!(day03::part_2),
+        (4, 1) => answer!(day04::part_1),
+        (4, 2) => answer!(day04::part_2),
+        (5, 1) => answer!(day05::part_1),
+        (5, 2) => answer!(day05::part_2),
+        (6, 1) => answer!(day06::part_1),
+        (6, 2) => answer!(day06::part_2),
+        (7, 1) => answer!(day07::part_1),
+        (7, 2) => answer!(day07::part_2),
+        (8, 1) => answer!(day08::part_1),
+        (8, 2) => answer!(day08::part_2),
+        (9, 1) => answer!(day09::part_1),
+        (9, 2) => answer!(day09::part_2),
+        (10, 1) => answer!(day10::part_1),
+        (10, 2) => answer!(day10::part_2),
+        (11, 1) => answer!(day11::part_1),
+        (11, 2) => answer!(day11::part_2),
+        (12, 1) => answer!(day12::part_1),
+        (12, 2) => answer!(day12::part_2),
+        (13, 1) => answer!(day13::part_1),
+        (13, 2) => answer!(day13::part_2),
+        (14, 1) => answer!(day14::part_1),
+        (14, 2) => answer!(day14::part_2),
+        (15, 1) => answer!(day15::part_1),
+        (15, 2) => answer!(day15::part_2),
+        (16, 1) => answer!(day16::part_1),
+        (16, 2) => answer!(day16::part_2),
+        (17, 1) => answer!(day17::part_1),
+        (17, 2) => answer!(day17::part_2),
+        (18, 1) => answer!(day18::part_1),
+        (18, 2) => answer!(day18::part_2),
+        (19, 1) => answer!(day19::part_1),
+        (19, 2) => answer!(day19::part_2),
+        (20, 1) => answer!(day20::part_1),
+        (20, 2) => answer!(day20::part_2),
+        (21, 1) => answer!(day21::part_1),
+        (21, 2) => answer!(day21::part_2),
+        (22, 1) => answer!(day22::part_1),
+        (22, 2) => answer!(day22::part_2),
+        (23, 1) => answer!(day23::part_1),
+        (23, 2) => answer!(day23::part_2),
+        (24, 1) => answer!(day24::part_1),
+        (24, 2) => answer!(day24::part_2),
+        (25, 1) => answer!(day25::part_1),
+        _ => None,
+    }
+}
+
+/// Every registered `(day, part)` among `days`, in order.
+pub fn jobs(days: &[u32]) -> Vec<(u32, u8)> {
+    days.iter()
+        .flat_map(|day| [(*day, 1), (*day, 2)])
+        .filter(|(day, part)| solver(*day, *part).is_some())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Solved(String),
+    /// Panicked or exited with an error; the message, if any.
+    Failed(String),
+    TimedOut(Duration),
+    NoInput(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub day: u32,
+    pub part: u8,
+    pub status: Status,
+    pub elapsed: Duration,
+}
+
+impl Outcome {
+    /// How the answer compares with the recorded one; `None` without an answer.
+    pub fn verdict(&self, answers: &Answers) -> Option<Verdict> {
+        match &self.status {
+            Status::Solved(a) => Some(answers.check(self.day, self.part, a)),
+            _ => None,
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked".to_string())
+}
+
+/// Runs a solver in this process, turning a panic into [`Status::Failed`].
+///
+/// The panic hook still prints as usual; see [`silence_panics`].
+pub fn solve(day: u32, part: u8, input: &str) -> Status {
+    let Some(f) = solver(day, part) else {
+        return Status::Failed(format!("day {} has no part {}", day, part));
+    };
+    match panic::catch_unwind(|| f(input)) {
+        Ok(answer) => Status::Solved(answer),
+        Err(payload) => Status::Failed(panic_message(payload)),
+    }
+}
+
+/// Stops panics from printing, for a process whose only job is one solver.
+pub fn silence_panics() {
+    panic::set_hook(Box::new(|_| {}));
+}
+
+/// Runs `exe solve <day> <part> <input>` and kills it once `timeout` is up.
+///
+/// The answer is everything on stdout; on failure the last line on stderr
+/// is the message.
+pub fn run_child(exe: &Path, day: u32, part: u8, input: &Path, timeout: Duration) -> Status {
+    if !input.exists() {
+        return Status::NoInput(input.display().to_string());
+    }
+    let mut child = match Command::new(exe)
+        .args(["solve", &day.to_string(), &part.to_string()])
+        .arg(input)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return Status::Failed(e.to_string()),
+    };
+
+    // drain the pipes while waiting, or the child blocks on a full one
+    let drain = |pipe: Option<Box<dyn Read + Send>>| thread::spawn(move || {
+        let mut s = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut s);
+        }
+        s
+    });
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+
+    let start = Instant::now();
+    let exit = loop {
+        match child.try_wait() {
+            Ok(Some(exit)) => break exit,
+            Ok(None) if start.elapsed() < timeout => thread::sleep(Duration::from_millis(1)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Status::TimedOut(timeout);
+            },
+            Err(e) => return Status::Failed(e.to_string()),
+        }
+    };
+
+    let stdout = stdout.join().unwrap_or_default().trim().to_string();
+    let stderr = stderr.join().unwrap_or_default();
+    let stderr = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("").trim().to_string();
+    if exit.success() {
+        Status::Solved(stdout)
+    } else if stderr.is_empty() {
+        Status::Failed(exit.to_string())
+    } else {
+        Status::Failed(stderr)
+    }
+}
+
+/// Runs `jobs` on `threads` workers; outcomes come back by day and part.
+pub fn run_pool<F>(jobs: &[(u32, u8)], threads: usize, exec: F) -> Vec<Outcome>
+where
+    F: Fn(u32, u8) -> Status + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(jobs.len()));
+
+    thread::scope(|s| {
+        for _ in 0..threads.clamp(1, jobs.len().max(1)) {
+            s.spawn(|| {
+                while let Some(&(day, part)) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let start = Instant::now();
+                    let status = exec(day, part);
+                    let outcome = Outcome { day, part, status, elapsed: start.elapsed() };
+                    outcomes.lock().unwrap().push(outcome);
+                }
+            });
+        }
+    });
+
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|o| (o.day, o.part));
+    outcomes
+}
+
+fn duration(d: Duration) -> String {
+    if d.as_secs() > 0 {
+        format!("{:.2}s", d.as_secs_f64())
+    } else {
+        format!("{:.1}ms", d.as_secs_f64() * 1000.0)
+    }
+}
+
+/// A table of answers, whether they match `answers`, and timings, with a
+/// count of what went wrong and which parts aren't confirmed yet.
+pub fn table(outcomes: &[Outcome], answers: &Answers) -> String {
+    let mut s = String::new();
+    let width = outcomes.iter()
+        .map(|o| match &o.status { Status::Solved(a) => a.len(), _ => 0 })
+        .fold("answer".len(), usize::max);
+
+    writeln!(s, "day part  {:<width$}  check  time", "answer").unwrap();
+    for o in outcomes {
+        let time = duration(o.elapsed);
+        match (&o.status, o.verdict(answers)) {
+            (Status::Solved(a), Some(Verdict::Wrong { expected })) => {
+                writeln!(s, "{:>3} {:>4}  {:<width$}  WRONG  {}  expected {}", o.day, o.part, a, time, expected)
+            },
+            (Status::Solved(a), verdict) => {
+                let check = if verdict == Some(Verdict::Correct) { "ok" } else { "?" };
+                writeln!(s, "{:>3} {:>4}  {:<width$}  {:<5}  {}", o.day, o.part, a, check, time)
+            },
+            (Status::Failed(e), _) => writeln!(s, "{:>3} {:>4}  failed: {}", o.day, o.part, e),
+            (Status::TimedOut(t), _) => writeln!(s, "{:>3} {:>4}  timed out after {}", o.day, o.part, duration(*t)),
+            (Status::NoInput(p), _) => writeln!(s, "{:>3} {:>4}  no input at {}", o.day, o.part, p),
+        }.unwrap();
+    }
+
+    let count = |f: fn(&Status) -> bool| outcomes.iter().filter(|o| f(&o.status)).count();
+    let total = outcomes.iter().map(|o| o.elapsed).sum::<Duration>();
+    writeln!(s, "\n{} solved, {} failed, {} timed out, {} without input; {} of solver time",
+        count(|s| matches!(s, Status::Solved(_))),
+        count(|s| matches!(s, Status::Failed(_))),
+        count(|s| matches!(s, Status::TimedOut(_))),
+        count(|s| matches!(s, Status::NoInput(_))),
+        duration(total),
+    ).unwrap();
+
+    let missing = outcomes.iter()
+        .filter(|o| o.verdict(answers) != Some(Verdict::Correct))
+        .map(|o| format!("{}.{}", o.day, o.part))
+        .collect::<Vec<_>>();
+    write!(s, "{} of {} answers confirmed", outcomes.len() - missing.len(), outcomes.len()).unwrap();
+    if missing.is_empty() {
+        writeln!(s).unwrap();
+    } else {
+        writeln!(s, "; not yet: {}", missing.join(", ")).unwrap();
+    }
+    s
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// One object per outcome, e.g.
+/// `{"day":1,"part":1,"status":"solved","answer":"142","verdict":"correct","ms":0.12}`;
+/// a wrong answer also carries `"expected"`.
+pub fn to_json(outcomes: &[Outcome], answers: &Answers) -> String {
+    let items = outcomes.iter()
+        .map(|o| {
+            let (status, key, value) = match &o.status {
+                Status::Solved(a) => ("solved", "answer", json_string(a)),
+                Status::Failed(e) => ("failed", "error", json_string(e)),
+                Status::TimedOut(t) => ("timed_out", "timeout_ms", t.as_millis().to_string()),
+                Status::NoInput(p) => ("no_input", "input", json_string(p)),
+            };
+            let verdict = match o.verdict(answers) {
+                None => String::new(),
+                Some(Verdict::Correct) => ",\"verdict\":\"correct\"".to_string(),
+                Some(Verdict::Unknown) => ",\"verdict\":\"unknown\"".to_string(),
+                Some(Verdict::Wrong { expected }) => {
+                    format!(",\"verdict\":\"wrong\",\"expected\":{}", json_string(&expected))
+                },
+            };
+            format!("{{\"day\":{},\"part\":{},\"status\":\"{}\",\"{}\":{}{},\"ms\":{:.3}}}",
+                o.day, o.part, status, key, value, verdict, o.elapsed.as_secs_f64() * 1000.0)
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", items.join(","))
+}
+
+#[test]
+fn test() {
+    assert_eq!(jobs(&[24, 25]), vec![(24, 1), (24, 2), (25, 1)]);
+    assert!(solver(25, 2).is_none());
+
+    let outcomes = run_pool(&[(9, 2), (1, 1), (9, 1), (2, 1)], 3, |day, part| match day {
+        1 => solve(day, part, "1abc2\npqr3stu8vwx"),
+        9 => solve(day, part, "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45"),
+        _ => solve(day, part, "not a game"),
+    });
+    let statuses = outcomes.iter().map(|o| (o.day, o.part, &o.status)).collect::<Vec<_>>();
+    assert_eq!(statuses[0], (1, 1, &Status::Solved("50".into())));
+    assert!(matches!(statuses[1], (2, 1, Status::Failed(_))));
+    assert_eq!(statuses[2], (9, 1, &Status::Solved("114".into())));
+    assert_eq!(statuses[3], (9, 2, &Status::Solved("2".into())));
+
+    let answers: Answers = "[day01]\npart1 = 50\n[day09]\npart1 = 113".parse().unwrap();
+    let s = table(&outcomes, &answers);
+    assert!(s.contains("  1    1  50      ok "));
+    assert!(s.contains("  9    1  114     WRONG  "));
+    assert!(s.contains("expected 113\n"));
+    assert!(s.contains("3 solved, 1 failed, 0 timed out, 0 without input"));
+    assert!(s.contains("1 of 4 answers confirmed; not yet: 2.1, 9.1, 9.2"));
+
+    let o = Outcome { day: 3, part: 2, status: Status::Failed("bad \"x\"\n".into()), elapsed: Duration::ZERO };
+    assert_eq!(to_json(&[o], &answers), r#"[{"day":3,"part":2,"status":"failed","error":"bad \"x\"\n","ms":0.000}]"#);
+    let o = Outcome { day: 9, part: 1, status: Status::Solved("114".into()), elapsed: Duration::ZERO };
+    assert_eq!(to_json(&[o], &answers),
+        r#"[{"day":9,"part":1,"status":"solved","answer":"114","verdict":"wrong","expected":"113","ms":0.000}]"#);
+}
@@ -0,0 +1,334 @@
+//! 3D vectors, boxes and lines.
+//!
+//! [`Vec3`] works over any number type: machine integers for positions,
+//! `BigInt` or `BigRational` where products would overflow or points fall
+//! between lattice points. The exact line intersections always go through
+//! `BigRational`, whatever the coordinates are.
+
+use std::{
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use num::{BigInt, BigRational, Signed, ToPrimitive, Zero};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+pub type BigVec3 = Vec3<BigInt>;
+pub type RatVec3 = Vec3<BigRational>;
+
+impl<T> Vec3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Vec3<U> {
+        Vec3::new(f(self.x), f(self.y), f(self.z))
+    }
+
+    pub fn to_array(self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl<T: Clone + Into<BigInt>> Vec3<T> {
+    pub fn to_big(&self) -> BigVec3 {
+        self.clone().map(Into::into)
+    }
+
+    pub fn to_rational(&self) -> RatVec3 {
+        self.clone().map(|v| BigRational::from_integer(v.into()))
+    }
+}
+
+impl<T: Clone + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Vec3<T> {
+    pub fn dot(&self, other: &Self) -> T {
+        self.x.clone() * other.x.clone() + self.y.clone() * other.y.clone() + self.z.clone() * other.z.clone()
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Self::new(
+            a.y.clone() * b.z.clone() - a.z.clone() * b.y.clone(),
+            a.z * b.x.clone() - a.x.clone() * b.z,
+            a.x * b.y - a.y * b.x,
+        )
+    }
+
+    /// z of the cross product with both z ignored.
+    pub fn cross_xy(&self, other: &Self) -> T {
+        self.x.clone() * other.y.clone() - self.y.clone() * other.x.clone()
+    }
+
+    /// The Euclidean norm squared, exact.
+    pub fn norm_squared(&self) -> T {
+        self.dot(self)
+    }
+}
+
+impl<T: Clone + Zero + PartialEq> Vec3<T> {
+    pub fn is_zero(&self) -> bool {
+        self.x.is_zero() && self.y.is_zero() && self.z.is_zero()
+    }
+}
+
+impl<T: Signed + Clone> Vec3<T> {
+    pub fn manhattan(&self) -> T {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+}
+
+impl<T: ToPrimitive> Vec3<T> {
+    pub fn norm(&self) -> f64 {
+        let f = |v: &T| v.to_f64().unwrap_or(f64::NAN);
+        (f(&self.x).powi(2) + f(&self.y).powi(2) + f(&self.z).powi(2)).sqrt()
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vec3<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vec3<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Vec3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.map(|v| -v)
+    }
+}
+
+/// Scaling by a scalar.
+impl<T: Clone + Mul<Output = T>> Mul<T> for Vec3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.map(|v| v * rhs.clone())
+    }
+}
+
+impl<T: Clone + Div<Output = T>> Div<T> for Vec3<T> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        self.map(|v| v / rhs.clone())
+    }
+}
+
+impl<T: AddAssign> AddAssign for Vec3<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.z += rhs.z;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Vec3<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self.z -= rhs.z;
+    }
+}
+
+/// `x,y,z`, spaces allowed around each number.
+impl<T: FromStr> FromStr for Vec3<T> {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut it = s.split(',').map(|s| s.trim().parse::<T>().map_err(|_| ()));
+        let x = it.next().ok_or(())??;
+        let y = it.next().ok_or(())??;
+        let z = it.next().ok_or(())??;
+        if it.next().is_some() {
+            return Err(());
+        }
+        Ok(Self::new(x, y, z))
+    }
+}
+
+/// An axis-aligned box holding both corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Aabb<T> {
+    pub min: Vec3<T>,
+    pub max: Vec3<T>,
+}
+
+impl<T: Copy + Ord> Aabb<T> {
+    /// The box spanned by two opposite corners, in either order.
+    pub fn new(a: Vec3<T>, b: Vec3<T>) -> Self {
+        Self {
+            min: Vec3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Vec3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// The smallest box around every point; `None` if there are none.
+    pub fn around(points: impl IntoIterator<Item = Vec3<T>>) -> Option<Self> {
+        let mut it = points.into_iter();
+        let first = it.next()?;
+        Some(it.fold(Self::new(first, first), |b, p| b.union(&Self::new(p, p))))
+    }
+
+    pub fn contains(&self, p: &Vec3<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let min = Vec3::new(self.min.x.max(other.min.x), self.min.y.max(other.min.y), self.min.z.max(other.min.z));
+        let max = Vec3::new(self.max.x.min(other.max.x), self.max.y.min(other.max.y), self.max.z.min(other.max.z));
+        (min.x <= max.x && min.y <= max.y && min.z <= max.z).then_some(Self { min, max })
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intersect(other).is_some()
+    }
+
+    /// The smallest box around both.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(
+            Vec3::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+            Vec3::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)),
+        )
+    }
+}
+
+/// `origin + t * dir` for every `t`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Line<T> {
+    pub origin: Vec3<T>,
+    pub dir: Vec3<T>,
+}
+
+/// A [`Line`] only for `t >= 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ray<T> {
+    pub origin: Vec3<T>,
+    pub dir: Vec3<T>,
+}
+
+impl<T: Clone + Into<BigInt>> Line<T> {
+    pub fn new(origin: Vec3<T>, dir: Vec3<T>) -> Self {
+        Self { origin, dir }
+    }
+
+    pub fn at(&self, t: &BigRational) -> RatVec3 {
+        self.origin.to_rational() + self.dir.to_rational() * t.clone()
+    }
+
+    /// `(t, s)` such that `self.at(t)` and `other.at(s)` agree in x and y;
+    /// `None` if the lines are parallel there.
+    pub fn intersect_xy(&self, other: &Self) -> Option<(BigRational, BigRational)> {
+        let (v1, v2) = (self.dir.to_big(), other.dir.to_big());
+        let denom = v1.cross_xy(&v2);
+        if denom.is_zero() {
+            return None;
+        }
+        // self.origin + t * v1 = other.origin + s * v2
+        let d = other.origin.to_big() - self.origin.to_big();
+        let t = BigRational::new(d.cross_xy(&v2), denom.clone());
+        let s = BigRational::new(d.cross_xy(&v1), denom);
+        Some((t, s))
+    }
+
+    /// `(t, s)` such that `self.at(t) == other.at(s)`; `None` if the lines
+    /// are parallel or skew.
+    pub fn intersect(&self, other: &Self) -> Option<(BigRational, BigRational)> {
+        let (v1, v2) = (self.dir.to_big(), other.dir.to_big());
+        let d = other.origin.to_big() - self.origin.to_big();
+
+        // d x v2 = t (v1 x v2)
+        let n = v1.cross(&v2);
+        let nn = n.norm_squared();
+        if nn.is_zero() {
+            return None;
+        }
+        let t = BigRational::new(d.cross(&v2).dot(&n), nn.clone());
+        let s = BigRational::new(d.cross(&v1).dot(&n), nn);
+        (self.at(&t) == other.at(&s)).then_some((t, s))
+    }
+}
+
+impl<T: Clone + Into<BigInt>> Ray<T> {
+    pub fn new(origin: Vec3<T>, dir: Vec3<T>) -> Self {
+        Self { origin, dir }
+    }
+
+    pub fn line(&self) -> Line<T> {
+        Line::new(self.origin.clone(), self.dir.clone())
+    }
+
+    /// Like [`Line::intersect_xy`], but only ahead on both rays.
+    pub fn intersect_xy(&self, other: &Self) -> Option<(BigRational, BigRational)> {
+        self.line().intersect_xy(&other.line()).filter(|(t, s)| !t.is_negative() && !s.is_negative())
+    }
+
+    /// Like [`Line::intersect`], but only ahead on both rays.
+    pub fn intersect(&self, other: &Self) -> Option<(BigRational, BigRational)> {
+        self.line().intersect(&other.line()).filter(|(t, s)| !t.is_negative() && !s.is_negative())
+    }
+}
+
+#[test]
+fn test() {
+    let a = Vec3::new(1_i64, 2, 3);
+    let b: Vec3<i64> = " 4, -5,6".parse().unwrap();
+    assert_eq!(a + b, Vec3::new(5, -3, 9));
+    assert_eq!(a - b, Vec3::new(-3, 7, -3));
+    assert_eq!(-a * 2, Vec3::new(-2, -4, -6));
+    assert_eq!(b / 2, Vec3::new(2, -2, 3));
+    assert_eq!(a.dot(&b), 12);
+    assert_eq!(a.cross(&b), Vec3::new(27, 6, -13));
+    assert_eq!(a.cross(&b).dot(&a), 0);
+    assert_eq!(b.manhattan(), 15);
+    assert_eq!(Vec3::new(2, 3, 6).norm(), 7.0);
+    let mut c = a;
+    c += b;
+    c -= a;
+    assert_eq!(c, b);
+    assert!("1,2".parse::<Vec3<i64>>().is_err());
+    assert!("1,2,3,4".parse::<Vec3<i64>>().is_err());
+
+    // no overflow once widened
+    let big = Vec3::new(i64::MAX, i64::MAX, 0).to_big();
+    assert_eq!(big.norm_squared(), BigInt::from(i64::MAX) * BigInt::from(i64::MAX) * 2);
+
+    let bounds = Aabb::new(Vec3::new(3, 0, 0), Vec3::new(0, 3, 3));
+    assert_eq!(bounds.min, Vec3::new(0, 0, 0));
+    assert!(bounds.contains(&Vec3::new(3, 3, 3)) && !bounds.contains(&Vec3::new(4, 0, 0)));
+    let other = Aabb::new(Vec3::new(3, 3, 3), Vec3::new(5, 5, 5));
+    assert_eq!(bounds.intersect(&other), Some(Aabb::new(Vec3::new(3, 3, 3), Vec3::new(3, 3, 3))));
+    assert!(!bounds.overlaps(&Aabb::new(Vec3::new(4, 0, 0), Vec3::new(5, 0, 0))));
+    assert_eq!(Aabb::around([a, b]), Some(Aabb::new(Vec3::new(1, -5, 3), Vec3::new(4, 2, 6))));
+
+    let l1 = Line::new(Vec3::new(0_i64, 0, 0), Vec3::new(1, 1, 1));
+    let l2 = Line::new(Vec3::new(4_i64, 0, 2), Vec3::new(-1, 1, 0));
+    let (t, s) = l1.intersect(&l2).unwrap();
+    assert_eq!(l1.at(&t), l2.at(&s));
+    assert_eq!(l1.at(&t), Vec3::new(2, 2, 2).to_rational());
+    let skew = Line::new(Vec3::new(0_i64, 0, 1), Vec3::new(0, 1, 0));
+    assert!(Line::new(Vec3::new(0, 0, 0), Vec3::new(1, 0, 0)).intersect(&skew).is_none());
+    assert!(l1.intersect_xy(&Line::new(Vec3::new(0, 1, 0), Vec3::new(2, 2, 5))).is_none());
+
+    let r1 = Ray::new(Vec3::new(0_i64, 0, 0), Vec3::new(1, 0, 0));
+    assert!(r1.intersect_xy(&Ray::new(Vec3::new(2, 1, 0), Vec3::new(0, -1, 0))).is_some());
+    assert!(r1.intersect_xy(&Ray::new(Vec3::new(-2, 1, 0), Vec3::new(0, -1, 0))).is_none());
+}
@@ -0,0 +1,432 @@
+//! Terminal rendering and animation of the grid puzzles.
+//!
+//! A day turns its simulation into a list of [`Frame`]s; [`Player`] shows
+//! them in the terminal at a given rate, and [`export`] writes them out as
+//! plain text files.
+
+use std::{fs, io::{self, Write}, path::Path, thread, time::Duration};
+
+use crate::{day10, day14, day16, day17, day21, day23};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+}
+
+impl Color {
+    /// ANSI foreground code.
+    fn code(self) -> u8 {
+        match self {
+            Self::Red => 31,
+            Self::Green => 32,
+            Self::Yellow => 33,
+            Self::Blue => 34,
+            Self::Magenta => 35,
+            Self::Cyan => 36,
+            Self::Gray => 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Option<Color>,
+}
+
+/// One picture of a grid, with a title line above it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub title: String,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    /// Uncoloured cells from the lines of `text`.
+    pub fn from_text(title: &str, text: &str) -> Self {
+        let rows = text.trim()
+            .lines()
+            .map(|line| line.trim().chars().map(|ch| Cell { ch, color: None }).collect())
+            .collect();
+        Self { title: title.to_string(), rows }
+    }
+
+    pub fn from_chars(title: &str, v: &[Vec<char>]) -> Self {
+        let rows = v.iter()
+            .map(|row| row.iter().map(|&ch| Cell { ch, color: None }).collect())
+            .collect();
+        Self { title: title.to_string(), rows }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, |r| r.len())
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<Cell> {
+        self.rows.get(row)?.get(col).copied()
+    }
+
+    /// Replaces a cell; out of range is ignored.
+    pub fn set(&mut self, row: usize, col: usize, ch: char, color: Option<Color>) {
+        if let Some(cell) = self.rows.get_mut(row).and_then(|r| r.get_mut(col)) {
+            *cell = Cell { ch, color };
+        }
+    }
+
+    /// Colours a cell, keeping its character.
+    pub fn paint(&mut self, row: usize, col: usize, color: Color) {
+        if let Some(cell) = self.rows.get_mut(row).and_then(|r| r.get_mut(col)) {
+            cell.color = Some(color);
+        }
+    }
+
+    /// Colours every cell showing `ch`.
+    pub fn paint_all(&mut self, ch: char, color: Color) {
+        for cell in self.rows.iter_mut().flatten().filter(|c| c.ch == ch) {
+            cell.color = Some(color);
+        }
+    }
+
+    /// The title and the grid without colours, as exported.
+    pub fn plain(&self) -> String {
+        let mut s = format!("{}\n", self.title);
+        for row in self.rows.iter() {
+            s.extend(row.iter().map(|c| c.ch));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Like `plain`, with ANSI escapes around each run of one colour.
+    pub fn ansi(&self) -> String {
+        let mut s = format!("\x1b[1m{}\x1b[0m\n", self.title);
+        for row in self.rows.iter() {
+            let mut current = None;
+            for cell in row.iter() {
+                if cell.color != current {
+                    match cell.color {
+                        Some(color) => s.push_str(&format!("\x1b[{}m", color.code())),
+                        None => s.push_str("\x1b[0m"),
+                    }
+                    current = cell.color;
+                }
+                s.push(cell.ch);
+            }
+            if current.is_some() {
+                s.push_str("\x1b[0m");
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+/// Shows frames one after the other, redrawing the screen in place.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Player {
+    /// Frames per second.
+    pub fps: f64,
+    pub color: bool,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self { fps: 10.0, color: true }
+    }
+}
+
+impl Player {
+    pub fn play<W: Write>(&self, frames: &[Frame], out: &mut W) -> io::Result<()> {
+        let delay = Duration::from_secs_f64(1.0 / self.fps.max(0.001));
+        for (i, frame) in frames.iter().enumerate() {
+            // home and clear
+            write!(out, "\x1b[H\x1b[2J")?;
+            let text = if self.color { frame.ansi() } else { frame.plain() };
+            write!(out, "{}", text)?;
+            out.flush()?;
+            if i + 1 < frames.len() {
+                thread::sleep(delay);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes `frame-0000.txt`, `frame-0001.txt`, ... into `dir`, creating it
+/// if needed.
+pub fn export(frames: &[Frame], dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (i, frame) in frames.iter().enumerate() {
+        fs::write(dir.join(format!("frame-{:04}.txt", i)), frame.plain())?;
+    }
+    Ok(())
+}
+
+/// Every `every`th frame, always keeping the last one.
+pub fn sample(frames: Vec<Frame>, every: usize) -> Vec<Frame> {
+    let n = frames.len();
+    frames.into_iter()
+        .enumerate()
+        .filter(|(i, _)| i % every.max(1) == 0 || i + 1 == n)
+        .map(|(_, f)| f)
+        .collect()
+}
+
+/// Days with an animation.
+pub const DAYS: [u32; 6] = [10, 14, 16, 17, 21, 23];
+
+/// The animation of `day` for `input`. `steps` is the length of the
+/// simulation where there's one to choose: spin cycles for day 14 and
+/// garden steps for day 21.
+pub fn frames(day: u32, input: &str, steps: Option<usize>) -> Result<Vec<Frame>, String> {
+    let bad_input = || format!("day {}: can't parse input", day);
+    match day {
+        10 => {
+            let maze = day10::parse_maze(input).ok_or_else(bad_input)?;
+            Ok(pipe_loop(input, &maze))
+        },
+        14 => {
+            let platform = day14::parse(input).ok_or_else(bad_input)?;
+            Ok(spin_cycles(platform, steps.unwrap_or(3)))
+        },
+        16 => {
+            let contraption: day16::Contraption = input.parse().map_err(|_| bad_input())?;
+            Ok(beam(input, &contraption))
+        },
+        17 => {
+            let map: day17::HeatMap = input.parse().map_err(|_| bad_input())?;
+            let route = day17::Crucible::NORMAL.route(&map, map.top_left(), map.bottom_right())
+                .ok_or("no route")?;
+            Ok(crucible(input, &map, &route))
+        },
+        21 => {
+            let garden: day21::Garden = input.parse().map_err(|_| bad_input())?;
+            Ok(garden_steps(input, &garden, steps.unwrap_or(64)))
+        },
+        23 => {
+            let map: day23::TrailMap = input.parse().map_err(|_| bad_input())?;
+            let graph = map.junction_graph(true);
+            let hike = graph.longest_hike().ok_or("no hike")?;
+            Ok(long_hike(input, &graph, &hike))
+        },
+        _ => Err(format!("day {} has no animation", day)),
+    }
+}
+
+/// The main loop traced in chunks, then the tiles it encloses.
+fn pipe_loop(input: &str, maze: &day10::Maze) -> Vec<Frame> {
+    let cells = maze.iter().map(|(_, idx)| (idx.row, idx.col)).collect::<Vec<_>>();
+
+    let mut frame = Frame::from_text("loop", input);
+    let mut frames = vec![frame.clone()];
+    let chunk = cells.len().div_ceil(60).max(1);
+    for (i, v) in cells.chunks(chunk).enumerate() {
+        for &(row, col) in v {
+            frame.paint(row, col, Color::Cyan);
+        }
+        frame.title = format!("loop: {} of {} pipes", ((i + 1) * chunk).min(cells.len()), cells.len());
+        frames.push(frame.clone());
+    }
+
+    // a tile is inside if a ray to its left crosses the loop an odd number of times
+    let mut inside = 0;
+    for (row, nodes) in maze.maze.iter().enumerate() {
+        let mut crossings = 0;
+        for (col, node) in nodes.iter().enumerate() {
+            if node.is_border {
+                if node.pipe.is_some_and(|p| p.hash_up()) {
+                    crossings += 1;
+                }
+            } else if crossings % 2 == 1 {
+                frame.set(row, col, 'I', Some(Color::Green));
+                inside += 1;
+            } else {
+                frame.paint(row, col, Color::Gray);
+            }
+        }
+    }
+    frame.title = format!("loop: {} pipes, {} tiles inside", cells.len(), inside);
+    frames.push(frame);
+    frames
+}
+
+type Tilt = fn(Vec<Vec<char>>) -> Vec<Vec<char>>;
+
+/// The platform after every tilt of the first `cycles` spin cycles.
+fn spin_cycles(mut v: Vec<Vec<char>>, cycles: usize) -> Vec<Frame> {
+    let frame = |title: String, v: &[Vec<char>]| {
+        let mut f = Frame::from_chars(&title, v);
+        f.paint_all('O', Color::Yellow);
+        f.paint_all('#', Color::Gray);
+        f
+    };
+
+    let mut frames = vec![frame("start".to_string(), &v)];
+    let tilts: [(&str, Tilt); 4] = [
+        ("north", day14::tilt_north),
+        ("west", day14::tilt_west),
+        ("south", day14::tilt_south),
+        ("east", day14::tilt_east),
+    ];
+    for cycle in 1..=cycles {
+        for (name, tilt) in tilts {
+            v = tilt(v);
+            frames.push(frame(format!("cycle {}: {}", cycle, name), &v));
+        }
+    }
+    frames
+}
+
+/// The beam from the top left spreading one cell per frame.
+fn beam(input: &str, contraption: &day16::Contraption) -> Vec<Frame> {
+    let start = day16::Beam::new(0, 0, day16::Direction::Right);
+    let mut frame = Frame::from_text("beam", input);
+    let mut frames = Vec::new();
+    let mut energized = vec![vec![false; contraption.width()]; contraption.height()];
+    let mut count = 0;
+    for (i, front) in contraption.wavefronts(start).iter().enumerate() {
+        // fade the previous front
+        for (row, v) in energized.iter().enumerate() {
+            for (col, e) in v.iter().enumerate() {
+                if *e {
+                    frame.paint(row, col, Color::Yellow);
+                }
+            }
+        }
+        for b in front.iter() {
+            if !energized[b.row][b.col] {
+                energized[b.row][b.col] = true;
+                count += 1;
+            }
+            frame.paint(b.row, b.col, Color::Red);
+        }
+        frame.title = format!("beam: step {}, {} energized", i, count);
+        frames.push(frame.clone());
+    }
+    frames
+}
+
+/// The optimal crucible route drawn a block at a time.
+fn crucible(input: &str, map: &day17::HeatMap, route: &day17::Route) -> Vec<Frame> {
+    let drawn = Frame::from_text("", &route.render(map));
+    let mut frame = Frame::from_text("route", input);
+    let mut frames = vec![frame.clone()];
+    let mut loss = 0;
+    for (i, pos) in route.path.iter().enumerate().skip(1) {
+        loss += map.heat(*pos);
+        let ch = drawn.get(pos.row, pos.col).map_or('*', |c| c.ch);
+        frame.set(pos.row, pos.col, ch, Some(Color::Red));
+        frame.title = format!("route: {} blocks, heat loss {}", i, loss);
+        frames.push(frame.clone());
+    }
+    frames
+}
+
+/// Plots the elf can stop on after each of the first `steps` steps.
+fn garden_steps(input: &str, garden: &day21::Garden, steps: usize) -> Vec<Frame> {
+    let dist = garden.tile_distances();
+    let mut base = Frame::from_text("garden", input);
+    base.paint_all('#', Color::Gray);
+
+    (0..=steps)
+        .map(|step| {
+            let mut frame = base.clone();
+            let mut count = 0;
+            for (row, v) in dist.iter().enumerate() {
+                for (col, d) in v.iter().enumerate() {
+                    if d.is_some_and(|d| d <= step && d % 2 == step % 2) {
+                        frame.set(row, col, 'O', Some(Color::Green));
+                        count += 1;
+                    }
+                }
+            }
+            frame.title = format!("garden: step {}, {} plots", step, count);
+            frame
+        })
+        .collect()
+}
+
+/// The longest hike, one corridor between junctions per frame.
+fn long_hike(input: &str, graph: &day23::JunctionGraph, hike: &day23::Hike) -> Vec<Frame> {
+    let mut frame = Frame::from_text("hike", input);
+    frame.paint_all('#', Color::Gray);
+    for pos in graph.nodes.iter() {
+        frame.paint(pos.row, pos.col, Color::Magenta);
+    }
+
+    let mut frames = vec![frame.clone()];
+    let mut steps = 0;
+    for (i, pos) in hike.route.iter().enumerate() {
+        frame.set(pos.row, pos.col, 'O', Some(Color::Red));
+        steps = i;
+        if graph.nodes.contains(pos) && i > 0 {
+            frame.title = format!("hike: {} steps", steps);
+            frames.push(frame.clone());
+        }
+    }
+    frame.title = format!("hike: {} steps", steps);
+    if frames.last() != Some(&frame) {
+        frames.push(frame);
+    }
+    frames
+}
+
+#[test]
+fn test() {
+    let mut frame = Frame::from_text("t", "ab\ncd");
+    frame.paint(0, 1, Color::Red);
+    frame.paint(1, 0, Color::Red);
+    frame.set(5, 5, 'x', None);
+    assert_eq!(frame.plain(), "t\nab\ncd\n");
+    assert_eq!(frame.ansi(), "\x1b[1mt\x1b[0m\na\x1b[31mb\x1b[0m\n\x1b[31mc\x1b[0md\n");
+
+    let v = (0..5).map(|i| Frame::from_text(&i.to_string(), ".")).collect::<Vec<_>>();
+    let titles = sample(v, 3).iter().map(|f| f.title.clone()).collect::<Vec<_>>();
+    assert_eq!(titles, ["0", "3", "4"]);
+
+    let input = {"
+...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........
+    "};
+    let garden = frames(21, input, Some(6)).unwrap();
+    assert_eq!(garden.len(), 7);
+    assert_eq!(garden[6].title, "garden: step 6, 16 plots");
+    assert_eq!(garden[6].plain().matches('O').count(), 16);
+
+    let input = {"
+..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........
+    "};
+    let pipes = frames(10, input, None).unwrap();
+    assert_eq!(pipes.last().unwrap().title, "loop: 44 pipes, 4 tiles inside");
+
+    let mut out = Vec::new();
+    Player { fps: 1000.0, color: false }.play(&pipes[..2], &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap().matches("\x1b[2J").count(), 2);
+}